@@ -1,4 +1,12 @@
+use crate::format::json::{self, DecodeError, FromJson, ToJson, Value};
+use crate::format::table::{Align, Column, Record, Style, Table};
+use std::borrow::Cow;
+use std::fmt::{self, Display, Formatter};
+
 pub fn format_string() {
+  // The examples print literals and over-precise floats on purpose.
+  #![allow(dead_code, clippy::print_literal, clippy::excessive_precision)]
+
   // Formatted print
 
   /*
@@ -49,7 +57,11 @@ pub fn format_string() {
 
   // A02: Add a println! macro that prints: Pi is roughly 3.142 by controlling the number of decimal places shown.
   let pi: f32 = 3.141562654;
-  println!("Pi number is {0:.3}", pi)
+  println!("Pi number is {0:.3}", pi);
+
+  // An `f32` can't hold all those digits, this is what is really stored
+  use crate::inspect::float::Float;
+  println!("{}", Float::from(pi).table(Some("3.141562654")));
 }
 
 pub fn display() {
@@ -159,71 +171,206 @@ pub fn testcase_list() {
   let v2 = List(vec![9, 8, 33]);
 
   println!("{:?}", v2);
+
+  // A generic `List` works for any `Display` type, and the delimiters,
+  // separator, indices and truncation can be configured.
+  use crate::format::list;
+
+  let indexed = list::List::new(vec![9, 8, 33]).indexed(true);
+  println!("{}", indexed);
+
+  let words = list::List::new(vec!["a", "b", "c"])
+    .delimiters("(", ")")
+    .separator(" | ");
+  println!("{}", words);
+
+  let long = list::List::new((1..=1000).collect()).truncate(3);
+  println!("{}", long);
+
+  let floats = list::List::new(vec![1.0f64, 2.25, 3.875]);
+  println!("{:.2}", floats);
+
+  // `{:#}` prints one element per line, nesting the indentation
+  let nested = list::List::new(vec![
+    list::List::new(vec![1, 2]),
+    list::List::new(vec![3, 4, 5, 6]).truncate(2),
+    list::List::new(vec![]),
+  ]);
+  println!("{}", nested);
+  println!("{:#}", nested);
 }
 
-pub fn formatting() {
-  /*
-   * This formatting functionality is implemented via traits, and there is one trait
-   * for each argument type. The most common formatting trait is Display, which
-   * handles cases where the argument type is left unspecified: {} for instance.
-   */
+/*
+ * This formatting functionality is implemented via traits, and there is one trait
+ * for each argument type. The most common formatting trait is Display, which
+ * handles cases where the argument type is left unspecified: {} for instance.
+ */
+
+pub struct City {
+  // Borrowed for the literals below, owned when read from JSON
+  pub name: Cow<'static, str>,
+  // Latitude
+  pub lat: f32,
+  // Longitude
+  pub lon: f32,
+}
+
+impl City {
+  // Latitude as shown by `Display`, e.g. `53.348°N`
+  pub fn latitude(&self) -> String {
+    let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+    format!("{:.3}°{}", self.lat.abs(), lat_c)
+  }
 
-  use std::fmt::{self, Display, Formatter};
+  // Longitude as shown by `Display`, e.g. `6.260°W`
+  pub fn longitude(&self) -> String {
+    let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
+    format!("{:.3}°{}", self.lon.abs(), lon_c)
+  }
+}
 
-  struct City {
-    name: &'static str,
-    // Latitude
-    lat: f32,
-    // Longitude
-    lon: f32,
+impl Display for City {
+  // `f` is a buffer, and this method must write the formatted string into it
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    // `write!` is like `format!`, but it will write the formatted string
+    // into a buffer (the first argument)
+    write!(f, "{}: {} {}", self.name, self.latitude(), self.longitude())
   }
+}
 
-  impl Display for City {
-    // `f` is a buffer, and this method must write the formatted string into it
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-      let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-      let lon_c = if self.lon >= 0.0 { 'E' } else { 'W' };
-
-      // `write!` is like `format!`, but it will write the formatted string
-      // into a buffer (the first argument)
-      write!(
-        f,
-        "{}: {:.3}°{} {:.3}°{}",
-        self.name,
-        self.lat.abs(),
-        lat_c,
-        self.lon.abs(),
-        lon_c
-      )
-    }
+impl Record for City {
+  fn columns() -> Vec<Column> {
+    vec![
+      Column::new("City", Align::Left),
+      Column::new("Latitude", Align::Right),
+      Column::new("Longitude", Align::Right),
+    ]
   }
 
-  #[derive(Debug)]
-  struct Color {
-    red: u8,
-    green: u8,
-    blue: u8,
+  fn cells(&self) -> Vec<String> {
+    vec![self.name.to_string(), self.latitude(), self.longitude()]
+  }
+}
+
+// `{"name": "Oslo", "lat": 59.95, "lon": 10.75}`
+impl ToJson for City {
+  fn to_json(&self) -> Value {
+    Value::Object(vec![
+      ("name".to_owned(), self.name.to_json()),
+      ("lat".to_owned(), self.lat.to_json()),
+      ("lon".to_owned(), self.lon.to_json()),
+    ])
+  }
+}
+
+impl FromJson for City {
+  fn from_json(value: &Value) -> Result<City, DecodeError> {
+    json::object(value, &["name", "lat", "lon"])?;
+    Ok(City {
+      name: Cow::Owned(json::field(value, "name")?),
+      lat: json::field(value, "lat")?,
+      lon: json::field(value, "lon")?,
+    })
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+  pub red: u8,
+  pub green: u8,
+  pub blue: u8,
+}
+
+impl Display for Color {
+  // `f` is a buffer, and this method must write the formatted string into it
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    // let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
+
+    let exa = format!("{:02X}{:02X}{:02X}", self.red, self.green, self.blue);
+
+    // `write!` is like `format!`, but it will write the formatted string
+    // into a buffer (the first argument)
+    write!(
+      f,
+      "RGB ({}, {}, {}) 0x{}",
+      self.red, self.green, self.blue, exa
+    )
+
+    // Alternative
+
+    // write!(
+    //   f,
+    //   "RGB ({red}, {green}, {blue}) 0x{red:02X}{green:02X}{blue:02X}",
+    //   red = self.red,
+    //   green = self.green, blue=self.blue
+    // )
+  }
+}
+
+impl Record for Color {
+  fn columns() -> Vec<Column> {
+    vec![
+      Column::new("Red", Align::Right),
+      Column::new("Green", Align::Right),
+      Column::new("Blue", Align::Right),
+      Column::new("Hex", Align::Center),
+    ]
+  }
+
+  fn cells(&self) -> Vec<String> {
+    vec![
+      self.red.to_string(),
+      self.green.to_string(),
+      self.blue.to_string(),
+      format!("0x{:02X}{:02X}{:02X}", self.red, self.green, self.blue),
+    ]
+  }
+}
+
+// `{"red": 128, "green": 255, "blue": 90}`
+impl ToJson for Color {
+  fn to_json(&self) -> Value {
+    Value::Object(vec![
+      ("red".to_owned(), self.red.to_json()),
+      ("green".to_owned(), self.green.to_json()),
+      ("blue".to_owned(), self.blue.to_json()),
+    ])
+  }
+}
+
+impl FromJson for Color {
+  fn from_json(value: &Value) -> Result<Color, DecodeError> {
+    json::object(value, &["red", "green", "blue"])?;
+    Ok(Color {
+      red: json::field(value, "red")?,
+      green: json::field(value, "green")?,
+      blue: json::field(value, "blue")?,
+    })
   }
+}
+
+pub fn formatting() {
+  #![allow(clippy::excessive_precision)]
 
-  for city in [
+  let cities = [
     City {
-      name: "Dublin",
+      name: "Dublin".into(),
       lat: 53.347778,
       lon: -6.259722,
     },
     City {
-      name: "Oslo",
+      name: "Oslo".into(),
       lat: 59.95,
       lon: 10.75,
     },
     City {
-      name: "Vancouver",
+      name: "Vancouver".into(),
       lat: 49.25,
       lon: -123.1,
     },
-  ]
-  .iter()
-  {
+  ];
+
+  for city in cities.iter() {
     println!("{}", *city);
   }
 
@@ -249,33 +396,34 @@ pub fn formatting() {
     println!("{:?}", *color);
   }
 
-  impl Display for Color {
-    // `f` is a buffer, and this method must write the formatted string into it
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-      // let lat_c = if self.lat >= 0.0 { 'N' } else { 'S' };
-
-      let exa = format!("{:02X}{:02X}{:02X}", self.red, self.green, self.blue);
-
-      // `write!` is like `format!`, but it will write the formatted string
-      // into a buffer (the first argument)
-      write!(
-        f,
-        "RGB ({}, {}, {}) 0x{}",
-        self.red, self.green, self.blue, exa
-      )
-
-      // Alternative
-
-      // write!(
-      //   f,
-      //   "RGB ({red}, {green}, {blue}) 0x{red:02X}{green:02X}{blue:02X}",
-      //   red = self.red,
-      //   green = self.green, blue=self.blue
-      // )
-    }
-  }
-
   for color in colors.iter() {
     println!("{}", *color);
   }
+
+  // Printing one line at a time doesn't line up the columns, a `Table` works
+  // out the width of every column first.
+  println!("{}", Table::from_records(&cities));
+  println!("{}", Table::from_records(&colors).align(3, Align::Left));
+  print!("{}", Table::from_records(&colors).render(Style::Markdown));
+  print!("{}", Table::from_records(&cities).render(Style::Csv));
+
+  // Or saved as JSON, and loaded back. `{:#}` prints it on several lines.
+  let saved = cities.to_json();
+  println!("{:#}", saved);
+  let loaded = Vec::<City>::from_json(&saved).map(|cities| cities.to_json());
+  println!(
+    "cities survive a round trip through JSON: {}",
+    loaded == Ok(saved)
+  );
+  let colors_json = colors.to_json().to_string();
+  println!("{}", colors_json);
+  println!(
+    "colors survive a round trip through JSON: {}",
+    json::from_str::<Vec<Color>>(&colors_json) == Ok(colors.to_vec())
+  );
+  // A color is three bytes
+  let too_bright = r#"{"red": 300, "green": 0, "blue": 0}"#;
+  if let Err(error) = json::from_str::<Color>(too_bright) {
+    println!("{}: {}", too_bright, error);
+  }
 }
@@ -1,6 +1,9 @@
-use std::fmt::{self, Display, Formatter};
-
 pub fn primitives_and_operations() {
+  // The variables below only exist to show their types.
+  #![allow(unused_variables, unused_assignments, clippy::nonminimal_bool)]
+
+  use crate::inspect::overflow::IntType;
+
   /*
    * Primitives
    * Rust provides access to a wide variety of primitives. A sample includes:
@@ -55,6 +58,11 @@ pub fn primitives_and_operations() {
   // TODO ^ Try changing `1i32` to `1u32` to see why the type is important
   // This is a problem only if the result is negative
   // 3u32 - 2 will work
+  // `inspect::overflow` shows every way `1u32 - 2` can be computed
+  use crate::inspect::overflow::{self, Op};
+  if let Ok(report) = overflow::explore(IntType::U32, "1", Op::Sub, "2") {
+    println!("{}", report);
+  }
 
   // Short-circuiting boolean logic
   println!("true AND false is {}", true && false);
@@ -68,8 +76,112 @@ pub fn primitives_and_operations() {
   println!("1 << 5 is {}", 1u32 << 5);
   println!("0x80 >> 2 is 0x{:x}", 0x80u32 >> 2);
 
+  // `inspect::bits` lines the operands up bit by bit, for any integer type
+  use crate::inspect::bits::{BitOp, Bits};
+  if let (Ok(a), Ok(b)) = (
+    Bits::parse(IntType::U8, "0b0011"),
+    Bits::parse(IntType::U8, "0b0101"),
+  ) {
+    for &op in [BitOp::And, BitOp::Or, BitOp::Xor].iter() {
+      print!("{}", a.operation(op, &b));
+    }
+  }
+  if let Ok(minus_five) = Bits::parse(IntType::I8, "-5") {
+    println!("{}", minus_five.table());
+  }
+
+  // chars take 4 bytes each, but in a string they take 1 to 4 bytes
+  use crate::inspect::unicode;
+  let chars = "aα∞";
+  println!("{}", unicode::table(chars));
+  match unicode::slice(chars, 0, 2) {
+    Ok(slice) => println!("&chars[0..2] is {}", slice),
+    Err(error) => println!("&chars[0..2] would panic: {}", error),
+  }
+
   // Use underscores to improve readability!
   println!("One million is written as {}", 1_000_000u32);
+
+  // When printing, `Humanize` adds the separators back, for any primitive
+  use crate::format::number::{Humanize, Locale};
+  println!(
+    "One million is printed as {}",
+    1_000_000u32.separated(Locale::EN)
+  );
+  println!(
+    "u128::MAX in Switzerland is {}",
+    u128::MAX.separated(Locale::CH)
+  );
+  println!(
+    "-1234567.25 in Germany is {}",
+    (-1234567.25f64).separated(Locale::DE)
+  );
+  println!(
+    "Avogadro's number is {} or {}",
+    6.02214076e23f64.scientific(3),
+    6.02214076e23f64.engineering(3)
+  );
+  println!("i64::MIN is {}", i64::MIN.engineering(2));
+  for n in [1u8, 2, 3, 4, 11, 12, 13, 21, 102, 111].iter() {
+    print!("{} ", n.ordinal().unwrap_or_default());
+  }
+  println!();
+
+  // Past u128, integers need to grow: `BigInt` has as many digits as needed
+  use crate::math::bigint::BigInt;
+  let big = BigInt::from(u128::MAX) + BigInt::from(1u8);
+  println!("u128::MAX + 1 = {} = {:#x}", big, big);
+  let factorial = (1..=30u32).fold(BigInt::from(1u8), |product, n| product * BigInt::from(n));
+  println!("30! = {} = {:#X}", factorial, factorial);
+  println!(
+    "gcd(30!, 2^100) = {:#b}",
+    factorial.gcd(&BigInt::from(2u8).pow(100))
+  );
+  if let Ok(parsed) = "-0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff_ff".parse::<BigInt>() {
+    println!("{:#x} is {} in octal", parsed, parsed.to_str_radix(8));
+  }
+
+  // Big enough for Karatsuba: dividing the product gives back the factors
+  let (a, b) = (BigInt::from(3u8).pow(2000), BigInt::from(7u8).pow(1500));
+  let product = &a * &b;
+  println!(
+    "3^2000 * 7^1500 has {} bits, and dividing it by 7^1500 gives 3^2000: {}",
+    product.bits(),
+    product.div_rem(&b) == Some((a, BigInt::zero()))
+  );
+
+  // Complex numbers are pairs of floats with their own arithmetic
+  use crate::math::complex::Complex;
+  type C = Complex<f64>;
+  let z = C::new(3.0, 4.0);
+  let w = C::new(1.0, -2.0);
+  println!("z = {}, w = {}, conj(z) = {}", z, w, z.conj());
+  println!(
+    "z + w = {}, z - w = {}, z * w = {}, z / w = {}",
+    z + w,
+    z - w,
+    z * w,
+    z / w
+  );
+  println!(
+    "|z| = {}, arg z = {:.3} rad, in polar form {:#.2}",
+    z.modulus(),
+    z.arg(),
+    z
+  );
+  let (r, theta) = z.to_polar();
+  println!("back to rectangular: {:.3}", C::from_polar(r, theta));
+  println!(
+    "e^(iπ) = {:.3}",
+    (C::I * C::from(std::f64::consts::PI)).exp()
+  );
+  println!("ln(-1) = {:.5}", C::from(-1.0).ln());
+  println!(
+    "sqrt(-4) = {}, i^i = {:.4}, 8^(1/3) = [{:>14.3}]",
+    C::from(-4.0).sqrt(),
+    C::I.pow(C::I),
+    C::from(8.0).powf(1.0 / 3.0)
+  );
 }
 
 pub fn tuples() {
@@ -81,10 +193,6 @@ pub fn tuples() {
     (boolean, integer)
   }
 
-  // The following struct is for the activity.
-  #[derive(Debug)]
-  struct Matrix(f32, f32, f32, f32);
-
   // A tuple with a bunch of different types
   let long_tuple = (
     1u8, 2u16, 3u32, 4u64, -1i8, -2i16, -3i32, -4i64, 0.1f32, 0.2f64, 'a', true,
@@ -121,25 +229,143 @@ pub fn tuples() {
   let (a, b, c, d) = tuple;
   println!("{:?}, {:?}, {:?}, {:?}", a, b, c, d);
 
-  let matrix = Matrix(1.1, 1.2, 2.1, 2.2);
+  // The `tuple` module implements `reverse` and friends for every tuple up to
+  // 16 items, whatever their types
+  use crate::tuple::{Homogeneous, Reverse, Tuple, TupleDebug, Zip};
+
+  println!("{:?} reversed is {:?}", tuple, tuple.reverse());
+  println!("{:?} has {} items", tuple, tuple.len());
+  println!("zipped: {:?}", (1, 2, 3).zip(('a', 'b', 'c')));
+
+  // When all the items have the same type, they can be mapped over, swapped by
+  // index and collected like the items of an array
+  let mut same = (1, 2, 3, 4, 5);
+  println!("squares of {:?}: {:?}", same, same.map_each(|x| x * x));
+  same.swap(0, 4);
+  println!("with the ends swapped: {:?}", same);
+  println!("as a vector: {:?}", same.to_vec());
+
+  // And the 13 items of the tuple above can be printed after all
+  let too_long_tuple = (1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13);
+  println!("too long tuple: {:?}", too_long_tuple.debug());
+  println!("{:#?}", (1, "two", 3.0).debug());
+  println!(
+    "any number of values: {}",
+    crate::debug_tuple!(
+      1u8,
+      'b',
+      "c",
+      4.0,
+      [5],
+      (6,),
+      7,
+      8,
+      9,
+      10,
+      11,
+      12,
+      13,
+      14,
+      15,
+      16,
+      17
+    )
+  );
+
+  // The activities use a 2×2 matrix, `Matrix<f32, 2, 2>`
+  use crate::math::matrix::Matrix;
+  let matrix = Matrix::new([[1.1, 1.2], [2.1, 2.2]]);
   println!("{:?}", matrix);
+  println!("row 0, column 1: {}", matrix[(0, 1)]);
 
   // ACTIVITIES
   // A01
-  impl Display for Matrix {
-    // `f` is a buffer, and this method must write the formatted string into it
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-      write!(f, "( {} {} ) \n( {} {} )", self.0, self.1, self.2, self.3)
-    }
-  }
   println!("{}", matrix);
 
   // A02
-  fn transpose(m: Matrix) -> Matrix {
-    Matrix(m.0, m.2, m.1, m.3)
+  println!("{}", matrix.transpose());
+
+  // The size is part of the type, so the operators check it at compile time
+  let wide = Matrix::new([[1.0, -2.5, 3.0], [10.0, 0.5, -1.0]]);
+  println!("{}", wide);
+  println!("transposed:\n{}", wide.transpose());
+  println!("times its transpose:\n{:.2}", wide * wide.transpose());
+  println!("times [1, 0, 2]: {:?}", wide * [1.0, 0.0, 2.0]);
+  println!("plus itself:\n{}", wide + wide);
+  println!("doubled minus itself:\n{}", wide.scale(2.0) - wide);
+  println!("identity:\n{}", Matrix::<i32, 3, 3>::identity());
+
+  // Square matrices can be factored to solve linear systems
+  use crate::math::linalg::{self, Lu};
+  let a = Matrix::new([[2.0, 1.0, -1.0], [-3.0, -1.0, 2.0], [-2.0, 1.0, 2.0]]);
+  let lu = Lu::new(&a);
+  println!(
+    "P * A = L * U with L =\n{:.3}\nand U =\n{:.3}",
+    lu.lower(),
+    lu.upper()
+  );
+  println!("det A = {:.3}", lu.determinant());
+  match lu.solve([8.0, -11.0, -3.0]) {
+    Ok(x) => println!("A * x = [8, -11, -3] for x = {:.3?}", x),
+    Err(error) => println!("A * x = [8, -11, -3] has no unique solution: {}", error),
+  }
+  if let Ok(inverse) = lu.inverse() {
+    println!("A⁻¹ =\n{:.3}\nA * A⁻¹ =\n{:.3}", inverse, a * inverse);
+  }
+  let singular = Matrix::new([[1.0, 2.0], [2.0, 4.0]]);
+  println!(
+    "rank {}, inverse: {:?}",
+    linalg::rank(&singular),
+    linalg::inverse(&singular).map_err(|e| e.to_string())
+  );
+  println!("the wide matrix has rank {}", linalg::rank(&wide));
+
+  // As JSON a matrix is its rows, and loading it checks the size
+  use crate::format::json::{self, ToJson};
+  let saved = wide.to_json().to_string();
+  println!("{}", saved);
+  println!(
+    "loaded back as a 2×3 matrix: {}",
+    json::from_str::<Matrix<f64, 2, 3>>(&saved) == Ok(wide)
+  );
+  if let Err(error) = json::from_str::<Matrix<f64, 3, 2>>(&saved) {
+    println!("as a 3×2 matrix: {}", error);
+  }
+
+  // Fractions have no rounding error: `1.1` is exactly `11/10`
+  use crate::math::ratio::Ratio;
+  type Q = Ratio<i64>;
+  let q = |text: &str| text.parse::<Q>().unwrap();
+  let exact = Matrix::new([[q("1.1"), q("1.2")], [q("2.1"), q("2.2")]]);
+  println!("{}", exact);
+  println!(
+    "squared:\n{}\ndet = {}",
+    exact * exact,
+    linalg::determinant(&exact)
+  );
+  let a = a.map(|x| Q::from(x as i64));
+  if let Ok(inverse) = linalg::inverse(&a) {
+    println!(
+      "A * A⁻¹ == I exactly: {}",
+      a * inverse == Matrix::identity()
+    );
   }
 
-  println!("{}", transpose(matrix));
+  // Fractions are kept reduced, with the sign on the numerator
+  let sum = q("1/3") + q("-10/-4");
+  println!("1/3 + -10/-4 = {}, or {:#} as a mixed number", sum, sum);
+  println!(
+    "0.1 is {}, pi is about {}, -4/6 < -1/2: {}",
+    Q::from_f64(0.1, 1_000_000).unwrap(),
+    Q::from_f64(std::f64::consts::PI, 1000).unwrap(),
+    q("-4/6") < q("-1/2")
+  );
+  println!(
+    "{:?}, {:?}, {}",
+    "1 1/2".parse::<Q>().map(|x| x.to_string()),
+    Ratio::<i8>::from(100).checked_add(Ratio::from(100)),
+    "1/0".parse::<Q>().unwrap_err()
+  );
 }
 
 pub fn arrays_and_slices() {
@@ -157,10 +383,20 @@ pub fn arrays_and_slices() {
 
   use std::mem;
 
+  use crate::math::stats;
+
   // This function borrows a slice
   fn analyze_slice(slice: &[i32]) {
-    println!("first element of the slice: {}", slice[0]);
+    // `slice[0]` would panic on an empty slice, `first` returns an `Option`
+    match slice.first() {
+      Some(first) => println!("first element of the slice: {}", first),
+      None => println!("the slice is empty"),
+    }
     println!("the slice has {} elements", slice.len());
+    match stats::analyze(slice) {
+      Ok(stats) => println!("{:.2}", stats),
+      Err(error) => println!("no statistics: {}", error),
+    }
   }
 
   // Fixed-size array (type signature is superfluous)
@@ -180,6 +416,20 @@ pub fn arrays_and_slices() {
   // Arrays are stack allocated
   println!("array occupies {} bytes", mem::size_of_val(&xs));
 
+  // Every byte of an array is an element, there is no padding
+  use crate::inspect::layout::Layout;
+  println!("{}", Layout::of::<[i32; 5]>("[i32; 5]"));
+
+  // Bigger sizes are easier to read with units
+  use crate::format::number::{ByteUnits, Humanize};
+  let size = mem::size_of_val(&ys);
+  println!(
+    "the array ys occupies {} bytes, that is {} or {}",
+    size,
+    size.bytes(ByteUnits::Si),
+    size.bytes(ByteUnits::Iec)
+  );
+
   // Arrays can be automatically borrowed as slices
   println!("borrow the whole array as a slice");
   analyze_slice(&xs);
@@ -191,6 +441,149 @@ pub fn arrays_and_slices() {
   println!("borrow a section of the array as a slice");
   analyze_slice(&xs[1..4]);
 
+  // An empty slice has no first element and no statistics
+  println!("borrow an empty section of the array as a slice");
+  analyze_slice(&xs[5..]);
+
+  // `analyze` works on any numeric slice. The sum stays in the type of the
+  // items, so it can overflow, but the mean is computed in `f64`.
+  if let Ok(bytes) = stats::analyze(&[200u8, 100, 3, 3, 250]) {
+    println!("{:.2}", bytes);
+  }
+  let temperatures = [21.5f32, 19.0, 23.25, 19.0, 25.5, 21.5];
+  if let Ok(stats) = stats::analyze(&temperatures) {
+    println!(
+      "temperatures: median {}, modes {:?}, p90 {:.2}, sample variance {:.3}",
+      stats.median,
+      stats.modes,
+      stats.percentile(90.0).unwrap_or_default(),
+      stats.sample_variance().unwrap_or_default()
+    );
+  }
+  if let Err(error) = stats::analyze(&[1.0, f64::NAN]) {
+    println!("[1.0, NaN] has no statistics: {}", error);
+  }
+
   // Out of bound indexing causes compile error
   // println!("{}", xs[5]);
+
+  // Every slice is a pointer into the array and a length. The explorer draws
+  // which items the slices returned by a method cover.
+  use crate::inspect::slices::Explorer;
+  if let Ok(explorer) = Explorer::new("xs", &xs) {
+    let views = [
+      explorer.range(1, 4),
+      explorer.windows(3),
+      explorer.chunks_exact(2),
+      explorer.rchunks(2),
+      explorer.split_at(2),
+      Ok(explorer.split("|x| x % 2 == 0", |x| x % 2 == 0)),
+      // These would panic, the explorer returns an error instead
+      explorer.chunks(0),
+      explorer.range(2, 6),
+    ];
+    for view in views.iter() {
+      match view {
+        Ok(view) => println!("{}", view),
+        Err(error) => println!("error: {}", error),
+      }
+    }
+  }
+
+  // Sorting works on a mutable slice. Each algorithm can record what it does,
+  // which `replay` turns into one frame per step.
+  use crate::inspect::sorting::{self, Algorithm, Counts};
+  let input = [5, 1, 4, 2, 3];
+  for &algorithm in Algorithm::ALL.iter() {
+    let mut items = input;
+    let mut events = Vec::new();
+    algorithm.sort(&mut items, &mut events);
+    println!(
+      "{:>9} sort: {:?} -> {:?}, {}",
+      algorithm,
+      input,
+      items,
+      Counts::of(&events)
+    );
+  }
+  let mut items = [3, 1, 2];
+  let mut events = Vec::new();
+  Algorithm::Insertion.sort(&mut items, &mut events);
+  for frame in sorting::replay(&[3, 1, 2], &events) {
+    println!("{}", frame);
+  }
+
+  // Binary search halves the sorted slice it looks at on every probe
+  let sorted = [1, 3, 5, 7, 9, 11, 13];
+  let mut events = Vec::new();
+  let found = sorting::binary_search(&sorted, &11, &mut events);
+  for frame in sorting::replay(&sorted, &events).iter().skip(1) {
+    println!("{}", frame);
+  }
+  println!("binary search for 11: {:?}", found);
+
+  // Arrays of arrays make a grid, whose rows follow each other in memory
+  use crate::math::grid::{Connectivity, Grid};
+  let mut grid = Grid::new([[1, 2, 3, 4], [5, 6, 7, 8], [9, 10, 11, 12]]);
+  println!("a {}×{} grid:\n{}", grid.width(), grid.height(), grid);
+  println!("all of its cells in one slice: {:?}", grid.as_slice());
+  println!(
+    "row 1: {:?}, column 2: {:?}",
+    grid.row(1),
+    grid.column(2).collect::<Vec<_>>()
+  );
+  let corner: Vec<_> = grid.neighbors(0, 0, Connectivity::Eight).collect();
+  let middle: Vec<_> = grid.neighbors(1, 1, Connectivity::Four).collect();
+  println!(
+    "neighbors of the corner (0, 0): {:?}, 4-connected neighbors of (1, 1): {:?}",
+    corner, middle
+  );
+  grid.row_mut(0).reverse();
+  grid[(3, 2)] = 0;
+  println!("with the first row reversed and (3, 2) cleared:\n{}", grid);
+  println!("transposed:\n{}", grid.transpose());
+  println!("rotated right:\n{}", grid.rotate_right());
+  println!("rotated left:\n{}", grid.rotate_left());
+
+  // Flood fill paints a region, the diagonal gap only leaks with 8 neighbors
+  const ROOMS: [&str; 5] = [
+    "#########",
+    "#...#...#",
+    "#...#...#",
+    "###.#.###",
+    "#....#..#",
+  ];
+  let rooms: Grid<char, 9, 5> = Grid::from_fn(|x, y| ROOMS[y].as_bytes()[x] as char);
+  for &connectivity in [Connectivity::Four, Connectivity::Eight].iter() {
+    let mut painted = rooms;
+    let filled = painted.flood_fill(1, 1, '~', connectivity);
+    println!(
+      "flood fill from (1, 1), {:?} neighbors, {} cells:\n{:#}",
+      connectivity, filled, painted
+    );
+  }
+
+  // Conway's Game of Life, on a random board that is the same on every run
+  use crate::math::random::XorShift;
+  let mut rng = XorShift::new(2024);
+  let mut life: Grid<bool, 24, 8> = Grid::from_fn(|_, _| rng.chance(1, 3));
+  for generation in 0..4 {
+    println!(
+      "generation {}, population {}:\n{:#}",
+      generation,
+      life.population(),
+      life.map(|alive| if alive { '█' } else { '·' })
+    );
+    life = life.life_step();
+  }
+
+  // A glider moves one cell diagonally every 4 generations
+  let glider: Grid<bool, 6, 6> =
+    Grid::from_fn(|x, y| [(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)].contains(&(x, y)));
+  let moved = (0..4).fold(glider, |life, _| life.life_step());
+  let shifted = Grid::from_fn(|x, y| x > 0 && y > 0 && glider[(x - 1, y - 1)]);
+  println!(
+    "the glider moved by (1, 1) after 4 generations: {}",
+    moved == shifted
+  );
 }
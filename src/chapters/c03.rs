@@ -7,7 +7,224 @@
  * Constants can also be created via the `const` and `static` keywords.
  */
 
+use crate::format::json::{self, DecodeError, FromJson, ToJson, Value};
+use crate::format::table::{Align, Column, Record, Table};
+use crate::inspect::layout;
+use std::fmt;
+
+#[derive(Debug)]
+pub struct Person<'a> {
+  // The 'a defines a lifetime
+  pub name: &'a str,
+  pub age: u8,
+}
+
+impl<'a> Record for Person<'a> {
+  fn columns() -> Vec<Column> {
+    vec![
+      Column::new("Name", Align::Left),
+      Column::new("Age", Align::Right),
+    ]
+  }
+
+  fn cells(&self) -> Vec<String> {
+    vec![self.name.to_owned(), self.age.to_string()]
+  }
+}
+
+// A tuple struct
+#[derive(Debug)]
+pub struct Pair(pub i32, pub f32);
+
+// A struct with two fields
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+  pub x: f32,
+  pub y: f32,
+}
+
+// Structs can be reused as fields of another struct
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Rectangle {
+  // A rectangle can be specified by where the top left and bottom right
+  // corners are in space. `geometry::rectangle` builds it from any two
+  // corners.
+  pub top_left: Point,
+  pub bottom_right: Point,
+}
+
+impl fmt::Display for Rectangle {
+  fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    let Point { x: left, y: top } = self.top_left;
+    let Point {
+      x: right,
+      y: bottom,
+    } = self.bottom_right;
+
+    // The precision, as in `{:.1}`, applies to every coordinate
+    let [left, top, right, bottom] = [left, top, right, bottom].map(|x| match f.precision() {
+      Some(precision) => format!("{:.*}", precision, x),
+      None => x.to_string(),
+    });
+
+    // `write!` is like `format!`, but it will write the formatted string
+    // into a buffer (the first argument)
+    write!(
+      f,
+      "top-left: ({}, {}), bottom-right: ({}, {})",
+      left, top, right, bottom
+    )
+  }
+}
+
+// Create an `enum` to classify a web event. Note how both
+// names and type information together specify the variant:
+// `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
+// Each is different and independent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WebEvent {
+  // An `enum` may either be `unit-like`,
+  PageLoad,
+  PageUnload,
+  // like tuple structs,
+  KeyPress(char),
+  Paste(String),
+  // or c-like structures.
+  Click { x: i64, y: i64 },
+}
+
+// Tagged by the name of the variant: `"PageLoad"`, `{"KeyPress": "x"}` or
+// `{"Click": {"x": 20, "y": 80}}`
+impl ToJson for WebEvent {
+  fn to_json(&self) -> Value {
+    match self {
+      WebEvent::PageLoad => "PageLoad".to_json(),
+      WebEvent::PageUnload => "PageUnload".to_json(),
+      WebEvent::KeyPress(c) => json::tagged("KeyPress", c.to_json()),
+      WebEvent::Paste(s) => json::tagged("Paste", s.to_json()),
+      WebEvent::Click { x, y } => json::tagged(
+        "Click",
+        Value::Object(vec![
+          ("x".to_owned(), x.to_json()),
+          ("y".to_owned(), y.to_json()),
+        ]),
+      ),
+    }
+  }
+}
+
+impl FromJson for WebEvent {
+  fn from_json(value: &Value) -> Result<WebEvent, DecodeError> {
+    match json::variant(value)? {
+      ("PageLoad", None) => Ok(WebEvent::PageLoad),
+      ("PageUnload", None) => Ok(WebEvent::PageUnload),
+      ("KeyPress", Some(c)) => {
+        json::within("KeyPress", || char::from_json(c)).map(WebEvent::KeyPress)
+      }
+      ("Paste", Some(s)) => json::within("Paste", || String::from_json(s)).map(WebEvent::Paste),
+      ("Click", Some(position)) => json::within("Click", || {
+        json::object(position, &["x", "y"])?;
+        Ok(WebEvent::Click {
+          x: json::field(position, "x")?,
+          y: json::field(position, "y")?,
+        })
+      }),
+      (name @ ("PageLoad" | "PageUnload"), Some(_)) => Err(DecodeError::Invalid(format!(
+        "the variant {} has no content",
+        name
+      ))),
+      (name @ ("KeyPress" | "Paste" | "Click"), None) => Err(DecodeError::Invalid(format!(
+        "the variant {} needs a content",
+        name
+      ))),
+      (name, _) => Err(DecodeError::UnknownVariant(name.to_owned())),
+    }
+  }
+}
+
+// A function which takes a `WebEvent` enum as an argument and
+// returns nothing. `records::events` replays logs through it.
+pub fn inspect(event: WebEvent) {
+  match event {
+    WebEvent::PageLoad => println!("page loaded"),
+    WebEvent::PageUnload => println!("page unloaded"),
+    // Destructure `c` from inside the `enum`.
+    WebEvent::KeyPress(c) => println!("pressed '{}'.", c),
+    WebEvent::Paste(s) => println!("pasted \"{}\".", s),
+    // Destructure `Click` into `x` and `y`.
+    WebEvent::Click { x, y } => {
+      println!("clicked at x={}, y={}.", x, y);
+    }
+  }
+}
+
+// enum with explicit discriminator
+#[derive(Debug, Clone, Copy)]
+pub enum Color {
+  Red = 0xff0000,
+  Green = 0x00ff00,
+  Blue = 0x0000ff,
+}
+
+// A linked list, for `testcase_linked_list`
+#[derive(Debug)]
+pub enum List {
+  // Cons: Tuple struct that wraps an element and a pointer to the next node
+  Cons(u32, Box<List>),
+  // Nil: A node that signifies the end of the linked list
+  Nil,
+}
+
+// Methods can be attached to an enum
+impl List {
+  // Create an empty list
+  fn new() -> List {
+    // `Nil` has type `List`
+    List::Nil
+  }
+
+  // Consume a list, and return the same list with a new element at its front
+  fn prepend(self, elem: u32) -> List {
+    // `Cons` also has type List
+    List::Cons(elem, Box::new(self))
+  }
+
+  // Return the length of the list
+  fn len(&self) -> u32 {
+    // `self` has to be matched, because the behavior of this method
+    // depends on the variant of `self`
+    // `self` has type `&List`, and `*self` has type `List`, matching on a
+    // concrete type `T` is preferred over a match on a reference `&T`
+    match *self {
+      // Can't take ownership of the tail, because `self` is borrowed;
+      // instead take a reference to the tail
+      // Cons(head, ref tail) => {
+      // println!("getting length {}", head);
+      //   return 1 + tail.len();
+      // }
+      // Base Case: An empty list has zero length
+      List::Cons(_, ref tail) => 1 + tail.len(),
+      List::Nil => 0,
+    }
+  }
+
+  // Return representation of the list as a (heap allocated) string
+  #[allow(clippy::useless_format)]
+  fn stringify(&self) -> String {
+    match *self {
+      List::Cons(head, ref tail) => {
+        // `format!` is similar to `print!`, but returns a heap
+        // allocated string instead of printing to the console
+        format!("{}, {}", head, tail.stringify())
+      }
+      List::Nil => format!("Nil"),
+    }
+  }
+}
+
 pub fn structures() {
+  #![allow(dead_code, clippy::redundant_field_names, clippy::needless_return)]
+
   /*
    * Structures
    * There are three types of structures ("structs") that can be created using the
@@ -18,34 +235,9 @@ pub fn structures() {
    * Unit structs, which are field-less, are useful for generics.
    */
 
-  #[derive(Debug)]
-  struct Person<'a> {
-    // The 'a defines a lifetime
-    name: &'a str,
-    age: u8,
-  }
-
   // A unit struct
   struct Nil;
 
-  // A tuple struct
-  struct Pair(i32, f32);
-
-  // A struct with two fields
-  struct Point {
-    x: f32,
-    y: f32,
-  }
-
-  // Structs can be reused as fields of another struct
-  #[allow(dead_code)]
-  struct Rectangle {
-    // A rectangle can be specified by where the top left and bottom right
-    // corners are in space.
-    top_left: Point,
-    bottom_right: Point,
-  }
-
   // Create struct with field init shorthand
   let name = "Peter";
   let age = 27;
@@ -98,34 +290,12 @@ pub fn structures() {
   println!("pair contains {:?} and {:?}", integer, decimal);
 
   // ACTIVITIES
-  // A01
-  use std::fmt; // Import the `fmt` module.
-
-  impl fmt::Display for Rectangle {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-      let Point { x: top, y: left } = self.top_left;
-      let Point {
-        x: bottom,
-        y: right,
-      } = self.bottom_right;
-
-      // `write!` is like `format!`, but it will write the formatted string
-      // into a buffer (the first argument)
-      write!(
-        f,
-        "top-left: ({}, {}), bottom-right: ({}, {})",
-        top, left, bottom, right
-      )
-    }
-  }
-
+  // A01: `Display` for `Rectangle` is implemented next to the struct
+  // `x` is the horizontal coordinate and `y` grows upwards, so the width is
+  // along `x` and the height along `y`. `Rectangle::area` also works when
+  // the corners are swapped.
   fn cal_area(r: &Rectangle) -> f32 {
-    let Point { x: top, y: left } = r.top_left;
-    let Point {
-      x: bottom,
-      y: right,
-    } = r.bottom_right;
-    return (top - bottom) * (right - left);
+    return r.area();
   }
   println!(
     "The area of the rectangle {} is {}",
@@ -134,25 +304,210 @@ pub fn structures() {
   );
 
   //A02
+  // `p` is the bottom left corner, and the opposite one is up and to the right
   fn create_square(p: Point, borders_len: f32) -> Rectangle {
-    let Point { x: bottom, y: left } = p;
-    let top_left = Point {
-      x: bottom + borders_len,
-      y: left,
+    let Point { x: left, y: bottom } = p;
+    let opposite = Point {
+      x: left + borders_len,
+      y: bottom + borders_len,
     };
-    let bottom_right = Point {
-      x: bottom,
-      y: left + borders_len,
-    };
-    Rectangle {
-      top_left,
-      bottom_right,
-    }
+    Rectangle::from_corners(p, opposite)
   }
 
   let base_point = Point { x: 2., y: 1. };
 
-  println!("Square {}", create_square(base_point, 4.4))
+  let square = create_square(base_point, 4.4);
+  println!("Square {}", square);
+
+  // The geometry module measures and combines rectangles
+  println!(
+    "its area is {:.2}, its perimeter {:.1} and its center {:.1}",
+    square.area(),
+    square.perimeter(),
+    square.center()
+  );
+  let swapped = Rectangle {
+    top_left: _rectangle.bottom_right,
+    bottom_right: _rectangle.top_left,
+  };
+  println!(
+    "with its corners swapped, {} still has an area of {:.2}, and normalizes to {}",
+    swapped,
+    cal_area(&swapped),
+    swapped.normalize()
+  );
+  for point in [
+    Point::new(3.0, 3.0),
+    Point::new(6.4, 5.4),
+    Point::new(7.0, 2.0),
+  ]
+  .iter()
+  {
+    println!("the square contains {}: {}", point, square.contains(point));
+  }
+  match square.intersection(&_rectangle) {
+    Some(overlap) => println!("it overlaps the rectangle in {:.1}", overlap),
+    None => println!("it doesn't overlap the rectangle"),
+  }
+  println!("together they fit in {:.1}", square.union(&_rectangle));
+  println!(
+    "moved by (1, -1): {:.1}, twice as big: {:.1}",
+    square.translate(1.0, -1.0),
+    square.scale(2.0)
+  );
+
+  // Rectangles are one kind of `Shape`. Different shapes can live in the same
+  // vector behind a `Box<dyn Shape>`, and be compared through the trait.
+  use crate::geometry::shape::{self, Circle, Polygon, Shape, Triangle};
+  // An L, which is not convex
+  let l_shape = Polygon::new(vec![
+    Point::new(0.0, 0.0),
+    Point::new(4.0, 0.0),
+    Point::new(4.0, 1.0),
+    Point::new(1.0, 1.0),
+    Point::new(1.0, 3.0),
+    Point::new(0.0, 3.0),
+  ]);
+  let mut shapes: Vec<Box<dyn Shape>> = vec![
+    Box::new(square),
+    Box::new(Circle::new(Point::new(0.0, 0.0), 1.5)),
+    Box::new(Triangle::new(
+      Point::new(0.0, 0.0),
+      Point::new(4.0, 0.0),
+      Point::new(0.0, 3.0),
+    )),
+    Box::new(_rectangle),
+  ];
+  match l_shape {
+    Ok(l_shape) => {
+      let notch = Point::new(2.0, 2.0);
+      println!(
+        "the L contains {}: {}, its bounding box does: {}",
+        notch,
+        l_shape.contains(&notch),
+        l_shape.bounding_box().contains(&notch)
+      );
+      shapes.push(Box::new(l_shape));
+    }
+    Err(error) => println!("invalid polygon: {}", error),
+  }
+  println!("{}", shape::area_report(&shapes));
+
+  // The edges of a bow tie cross, so it is not a simple polygon
+  let bow_tie = Polygon::new(vec![
+    Point::new(0.0, 0.0),
+    Point::new(2.0, 2.0),
+    Point::new(2.0, 0.0),
+    Point::new(0.0, 2.0),
+  ]);
+  if let Err(error) = bow_tie {
+    println!("bow tie: {}", error);
+  }
+
+  // Draw the rectangle and the square, with `y` upwards like above
+  use crate::chapters::c01::Color;
+  use crate::geometry::svg::{Style, Svg};
+  let blue = Color {
+    red: 70,
+    green: 110,
+    blue: 200,
+  };
+  let orange = Color {
+    red: 240,
+    green: 150,
+    blue: 40,
+  };
+  let drawing = Svg::new(480)
+    .axes(true)
+    .grid(true)
+    .ticks(true)
+    .add(
+      _rectangle,
+      Style::new().fill(blue).stroke(blue).label("rectangle"),
+    )
+    .add(square, Style::new().fill(orange).label("square"))
+    .add(
+      Circle::new(Point::new(0.0, 0.0), 1.5),
+      Style::new().stroke(orange).label("circle"),
+    )
+    .add(base_point, Style::new().fill(orange).no_stroke());
+  // `drawing.save("structures.svg")` would write it to a file, which any
+  // browser opens. Here it is only rendered, so that running the chapters
+  // leaves nothing behind.
+  let document = drawing.render();
+  println!(
+    "the shapes make an SVG document of {} lines:\n{}",
+    document.lines().count(),
+    document.lines().next().unwrap_or_default()
+  );
+
+  // Or in the terminal, where overlaps are shaded darker
+  use crate::geometry::canvas::Canvas;
+  let canvas = Canvas::new(48, 16)
+    .rectangle(_rectangle)
+    .rectangle(square)
+    .rectangle(
+      square
+        .intersection(&_rectangle)
+        .unwrap_or(square)
+        .scale(0.5),
+    )
+    .point(base_point, '●')
+    .point(square.center(), '+');
+  println!("{}", canvas);
+
+  // Structs can be shown as rows of a table
+  let people = [
+    peter,
+    Person {
+      name: "Ana",
+      age: 31,
+    },
+    Person {
+      name: "Björn",
+      age: 9,
+    },
+  ];
+  println!("{}", Table::from_records(&people));
+
+  // The size of a struct is a multiple of its alignment, so `age` is followed
+  // by padding
+  println!("{}", layout::find("Person").unwrap());
+
+  // `records::person::Person` owns its name, so it can be read from text.
+  // Invalid input is an error instead of a person.
+  use crate::format::json::{self, ToJson};
+  use crate::records::person::{self, Person as OwnedPerson};
+  for text in [
+    "Peter,27",
+    "name=Ana age=31",
+    "\"García, Lucía\",64",
+    " ,27",
+    "Peter,old",
+    "Peter,200",
+    "Peter",
+  ]
+  .iter()
+  {
+    match text.parse::<OwnedPerson>() {
+      Ok(person) => println!("{:?} is {}", text, person),
+      Err(error) => println!("{:?} is invalid: {}", text, error),
+    }
+  }
+  let owned: Vec<OwnedPerson> = people
+    .iter()
+    .map(|p| OwnedPerson::new(p.name, p.age as u32))
+    .collect::<Result<_, _>>()
+    .unwrap();
+  let csv = person::to_csv(&owned);
+  let json = owned.to_json().to_string();
+  print!("{}", csv);
+  println!("{}", json);
+  println!(
+    "people survive a round trip through CSV: {}, and through JSON: {}",
+    person::from_csv(&csv) == Ok(owned.clone()),
+    json::from_str::<Vec<OwnedPerson>>(&json) == Ok(owned)
+  );
 }
 
 pub fn enums() {
@@ -162,37 +517,6 @@ pub fn enums() {
    * an enum.
    */
 
-  // Create an `enum` to classify a web event. Note how both
-  // names and type information together specify the variant:
-  // `PageLoad != PageUnload` and `KeyPress(char) != Paste(String)`.
-  // Each is different and independent.
-  enum WebEvent {
-    // An `enum` may either be `unit-like`,
-    PageLoad,
-    PageUnload,
-    // like tuple structs,
-    KeyPress(char),
-    Paste(String),
-    // or c-like structures.
-    Click { x: i64, y: i64 },
-  }
-
-  // A function which takes a `WebEvent` enum as an argument and
-  // returns nothing.
-  fn inspect(event: WebEvent) {
-    match event {
-      WebEvent::PageLoad => println!("page loaded"),
-      WebEvent::PageUnload => println!("page unloaded"),
-      // Destructure `c` from inside the `enum`.
-      WebEvent::KeyPress(c) => println!("pressed '{}'.", c),
-      WebEvent::Paste(s) => println!("pasted \"{}\".", s),
-      // Destructure `Click` into `x` and `y`.
-      WebEvent::Click { x, y } => {
-        println!("clicked at x={}, y={}.", x, y);
-      }
-    }
-  }
-
   let pressed = WebEvent::KeyPress('x');
   // `to_owned()` creates an owned `String` from a string slice.
   let pasted = WebEvent::Paste("my text".to_owned());
@@ -200,14 +524,93 @@ pub fn enums() {
   let load = WebEvent::PageLoad;
   let unload = WebEvent::PageUnload;
 
+  // As JSON, an event is tagged with the name of its variant
+  let events = vec![
+    load.clone(),
+    pressed.clone(),
+    pasted.clone(),
+    click.clone(),
+    unload.clone(),
+  ];
+  let saved = events.to_json();
+  println!("{:#}", saved);
+
   inspect(pressed);
   inspect(pasted);
   inspect(click);
   inspect(load);
   inspect(unload);
+  println!(
+    "events survive a round trip through JSON: {}",
+    json::from_str::<Vec<WebEvent>>(&saved.to_string()) == Ok(events)
+  );
+  // Parse errors point at the character, decoding errors at the member
+  for text in [
+    "[\"PageLoad\",\n {\"Click\": {\"x\": 20 \"y\": 80}}]",
+    "[\"PageLoad\", {\"Click\": {\"x\": 20, \"y\": 8.5}}]",
+    "[{\"KeyPress\": \"xy\"}]",
+    "[\"Scroll\"]",
+  ]
+  .iter()
+  {
+    if let Err(error) = json::from_str::<Vec<WebEvent>>(text) {
+      println!("{}\n  {}", text, error);
+    }
+  }
+  // A log has one event per line, and can be replayed through `inspect` or
+  // any other handler
+  use crate::records::events::{self, Session};
+  let log = "\
+load
+key x
+paste \"my \\\"quoted\\\" text\"
+click 20 80
+unload
+";
+  match events::parse(log) {
+    Ok(logged) => {
+      events::replay(&logged, &mut inspect);
+      // A handler can be a closure, like one that counts the clicks
+      let mut clicks = 0;
+      events::replay(&logged, &mut |event| {
+        if let WebEvent::Click { .. } = event {
+          clicks += 1;
+        }
+      });
+      println!("clicks in the log: {}", clicks);
+      println!(
+        "the log survives a round trip: {}",
+        events::parse(&events::write(&logged)) == Ok(logged)
+      );
+    }
+    Err(error) => println!("invalid log: {}", error),
+  }
+  // A session checks that the events could happen in that order
+  let mut session = Session::new();
+  if let Ok(trace) = events::parse("key a\nload\nload\nclick 1 2\n") {
+    events::replay(&trace, &mut session);
+  }
+  println!("{}", session);
+  for log in [
+    "load\nscroll 10",
+    "load\nkey xy",
+    "click 20",
+    "click 20 eighty",
+    "paste my text",
+    "paste \"my text",
+    "unload now",
+  ]
+  .iter()
+  {
+    if let Err(error) = events::parse(log) {
+      println!("{:?}: {}", log, error);
+    }
+  }
 }
 
 pub fn alias() {
+  #![allow(dead_code)]
+
   /*
    * Type aliases
    * If you use a type alias, you can refer to each enum variant via its alias.
@@ -280,7 +683,7 @@ pub fn _use() {
 
 pub fn c_liKe_enums() {
   // An attribute to hide warnings for unused code.
-  #![allow(dead_code)]
+  #![allow(dead_code, non_snake_case)]
 
   // enum with implicit discriminator (starts at 0)
   enum Number {
@@ -289,13 +692,6 @@ pub fn c_liKe_enums() {
     Two,
   }
 
-  // enum with explicit discriminator
-  enum Color {
-    Red = 0xff0000,
-    Green = 0x00ff00,
-    Blue = 0x0000ff,
-  }
-
   fn main() {
     // `enums` can be cast as integers.
     println!("zero is {}", Number::Zero as i32);
@@ -309,61 +705,6 @@ pub fn c_liKe_enums() {
 }
 
 pub fn testcase_linked_list() {
-  use List::*;
-
-  enum List {
-    // Cons: Tuple struct that wraps an element and a pointer to the next node
-    Cons(u32, Box<List>),
-    // Nil: A node that signifies the end of the linked list
-    Nil,
-  }
-
-  // Methods can be attached to an enum
-  impl List {
-    // Create an empty list
-    fn new() -> List {
-      // `Nil` has type `List`
-      Nil
-    }
-
-    // Consume a list, and return the same list with a new element at its front
-    fn prepend(self, elem: u32) -> List {
-      // `Cons` also has type List
-      Cons(elem, Box::new(self))
-    }
-
-    // Return the length of the list
-    fn len(&self) -> u32 {
-      // `self` has to be matched, because the behavior of this method
-      // depends on the variant of `self`
-      // `self` has type `&List`, and `*self` has type `List`, matching on a
-      // concrete type `T` is preferred over a match on a reference `&T`
-      match *self {
-        // Can't take ownership of the tail, because `self` is borrowed;
-        // instead take a reference to the tail
-        // Cons(head, ref tail) => {
-        // println!("getting length {}", head);
-        //   return 1 + tail.len();
-        // }
-        // Base Case: An empty list has zero length
-        Cons(_, ref tail) => 1 + tail.len(),
-        Nil => 0,
-      }
-    }
-
-    // Return representation of the list as a (heap allocated) string
-    fn stringify(&self) -> String {
-      match *self {
-        Cons(head, ref tail) => {
-          // `format!` is similar to `print!`, but returns a heap
-          // allocated string instead of printing to the console
-          format!("{}, {}", head, tail.stringify())
-        }
-        Nil => format!("Nil"),
-      }
-    }
-  }
-
   fn main() {
     // Create an empty linked list
     let mut list = List::new();
@@ -376,6 +717,12 @@ pub fn testcase_linked_list() {
     // Show the final state of the list
     println!("linked list has length: {}", list.len());
     println!("{}", list.stringify());
+
+    // The tag in front of the `u32` says which variant a `List` is. A `Box`
+    // is never null, so `Option<Box<List>>` stores `None` as a null pointer
+    // and takes no more room than `Box<List>`.
+    println!("{}", layout::find("List").unwrap());
+    println!("{}", layout::summary(&[layout::find("Box<List>").unwrap()]));
   }
   main();
 }
@@ -0,0 +1,462 @@
+/*
+ * Command line interface.
+ *
+ * Without arguments the binary runs every chapter in order. Otherwise the
+ * first argument selects one of the commands below, which get the remaining
+ * arguments.
+ */
+
+use crate::chapters::c03;
+use crate::format::json::ToJson;
+use crate::format::table::Table;
+use crate::inspect::bits::{BitOp, Bits};
+use crate::inspect::float::{self, Float, FloatType};
+use crate::inspect::layout;
+use crate::inspect::overflow::{self, IntType, Op};
+use crate::inspect::slices::Explorer;
+use crate::inspect::sorting::{self, Algorithm, Counts, Event};
+use crate::inspect::unicode;
+use crate::math::stats::{self, Number};
+use crate::records::events::{self, Session};
+use crate::records::person;
+use std::fs;
+use std::str::FromStr;
+use std::thread;
+use std::time::Duration;
+
+const USAGE: &str = "usage: rust-by-example [COMMAND [ARGS...]]
+
+Without a command, every chapter is run.
+
+commands:
+  overflow <type|all> <lhs> <op> <rhs>  compare checked, wrapping, saturating
+                                        and overflowing arithmetic,
+                                        e.g. `overflow u8 200 + 100`
+  bits <type> <value> [<other>]         show the bits of a value, and how the
+                                        bitwise operators combine it with
+                                        another one, e.g. `bits i8 -5 3`
+  float <f32|f64> <literal>             show how a float literal is stored,
+                                        e.g. `float f32 3.141562654`
+  unicode <text> [<start> <end>]        list the chars of a string, and try
+                                        to slice it at the given bytes,
+                                        e.g. `unicode aα∞ 0 2`
+  layout [<type>]                       list the size, alignment and niche of
+                                        every type, or draw the bytes of one,
+                                        e.g. `layout Person`
+  slices <items> <method> <n>...        show which items the slices returned
+                                        by a method cover, for comma
+                                        separated integers, e.g.
+                                        `slices 1,2,3,4,5 chunks 2`
+  sort <algorithm> <items> [--json | --delay <ms>]
+                                        replay a sort step by step, or print
+                                        its steps as JSON, e.g.
+                                        `sort quick 5,1,4,2,3 --delay 300`
+  search <items> <target> [--json | --delay <ms>]
+                                        replay a binary search in sorted
+                                        items, e.g. `search 1,3,5,7,9 7`
+  stats <type> <value>...               compute the statistics of numbers of
+                                        any integer or float type,
+                                        e.g. `stats u8 200 100 3 3 250`
+  people <file> [<option>...]           load people from a CSV, JSON or text
+                                        file, filter and sort them, e.g.
+                                        `people data/people.csv --sort age`
+  replay <file> [--session | --json]    replay a log of web events through
+                                        `inspect`, or check it as a session,
+                                        e.g. `replay data/events.log`
+  help                                  print this message";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+  let (command, args) = match args.split_first() {
+    Some((command, args)) => (command.as_str(), args),
+    None => return Err(USAGE.to_owned()),
+  };
+
+  match command {
+    "overflow" => overflow(args),
+    "bits" => bits(args),
+    "float" => float(args),
+    "unicode" => unicode(args),
+    "layout" => layout(args),
+    "slices" => slices(args),
+    "sort" => sort(args),
+    "search" => search(args),
+    "stats" => stats(args),
+    "people" => people(args),
+    "replay" => replay_log(args),
+    "help" | "-h" | "--help" => {
+      println!("{}", USAGE);
+      Ok(())
+    }
+    _ => Err(format!("unknown command `{}`\n\n{}", command, USAGE)),
+  }
+}
+
+fn overflow(args: &[String]) -> Result<(), String> {
+  let (ty, lhs, op, rhs) = match args {
+    [ty, lhs, op, rhs] => (ty, lhs, op, rhs),
+    _ => return Err("usage: overflow <type|all> <lhs> <op> <rhs>".to_owned()),
+  };
+  let op: Op = op.parse().map_err(|e: overflow::Error| e.to_string())?;
+
+  if ty == "all" {
+    println!("{} {} {} for every integer type", lhs, op, rhs);
+    println!("{}", overflow::comparison(lhs, op, rhs));
+  } else {
+    let ty: IntType = ty.parse().map_err(|e: overflow::Error| e.to_string())?;
+    let report = overflow::explore(ty, lhs, op, rhs).map_err(|e| e.to_string())?;
+    println!("{}", report);
+  }
+  Ok(())
+}
+
+fn bits(args: &[String]) -> Result<(), String> {
+  let (ty, value, other) = match args {
+    [ty, value] => (ty, value, None),
+    [ty, value, other] => (ty, value, Some(other)),
+    _ => return Err("usage: bits <type> <value> [<other>]".to_owned()),
+  };
+  let ty: IntType = ty.parse().map_err(|e: overflow::Error| e.to_string())?;
+  let value = Bits::parse(ty, value).map_err(|e| e.to_string())?;
+  println!("{}", value.table());
+
+  if let Some(other) = other {
+    let other = Bits::parse(ty, other).map_err(|e| e.to_string())?;
+    for &op in BitOp::ALL.iter() {
+      println!("{}", value.operation(op, &other));
+    }
+  }
+  Ok(())
+}
+
+fn float(args: &[String]) -> Result<(), String> {
+  let (ty, literal) = match args {
+    [ty, literal] => (ty, literal),
+    _ => return Err("usage: float <f32|f64> <literal>".to_owned()),
+  };
+  let ty: FloatType = ty.parse().map_err(|e: float::Error| e.to_string())?;
+  let value = Float::parse(ty, literal).map_err(|e| e.to_string())?;
+  println!("{}", value.table(Some(literal)));
+  Ok(())
+}
+
+fn unicode(args: &[String]) -> Result<(), String> {
+  let usage = || "usage: unicode <text> [<start> <end>]".to_owned();
+  let (text, range) = match args {
+    [text] => (text, None),
+    [text, start, end] => {
+      let start: usize = start.parse().map_err(|_| usage())?;
+      let end: usize = end.parse().map_err(|_| usage())?;
+      (text, Some((start, end)))
+    }
+    _ => return Err(usage()),
+  };
+
+  println!(
+    "{} chars in {} bytes ({} UTF-16 code units)",
+    text.chars().count(),
+    text.len(),
+    text.encode_utf16().count()
+  );
+  println!("{}", unicode::table(text));
+  println!(
+    "bytes that are not char boundaries: {:?}",
+    unicode::non_boundaries(text)
+  );
+
+  if let Some((start, end)) = range {
+    match unicode::slice(text, start, end) {
+      Ok(slice) => println!("&text[{}..{}] is {:?}", start, end, slice),
+      Err(error) => println!("&text[{}..{}] panics: {}", start, end, error),
+    }
+  }
+  Ok(())
+}
+
+fn layout(args: &[String]) -> Result<(), String> {
+  match args {
+    [] => {
+      println!("{}", layout::summary(&layout::primitives()));
+      println!("{}", layout::summary(&layout::custom_types()));
+    }
+    [name] => match layout::find(name) {
+      Some(layout) => println!("{}", layout),
+      None => {
+        let names: Vec<String> = layout::primitives()
+          .into_iter()
+          .chain(layout::custom_types())
+          .map(|layout| layout.name)
+          .collect();
+        return Err(format!(
+          "unknown type `{}`, expected one of: {}",
+          name,
+          names.join(", ")
+        ));
+      }
+    },
+    _ => return Err("usage: layout [<type>]".to_owned()),
+  }
+  Ok(())
+}
+
+fn slices(args: &[String]) -> Result<(), String> {
+  let usage = || {
+    "usage: slices <items> <method> <n>...\n\n\
+     methods: windows <size>, chunks <size>, chunks_exact <size>,\n\
+     rchunks <size>, split_at <mid>, split <separator>, range <start> <end>"
+      .to_owned()
+  };
+  let (items, method, numbers) = match args {
+    [items, method, numbers @ ..] => (items, method.as_str(), numbers),
+    _ => return Err(usage()),
+  };
+  let items = integers(items)?;
+  let numbers = numbers
+    .iter()
+    .map(|n| n.parse::<usize>().map_err(|_| usage()))
+    .collect::<Result<Vec<usize>, String>>();
+
+  let explorer = Explorer::new("xs", &items).map_err(|e| e.to_string())?;
+  let view = match (method, &numbers.as_deref()) {
+    ("windows", Ok([size])) => explorer.windows(*size),
+    ("chunks", Ok([size])) => explorer.chunks(*size),
+    ("chunks_exact", Ok([size])) => explorer.chunks_exact(*size),
+    ("rchunks", Ok([size])) => explorer.rchunks(*size),
+    ("split_at", Ok([mid])) => explorer.split_at(*mid),
+    ("range", Ok([start, end])) => explorer.range(*start, *end),
+    // The separator may be negative, so it isn't one of the numbers
+    ("split", _) => match args {
+      [_, _, separator] => {
+        let separator: i64 = separator.parse().map_err(|_| usage())?;
+        let description = format!("|&x| x == {}", separator);
+        Ok(explorer.split(&description, |&x| x == separator))
+      }
+      _ => return Err(usage()),
+    },
+    _ => return Err(usage()),
+  };
+  println!("{}", view.map_err(|e| e.to_string())?);
+  Ok(())
+}
+
+// Comma separated integers, like `5,1,4`
+fn integers(items: &str) -> Result<Vec<i64>, String> {
+  items
+    .split(',')
+    .map(|item| {
+      item
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| format!("`{}` is not an integer", item))
+    })
+    .collect()
+}
+
+// Print the frames of `events` on `input`, one after the other or in place
+// with `--delay`, or print them as JSON with `--json`
+fn replay(
+  name: &str,
+  input: &[i64],
+  events: &[Event<i64>],
+  options: &[String],
+  usage: &str,
+) -> Result<(), String> {
+  let delay = match options {
+    [] => None,
+    [json] if json == "--json" => {
+      println!("{:#}", sorting::to_json(name, input, events));
+      return Ok(());
+    }
+    [flag, ms] if flag == "--delay" => Some(ms.parse::<u64>().map_err(|_| usage.to_owned())?),
+    _ => return Err(usage.to_owned()),
+  };
+
+  for frame in sorting::replay(input, events) {
+    match delay {
+      Some(ms) => {
+        // Clear the terminal, so that the frame is drawn over the last one
+        print!("\x1b[2J\x1b[H");
+        println!("{}\n{}", name, frame);
+        thread::sleep(Duration::from_millis(ms));
+      }
+      None => println!("{}\n", frame),
+    }
+  }
+  println!("{}: {}", name, Counts::of(events));
+  Ok(())
+}
+
+const SORT_USAGE: &str = "usage: sort <algorithm> <items> [--json | --delay <ms>]";
+const SEARCH_USAGE: &str = "usage: search <items> <target> [--json | --delay <ms>]";
+
+fn sort(args: &[String]) -> Result<(), String> {
+  let (algorithm, items, options) = match args {
+    [algorithm, items, options @ ..] => (algorithm, items, options),
+    _ => return Err(SORT_USAGE.to_owned()),
+  };
+  let algorithm: Algorithm = algorithm.parse()?;
+  let input = integers(items)?;
+
+  let mut items = input.clone();
+  let mut events = Vec::new();
+  algorithm.sort(&mut items, &mut events);
+  let name = format!("{} sort", algorithm);
+  replay(&name, &input, &events, options, SORT_USAGE)
+}
+
+fn search(args: &[String]) -> Result<(), String> {
+  let (items, target, options) = match args {
+    [items, target, options @ ..] => (items, target, options),
+    _ => return Err(SEARCH_USAGE.to_owned()),
+  };
+  let items = integers(items)?;
+  let target: i64 = target
+    .parse()
+    .map_err(|_| format!("`{}` is not an integer", target))?;
+  if !items.windows(2).all(|pair| pair[0] <= pair[1]) {
+    return Err("binary search needs sorted items".to_owned());
+  }
+
+  let mut events = Vec::new();
+  let found = sorting::binary_search(&items, &target, &mut events);
+  let name = format!("binary search for {}", target);
+  replay(&name, &items, &events, options, SEARCH_USAGE)?;
+  if options.first().map(String::as_str) != Some("--json") {
+    match found {
+      Ok(index) => println!("found at index {}", index),
+      Err(index) => println!("not found, it would be inserted at index {}", index),
+    }
+  }
+  Ok(())
+}
+
+fn stats(args: &[String]) -> Result<(), String> {
+  let (ty, values) = match args.split_first() {
+    Some((ty, values)) => (ty, values),
+    None => return Err("usage: stats <type> <value>...".to_owned()),
+  };
+
+  fn print<T: Number + FromStr>(values: &[String]) -> Result<(), String> {
+    let values = values
+      .iter()
+      .map(|value| {
+        value
+          .parse()
+          .map_err(|_| format!("`{}` is not a valid {}", value, std::any::type_name::<T>()))
+      })
+      .collect::<Result<Vec<T>, String>>()?;
+    let stats = stats::analyze(&values).map_err(|e| e.to_string())?;
+    println!("{:.3}", stats);
+    Ok(())
+  }
+
+  match ty.as_str() {
+    "i8" => print::<i8>(values),
+    "i16" => print::<i16>(values),
+    "i32" => print::<i32>(values),
+    "i64" => print::<i64>(values),
+    "i128" => print::<i128>(values),
+    "isize" => print::<isize>(values),
+    "u8" => print::<u8>(values),
+    "u16" => print::<u16>(values),
+    "u32" => print::<u32>(values),
+    "u64" => print::<u64>(values),
+    "u128" => print::<u128>(values),
+    "usize" => print::<usize>(values),
+    "f32" => print::<f32>(values),
+    "f64" => print::<f64>(values),
+    _ => Err(format!(
+      "unknown type `{}`, expected an integer or float type",
+      ty
+    )),
+  }
+}
+
+const PEOPLE_USAGE: &str = "usage: people <file> [<option>...]
+
+options, applied in order:
+  --sort name|age   sort by name or by age, the other one breaking ties
+  --min-age <age>   keep the people at least this old
+  --max-age <age>   keep the people at most this old
+  --name <text>     keep the people whose name contains the text, in any case
+  --csv, --json     print the people in this format instead of a table";
+
+fn people(args: &[String]) -> Result<(), String> {
+  let usage = || PEOPLE_USAGE.to_owned();
+  let (path, mut options) = match args.split_first() {
+    Some((path, options)) => (path, options),
+    None => return Err(usage()),
+  };
+  let text = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+  let mut people = person::load(&text).map_err(|e| format!("{}: {}", path, e))?;
+  let total = people.len();
+
+  let mut output = None;
+  while let Some((option, rest)) = options.split_first() {
+    if option == "--csv" || option == "--json" {
+      output = Some(option.as_str());
+      options = rest;
+      continue;
+    }
+    let (value, rest) = match rest.split_first() {
+      Some(split) => split,
+      None => return Err(usage()),
+    };
+    let age = || {
+      value
+        .parse::<u8>()
+        .map_err(|_| format!("`{}` is not a valid age", value))
+    };
+    match (option.as_str(), value.as_str()) {
+      ("--sort", "name") => {
+        people.sort_by(|a, b| a.name().cmp(b.name()).then(a.age().cmp(&b.age())))
+      }
+      ("--sort", "age") => {
+        people.sort_by(|a, b| a.age().cmp(&b.age()).then(a.name().cmp(b.name())))
+      }
+      ("--min-age", _) => {
+        let min = age()?;
+        people.retain(|p| p.age() >= min);
+      }
+      ("--max-age", _) => {
+        let max = age()?;
+        people.retain(|p| p.age() <= max);
+      }
+      ("--name", _) => {
+        let text = value.to_lowercase();
+        people.retain(|p| p.name().to_lowercase().contains(&text));
+      }
+      _ => return Err(usage()),
+    }
+    options = rest;
+  }
+
+  match output {
+    Some("--csv") => print!("{}", person::to_csv(&people)),
+    Some(_) => println!("{:#}", people.to_json()),
+    None => {
+      println!("{}", Table::from_records(&people));
+      println!("{} of {} people", people.len(), total);
+    }
+  }
+  Ok(())
+}
+
+fn replay_log(args: &[String]) -> Result<(), String> {
+  let (path, option) = match args {
+    [path] => (path, None),
+    [path, option] if option == "--session" || option == "--json" => (path, Some(option.as_str())),
+    _ => return Err("usage: replay <file> [--session | --json]".to_owned()),
+  };
+  let log = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+  let logged = events::parse(&log).map_err(|e| format!("{}: {}", path, e))?;
+  match option {
+    Some("--session") => {
+      let mut session = Session::new();
+      events::replay(&logged, &mut session);
+      println!("{}", session);
+    }
+    Some(_) => println!("{:#}", logged.to_json()),
+    None => events::replay(&logged, &mut c03::inspect),
+  }
+  Ok(())
+}
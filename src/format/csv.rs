@@ -0,0 +1,208 @@
+/*
+ * Comma separated values.
+ *
+ * A field is quoted when it contains a comma, a quote or a line break, and
+ * the quotes inside it are doubled: `say "hi", then go` is written as
+ * `"say ""hi"", then go"`. A quoted field may span several lines, so records
+ * are not simply the lines of the text.
+ */
+
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  // A quoted field that goes on to the end of the text
+  UnterminatedQuote { line: usize },
+  // A quote in the middle of a field, or text after a closing quote
+  UnexpectedQuote { line: usize, column: usize },
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::UnterminatedQuote { line } => {
+        write!(f, "line {}: the quoted field is never closed", line)
+      }
+      Error::UnexpectedQuote { line, column } => write!(
+        f,
+        "line {}, column {}: a quote can only surround a whole field",
+        line, column
+      ),
+    }
+  }
+}
+
+// `text`, quoted when needed
+pub fn field(text: &str) -> String {
+  if text.contains(&[',', '"', '\n', '\r'][..]) {
+    format!("\"{}\"", text.replace('"', "\"\""))
+  } else {
+    text.to_owned()
+  }
+}
+
+// One record, without the line break
+pub fn record<S: AsRef<str>>(fields: &[S]) -> String {
+  let fields: Vec<String> = fields.iter().map(|f| field(f.as_ref())).collect();
+  fields.join(",")
+}
+
+// Every record of `text`. Empty lines are skipped, and `\r\n` line breaks
+// are read like `\n`.
+pub fn parse(text: &str) -> Result<Vec<Vec<String>>, Error> {
+  let mut records = Vec::new();
+  let mut fields = Vec::new();
+  let mut current = String::new();
+  // Where the current field started, and whether it is quoted
+  let mut quote_line = 0;
+  let mut quoted = false;
+  // The quoted field was closed, only a separator can follow
+  let mut closed = false;
+  let (mut line, mut column) = (1, 0);
+
+  let mut chars = text.chars().peekable();
+  while let Some(c) = chars.next() {
+    column += 1;
+    if quoted {
+      match c {
+        '"' if chars.peek() == Some(&'"') => {
+          chars.next();
+          column += 1;
+          current.push('"');
+        }
+        '"' => {
+          quoted = false;
+          closed = true;
+        }
+        '\n' => {
+          line += 1;
+          column = 0;
+          current.push(c);
+        }
+        _ => current.push(c),
+      }
+      continue;
+    }
+    match c {
+      ',' => {
+        fields.push(std::mem::take(&mut current));
+        closed = false;
+      }
+      '\r' if chars.peek() == Some(&'\n') => column -= 1,
+      '\n' => {
+        // A line with nothing on it is not a record with one empty field
+        if !fields.is_empty() || !current.is_empty() || closed {
+          fields.push(std::mem::take(&mut current));
+          records.push(std::mem::take(&mut fields));
+        }
+        closed = false;
+        line += 1;
+        column = 0;
+      }
+      '"' if current.is_empty() && !closed => {
+        quoted = true;
+        quote_line = line;
+      }
+      _ if c == '"' || closed => return Err(Error::UnexpectedQuote { line, column }),
+      _ => current.push(c),
+    }
+  }
+  if quoted {
+    return Err(Error::UnterminatedQuote { line: quote_line });
+  }
+  if !fields.is_empty() || !current.is_empty() || closed {
+    fields.push(current);
+    records.push(fields);
+  }
+  Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn records(rows: &[&[&str]]) -> Vec<Vec<String>> {
+    rows
+      .iter()
+      .map(|row| row.iter().map(|&field| field.to_owned()).collect())
+      .collect()
+  }
+
+  #[test]
+  fn plain_records() {
+    assert_eq!(
+      parse("Name,Age\nPeter,27\n\nAnna,"),
+      Ok(records(&[
+        &["Name", "Age"],
+        &["Peter", "27"],
+        &["Anna", ""]
+      ]))
+    );
+    assert_eq!(parse(""), Ok(Vec::new()));
+    assert_eq!(parse(",\n"), Ok(records(&[&["", ""]])));
+  }
+
+  #[test]
+  fn quoted_fields() {
+    assert_eq!(
+      parse("\"Smith, Anna\",31"),
+      Ok(records(&[&["Smith, Anna", "31"]]))
+    );
+    assert_eq!(
+      parse("\"say \"\"hi\"\"\",\"\"\"\""),
+      Ok(records(&[&["say \"hi\"", "\""]]))
+    );
+    // An empty quoted field is still a field
+    assert_eq!(parse("\"\""), Ok(records(&[&[""]])));
+    assert_eq!(
+      parse("\"two\nlines\",x\ny,z"),
+      Ok(records(&[&["two\nlines", "x"], &["y", "z"]]))
+    );
+  }
+
+  #[test]
+  fn crlf_line_endings() {
+    assert_eq!(
+      parse("a,b\r\nc,\"d\r\ne\"\r\n"),
+      Ok(records(&[&["a", "b"], &["c", "d\r\ne"]]))
+    );
+    assert_eq!(
+      parse("a,\"b\"x\r\n"),
+      Err(Error::UnexpectedQuote { line: 1, column: 6 })
+    );
+  }
+
+  #[test]
+  fn unterminated_quote() {
+    assert_eq!(
+      parse("a,b\n\"never\nclosed,c\n"),
+      Err(Error::UnterminatedQuote { line: 2 })
+    );
+    assert_eq!(
+      Error::UnterminatedQuote { line: 2 }.to_string(),
+      "line 2: the quoted field is never closed"
+    );
+  }
+
+  #[test]
+  fn misplaced_quotes() {
+    assert_eq!(
+      parse("a,b\nsay \"hi\""),
+      Err(Error::UnexpectedQuote { line: 2, column: 5 })
+    );
+    assert_eq!(
+      parse("\"closed\" too"),
+      Err(Error::UnexpectedQuote { line: 1, column: 9 })
+    );
+  }
+
+  #[test]
+  fn written_fields_read_back() {
+    let fields = ["plain", "a, b", "say \"hi\"", "two\nlines", "", "\r\n"];
+    assert_eq!(
+      record(&fields),
+      "plain,\"a, b\",\"say \"\"hi\"\"\",\"two\nlines\",,\"\r\n\""
+    );
+    assert_eq!(parse(&record(&fields)), Ok(records(&[&fields])));
+  }
+}
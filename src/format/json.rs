@@ -0,0 +1,1062 @@
+/*
+ * JSON documents.
+ *
+ * A `Value` is a parsed document. Objects keep their members in order, as a
+ * list of pairs, so that printing a parsed document gives the keys back in
+ * the order they were written.
+ *
+ * `parse` follows RFC 8259 to the letter: no trailing commas, no comments,
+ * no single quotes, no leading zeros and no duplicate keys. An error tells
+ * the line and the column, counted from 1, of the character that doesn't
+ * fit, and what was expected there instead.
+ *
+ * `Display` writes the compact form, without any spaces, and `{:#}` the
+ * pretty one.
+ *
+ * Types are converted to and from values with `ToJson` and `FromJson`.
+ * Structs are objects with a member per field, and enums are tagged by the
+ * name of their variant: a unit variant is just its name, and any other one
+ * an object with the name as its only key,
+ *
+ *    "PageLoad"    {"KeyPress": "x"}    {"Click": {"x": 20, "y": 80}}
+ *
+ * Numbers without a fraction or an exponent are kept as an `Integer`, so
+ * that an `i64` or a `u64` goes through a document exactly; `f64` only has
+ * 53 bits of mantissa and would round `u64::MAX` to `2^64`.
+ *
+ * Decoding is as strict as parsing: members that the type doesn't have are
+ * errors, as are numbers that don't fit, and the error tells where in the
+ * value it is, like `at .top_left.x`.
+ */
+
+use std::convert::{TryFrom, TryInto};
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+  Null,
+  Bool(bool),
+  Integer(i128),
+  Number(f64),
+  String(String),
+  Array(Vec<Value>),
+  Object(Vec<(String, Value)>),
+}
+
+impl Value {
+  // The member `key` of an object
+  pub fn get(&self, key: &str) -> Option<&Value> {
+    match self {
+      Value::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+      _ => None,
+    }
+  }
+
+  pub fn as_bool(&self) -> Option<bool> {
+    match self {
+      Value::Bool(b) => Some(*b),
+      _ => None,
+    }
+  }
+
+  pub fn as_f64(&self) -> Option<f64> {
+    match self {
+      Value::Integer(n) => Some(*n as f64),
+      Value::Number(n) => Some(*n),
+      _ => None,
+    }
+  }
+
+  pub fn as_i128(&self) -> Option<i128> {
+    match self {
+      Value::Integer(n) => Some(*n),
+      _ => None,
+    }
+  }
+
+  pub fn as_str(&self) -> Option<&str> {
+    match self {
+      Value::String(s) => Some(s),
+      _ => None,
+    }
+  }
+
+  pub fn as_array(&self) -> Option<&[Value]> {
+    match self {
+      Value::Array(items) => Some(items),
+      _ => None,
+    }
+  }
+
+  pub fn as_object(&self) -> Option<&[(String, Value)]> {
+    match self {
+      Value::Object(members) => Some(members),
+      _ => None,
+    }
+  }
+}
+
+// `text` between quotes, with the quotes, backslashes and control characters
+// escaped
+fn quote(text: &str) -> String {
+  let mut out = String::with_capacity(text.len() + 2);
+  out.push('"');
+  for c in text.chars() {
+    match c {
+      '"' => out.push_str("\\\""),
+      '\\' => out.push_str("\\\\"),
+      '\n' => out.push_str("\\n"),
+      '\r' => out.push_str("\\r"),
+      '\t' => out.push_str("\\t"),
+      c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+      c => out.push(c),
+    }
+  }
+  out.push('"');
+  out
+}
+
+impl Value {
+  fn is_container(&self) -> bool {
+    matches!(self, Value::Array(_) | Value::Object(_))
+  }
+
+  // Compact without `indent`, otherwise one item per line at this depth
+  fn write(&self, out: &mut String, indent: Option<usize>) {
+    // The line break and indentation before an item at `depth`
+    let newline = |out: &mut String, depth: usize| {
+      out.push('\n');
+      out.push_str(&"  ".repeat(depth));
+    };
+    match self {
+      Value::Null => out.push_str("null"),
+      Value::Bool(b) => out.push_str(&b.to_string()),
+      Value::Integer(n) => out.push_str(&n.to_string()),
+      // JSON has no infinities and no NaN
+      Value::Number(n) if !n.is_finite() => out.push_str("null"),
+      Value::Number(n) => out.push_str(&n.to_string()),
+      Value::String(s) => out.push_str(&quote(s)),
+      // Numbers and strings stay on one line, like the rows of a matrix
+      Value::Array(items) if indent.is_none() || !items.iter().any(Value::is_container) => {
+        let separator = if indent.is_some() { ", " } else { "," };
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 {
+            out.push_str(separator);
+          }
+          item.write(out, indent);
+        }
+        out.push(']');
+      }
+      Value::Array(items) => {
+        let depth = indent.unwrap_or(0);
+        out.push('[');
+        for (i, item) in items.iter().enumerate() {
+          if i > 0 {
+            out.push(',');
+          }
+          newline(out, depth + 1);
+          item.write(out, Some(depth + 1));
+        }
+        newline(out, depth);
+        out.push(']');
+      }
+      Value::Object(members) => {
+        out.push('{');
+        for (i, (key, value)) in members.iter().enumerate() {
+          if i > 0 {
+            out.push(',');
+          }
+          match indent {
+            Some(depth) => {
+              newline(out, depth + 1);
+              out.push_str(&quote(key));
+              out.push_str(": ");
+              value.write(out, Some(depth + 1));
+            }
+            None => {
+              out.push_str(&quote(key));
+              out.push(':');
+              value.write(out, None);
+            }
+          }
+        }
+        if let (Some(depth), false) = (indent, members.is_empty()) {
+          newline(out, depth);
+        }
+        out.push('}');
+      }
+    }
+  }
+}
+
+// The compact form, or with `{:#}` the pretty one, indented by two spaces
+impl Display for Value {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let mut out = String::new();
+    self.write(&mut out, if f.alternate() { Some(0) } else { None });
+    f.write_str(&out)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+  // `found` is `None` at the end of the text
+  Unexpected {
+    found: Option<char>,
+    expected: String,
+  },
+  InvalidEscape(String),
+  // Control characters must be escaped in strings
+  ControlCharacter(char),
+  // Too large for an `f64`
+  NumberOutOfRange(String),
+  DuplicateKey(String),
+  TooDeep,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+  pub line: usize,
+  pub column: usize,
+  pub kind: ErrorKind,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "line {}, column {}: ", self.line, self.column)?;
+    match &self.kind {
+      ErrorKind::Unexpected {
+        found: Some(c),
+        expected,
+      } => write!(f, "expected {}, found {:?}", expected, c),
+      ErrorKind::Unexpected {
+        found: None,
+        expected,
+      } => write!(f, "expected {}, found the end of the text", expected),
+      ErrorKind::InvalidEscape(escape) => write!(f, "invalid escape `{}`", escape),
+      ErrorKind::ControlCharacter(c) => {
+        write!(f, "the control character {:?} must be escaped", c)
+      }
+      ErrorKind::NumberOutOfRange(n) => write!(f, "the number {} is too large", n),
+      ErrorKind::DuplicateKey(key) => write!(f, "the key {} appears twice", quote(key)),
+      ErrorKind::TooDeep => write!(f, "arrays and objects are nested too deeply"),
+    }
+  }
+}
+
+// Deeper documents are rejected instead of overflowing the stack
+const MAX_DEPTH: usize = 128;
+
+struct Parser<'a> {
+  text: &'a str,
+  // A byte offset in `text`
+  position: usize,
+  depth: usize,
+}
+
+impl<'a> Parser<'a> {
+  fn error_at(&self, position: usize, kind: ErrorKind) -> Error {
+    let before = &self.text[..position];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    Error {
+      line: before.matches('\n').count() + 1,
+      column: before[line_start..].chars().count() + 1,
+      kind,
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.text[self.position..].chars().next()
+  }
+
+  fn next(&mut self) -> Option<char> {
+    let c = self.peek()?;
+    self.position += c.len_utf8();
+    Some(c)
+  }
+
+  // An error about the next character
+  fn unexpected(&self, expected: &str) -> Error {
+    let kind = ErrorKind::Unexpected {
+      found: self.peek(),
+      expected: expected.to_owned(),
+    };
+    self.error_at(self.position, kind)
+  }
+
+  fn skip_whitespace(&mut self) {
+    while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+      self.position += 1;
+    }
+  }
+
+  fn expect(&mut self, c: char) -> Result<(), Error> {
+    if self.peek() == Some(c) {
+      self.position += 1;
+      Ok(())
+    } else {
+      Err(self.unexpected(&format!("`{}`", c)))
+    }
+  }
+
+  fn value(&mut self) -> Result<Value, Error> {
+    self.skip_whitespace();
+    match self.peek() {
+      Some('{') => self.nested(Parser::object),
+      Some('[') => self.nested(Parser::array),
+      Some('"') => self.string().map(Value::String),
+      Some('t') => self.literal("true", Value::Bool(true)),
+      Some('f') => self.literal("false", Value::Bool(false)),
+      Some('n') => self.literal("null", Value::Null),
+      Some('-') | Some('0'..='9') => self.number(),
+      _ => Err(self.unexpected("a value")),
+    }
+  }
+
+  fn nested(&mut self, parse: fn(&mut Self) -> Result<Value, Error>) -> Result<Value, Error> {
+    if self.depth == MAX_DEPTH {
+      return Err(self.error_at(self.position, ErrorKind::TooDeep));
+    }
+    self.depth += 1;
+    let value = parse(self);
+    self.depth -= 1;
+    value
+  }
+
+  fn literal(&mut self, word: &str, value: Value) -> Result<Value, Error> {
+    for expected in word.chars() {
+      if self.peek() != Some(expected) {
+        return Err(self.unexpected(&format!("`{}`", word)));
+      }
+      self.position += 1;
+    }
+    Ok(value)
+  }
+
+  fn digits(&mut self) -> Result<(), Error> {
+    if !matches!(self.peek(), Some('0'..='9')) {
+      return Err(self.unexpected("a digit"));
+    }
+    while let Some('0'..='9') = self.peek() {
+      self.position += 1;
+    }
+    Ok(())
+  }
+
+  // `-`, then `0` or digits that don't start with `0`, then an optional
+  // fraction and an optional exponent
+  fn number(&mut self) -> Result<Value, Error> {
+    let start = self.position;
+    if self.peek() == Some('-') {
+      self.position += 1;
+    }
+    if self.peek() == Some('0') {
+      self.position += 1;
+    } else {
+      self.digits()?;
+    }
+    if self.peek() == Some('.') {
+      self.position += 1;
+      self.digits()?;
+    }
+    if let Some('e') | Some('E') = self.peek() {
+      self.position += 1;
+      if let Some('+') | Some('-') = self.peek() {
+        self.position += 1;
+      }
+      self.digits()?;
+    }
+    let text = &self.text[start..self.position];
+    // Beyond `i128`, an integer is read as an `f64` like any other number
+    if let Ok(n) = text.parse::<i128>() {
+      return Ok(Value::Integer(n));
+    }
+    match text.parse::<f64>() {
+      Ok(n) if n.is_finite() => Ok(Value::Number(n)),
+      _ => Err(self.error_at(start, ErrorKind::NumberOutOfRange(text.to_owned()))),
+    }
+  }
+
+  // The 4 hexadecimal digits after `\u`
+  fn code_unit(&mut self, start: usize) -> Result<u32, Error> {
+    let end = (self.position + 4).min(self.text.len());
+    let hex = self.text.get(self.position..end).unwrap_or("");
+    match u32::from_str_radix(hex, 16) {
+      Ok(unit) if hex.len() == 4 && hex.bytes().all(|b| b.is_ascii_hexdigit()) => {
+        self.position += 4;
+        Ok(unit)
+      }
+      _ => {
+        let escape: String = self.text[start..].chars().take(6).collect();
+        Err(self.error_at(start, ErrorKind::InvalidEscape(escape)))
+      }
+    }
+  }
+
+  // A `\u` escape, or two for a character outside the basic plane, which is
+  // written as a surrogate pair
+  fn unicode_escape(&mut self, start: usize) -> Result<char, Error> {
+    let invalid = |parser: &Self| {
+      let escape = parser.text[start..parser.position].to_owned();
+      Err(parser.error_at(start, ErrorKind::InvalidEscape(escape)))
+    };
+    let high = self.code_unit(start)?;
+    let unit = match high {
+      0xd800..=0xdbff => {
+        if !self.text[self.position..].starts_with("\\u") {
+          return invalid(self);
+        }
+        self.position += 2;
+        let low = self.code_unit(start)?;
+        if !(0xdc00..=0xdfff).contains(&low) {
+          return invalid(self);
+        }
+        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+      }
+      0xdc00..=0xdfff => return invalid(self),
+      unit => unit,
+    };
+    match char::from_u32(unit) {
+      Some(c) => Ok(c),
+      None => invalid(self),
+    }
+  }
+
+  fn string(&mut self) -> Result<String, Error> {
+    self.expect('"')?;
+    let mut out = String::new();
+    loop {
+      let start = self.position;
+      match self.next() {
+        None => return Err(self.unexpected("a closing `\"`")),
+        Some('"') => return Ok(out),
+        Some('\\') => {
+          let c = match self.next() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => self.unicode_escape(start)?,
+            _ => {
+              let escape = self.text[start..self.position].to_owned();
+              return Err(self.error_at(start, ErrorKind::InvalidEscape(escape)));
+            }
+          };
+          out.push(c);
+        }
+        Some(c) if (c as u32) < 0x20 => {
+          return Err(self.error_at(start, ErrorKind::ControlCharacter(c)));
+        }
+        Some(c) => out.push(c),
+      }
+    }
+  }
+
+  fn array(&mut self) -> Result<Value, Error> {
+    self.expect('[')?;
+    let mut items = Vec::new();
+    self.skip_whitespace();
+    if self.peek() == Some(']') {
+      self.position += 1;
+      return Ok(Value::Array(items));
+    }
+    loop {
+      items.push(self.value()?);
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.position += 1,
+        Some(']') => {
+          self.position += 1;
+          return Ok(Value::Array(items));
+        }
+        _ => return Err(self.unexpected("`,` or `]`")),
+      }
+    }
+  }
+
+  fn object(&mut self) -> Result<Value, Error> {
+    self.expect('{')?;
+    let mut members: Vec<(String, Value)> = Vec::new();
+    self.skip_whitespace();
+    if self.peek() == Some('}') {
+      self.position += 1;
+      return Ok(Value::Object(members));
+    }
+    loop {
+      self.skip_whitespace();
+      if self.peek() != Some('"') {
+        return Err(self.unexpected("a string key"));
+      }
+      let start = self.position;
+      let key = self.string()?;
+      if members.iter().any(|(k, _)| *k == key) {
+        return Err(self.error_at(start, ErrorKind::DuplicateKey(key)));
+      }
+      self.skip_whitespace();
+      self.expect(':')?;
+      let value = self.value()?;
+      members.push((key, value));
+      self.skip_whitespace();
+      match self.peek() {
+        Some(',') => self.position += 1,
+        Some('}') => {
+          self.position += 1;
+          return Ok(Value::Object(members));
+        }
+        _ => return Err(self.unexpected("`,` or `}`")),
+      }
+    }
+  }
+}
+
+// The single value that `text` holds, with only whitespace around it
+pub fn parse(text: &str) -> Result<Value, Error> {
+  let mut parser = Parser {
+    text,
+    position: 0,
+    depth: 0,
+  };
+  let value = parser.value()?;
+  parser.skip_whitespace();
+  if parser.position < text.len() {
+    return Err(parser.unexpected("the end of the text"));
+  }
+  Ok(value)
+}
+
+pub trait ToJson {
+  fn to_json(&self) -> Value;
+}
+
+pub trait FromJson: Sized {
+  fn from_json(value: &Value) -> Result<Self, DecodeError>;
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DecodeError {
+  // What was expected, and the value that was found instead
+  Expected(&'static str, String),
+  MissingField(&'static str),
+  UnknownField(String),
+  UnknownVariant(String),
+  // The value has the right shape but the type rejects it
+  Invalid(String),
+  // The error is in this member, `.name`, or item, `[2]`
+  At(String, Box<DecodeError>),
+}
+
+impl DecodeError {
+  fn at(self, place: String) -> DecodeError {
+    DecodeError::At(place, Box::new(self))
+  }
+}
+
+impl Display for DecodeError {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    // Nested places make a single path
+    let mut error = self;
+    let mut path = String::new();
+    while let DecodeError::At(place, inner) = error {
+      path.push_str(place);
+      error = inner;
+    }
+    if !path.is_empty() {
+      write!(f, "at {}: ", path)?;
+    }
+    match error {
+      DecodeError::Expected(what, found) => write!(f, "expected {}, found {}", what, found),
+      DecodeError::MissingField(field) => write!(f, "the member {} is missing", quote(field)),
+      DecodeError::UnknownField(field) => write!(f, "unknown member {}", quote(field)),
+      DecodeError::UnknownVariant(variant) => write!(f, "unknown variant {}", quote(variant)),
+      DecodeError::Invalid(message) => write!(f, "{}", message),
+      DecodeError::At(..) => unreachable!(),
+    }
+  }
+}
+
+// Parse `text` and decode it as a `T`
+pub fn from_str<T: FromJson>(text: &str) -> Result<T, String> {
+  T::from_json(&parse(text).map_err(|e| e.to_string())?).map_err(|e| e.to_string())
+}
+
+// The members of an object, which may only have the keys in `fields`
+pub fn object<'a>(value: &'a Value, fields: &[&str]) -> Result<&'a [(String, Value)], DecodeError> {
+  let members = value
+    .as_object()
+    .ok_or_else(|| DecodeError::Expected("an object", value.to_string()))?;
+  match members
+    .iter()
+    .find(|(key, _)| !fields.contains(&key.as_str()))
+  {
+    Some((key, _)) => Err(DecodeError::UnknownField(key.clone())),
+    None => Ok(members),
+  }
+}
+
+// Decode the member `key` of an object
+pub fn field<T: FromJson>(value: &Value, key: &'static str) -> Result<T, DecodeError> {
+  match value.get(key) {
+    Some(member) => T::from_json(member).map_err(|e| e.at(format!(".{}", key))),
+    None => Err(DecodeError::MissingField(key)),
+  }
+}
+
+// The name and the content of an enum variant. A unit variant has no
+// content.
+pub fn variant(value: &Value) -> Result<(&str, Option<&Value>), DecodeError> {
+  match value {
+    Value::String(name) => Ok((name, None)),
+    Value::Object(members) if members.len() == 1 => Ok((&members[0].0, Some(&members[0].1))),
+    _ => Err(DecodeError::Expected(
+      "a variant name, or an object with one member",
+      value.to_string(),
+    )),
+  }
+}
+
+// Decode the content of the variant `name`, so that errors show the name
+// in their path
+pub fn within<T, F>(name: &str, decode: F) -> Result<T, DecodeError>
+where
+  F: FnOnce() -> Result<T, DecodeError>,
+{
+  decode().map_err(|e| e.at(format!(".{}", name)))
+}
+
+// `{"name": content}`
+pub fn tagged(name: &str, content: Value) -> Value {
+  Value::Object(vec![(name.to_owned(), content)])
+}
+
+impl ToJson for Value {
+  fn to_json(&self) -> Value {
+    self.clone()
+  }
+}
+
+impl FromJson for Value {
+  fn from_json(value: &Value) -> Result<Value, DecodeError> {
+    Ok(value.clone())
+  }
+}
+
+impl ToJson for bool {
+  fn to_json(&self) -> Value {
+    Value::Bool(*self)
+  }
+}
+
+impl FromJson for bool {
+  fn from_json(value: &Value) -> Result<bool, DecodeError> {
+    value
+      .as_bool()
+      .ok_or_else(|| DecodeError::Expected("a boolean", value.to_string()))
+  }
+}
+
+// An integer must be a whole number within the range of its type. Written
+// with an exponent, like `2e3`, it is an `f64`, whose upper bound is
+// `MAX + 1`: unlike `MAX` it is exact as an `f64`, and a plain `as`
+// saturates, so it would read `2^64` as `u64::MAX`.
+macro_rules! json_integer {
+  ($($t:ty),*) => {
+    $(
+      impl ToJson for $t {
+        fn to_json(&self) -> Value {
+          Value::Integer(*self as i128)
+        }
+      }
+
+      impl FromJson for $t {
+        fn from_json(value: &Value) -> Result<$t, DecodeError> {
+          match value {
+            Value::Integer(n) if <$t>::try_from(*n).is_ok() => Ok(*n as $t),
+            Value::Number(n)
+              if n.fract() == 0.0 && *n >= <$t>::MIN as f64 && *n < <$t>::MAX as f64 + 1.0 =>
+            {
+              Ok(*n as $t)
+            }
+            _ => Err(DecodeError::Expected(
+              concat!("a number that fits in ", stringify!($t)),
+              value.to_string(),
+            )),
+          }
+        }
+      }
+    )*
+  };
+}
+
+json_integer!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl ToJson for f64 {
+  fn to_json(&self) -> Value {
+    Value::Number(*self)
+  }
+}
+
+// Any number, parsing already rejected the ones out of range
+impl FromJson for f64 {
+  fn from_json(value: &Value) -> Result<f64, DecodeError> {
+    value
+      .as_f64()
+      .ok_or_else(|| DecodeError::Expected("a number", value.to_string()))
+  }
+}
+
+// Any number, rounded to the nearest `f32`
+impl ToJson for f32 {
+  // Through the shortest decimal that reads back as the same `f32`: `0.1`
+  // rather than `0.10000000149011612`, which is what `as f64` gives
+  fn to_json(&self) -> Value {
+    Value::Number(self.to_string().parse().unwrap_or(f64::NAN))
+  }
+}
+
+impl FromJson for f32 {
+  fn from_json(value: &Value) -> Result<f32, DecodeError> {
+    match value.as_f64() {
+      Some(n) if (n as f32).is_finite() => Ok(n as f32),
+      _ => Err(DecodeError::Expected(
+        "a number that fits in f32",
+        value.to_string(),
+      )),
+    }
+  }
+}
+
+impl ToJson for char {
+  fn to_json(&self) -> Value {
+    Value::String(self.to_string())
+  }
+}
+
+impl FromJson for char {
+  fn from_json(value: &Value) -> Result<char, DecodeError> {
+    let mut chars = value.as_str().unwrap_or("").chars();
+    match (chars.next(), chars.next()) {
+      (Some(c), None) => Ok(c),
+      _ => Err(DecodeError::Expected(
+        "a single character",
+        value.to_string(),
+      )),
+    }
+  }
+}
+
+impl ToJson for str {
+  fn to_json(&self) -> Value {
+    Value::String(self.to_owned())
+  }
+}
+
+impl ToJson for String {
+  fn to_json(&self) -> Value {
+    Value::String(self.clone())
+  }
+}
+
+impl FromJson for String {
+  fn from_json(value: &Value) -> Result<String, DecodeError> {
+    value
+      .as_str()
+      .map(str::to_owned)
+      .ok_or_else(|| DecodeError::Expected("a string", value.to_string()))
+  }
+}
+
+// `null` when there is nothing
+impl<T: ToJson> ToJson for Option<T> {
+  fn to_json(&self) -> Value {
+    match self {
+      Some(inner) => inner.to_json(),
+      None => Value::Null,
+    }
+  }
+}
+
+impl<T: FromJson> FromJson for Option<T> {
+  fn from_json(value: &Value) -> Result<Option<T>, DecodeError> {
+    match value {
+      Value::Null => Ok(None),
+      _ => T::from_json(value).map(Some),
+    }
+  }
+}
+
+impl<T: ToJson> ToJson for [T] {
+  fn to_json(&self) -> Value {
+    Value::Array(self.iter().map(T::to_json).collect())
+  }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+  fn to_json(&self) -> Value {
+    self.as_slice().to_json()
+  }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+  fn from_json(value: &Value) -> Result<Vec<T>, DecodeError> {
+    let items = value
+      .as_array()
+      .ok_or_else(|| DecodeError::Expected("an array", value.to_string()))?;
+    items
+      .iter()
+      .enumerate()
+      .map(|(i, item)| T::from_json(item).map_err(|e| e.at(format!("[{}]", i))))
+      .collect()
+  }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+  fn to_json(&self) -> Value {
+    self.as_slice().to_json()
+  }
+}
+
+// An array of exactly `N` items
+impl<T: FromJson, const N: usize> FromJson for [T; N] {
+  fn from_json(value: &Value) -> Result<[T; N], DecodeError> {
+    let items = Vec::<T>::from_json(value)?;
+    let len = items.len();
+    items
+      .try_into()
+      .map_err(|_| DecodeError::Invalid(format!("expected an array of {} items, found {}", N, len)))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn error(text: &str) -> (usize, usize, ErrorKind) {
+    let error = parse(text).unwrap_err();
+    (error.line, error.column, error.kind)
+  }
+
+  fn unexpected(found: Option<char>, expected: &str) -> ErrorKind {
+    ErrorKind::Unexpected {
+      found,
+      expected: expected.to_owned(),
+    }
+  }
+
+  #[test]
+  fn parses_every_kind_of_value() {
+    let value = parse(" {\"a\": [1, -2.5e3, true, false, null], \"b\": {\"c\": \"d\"}} ").unwrap();
+    assert_eq!(
+      value,
+      Value::Object(vec![
+        (
+          "a".to_owned(),
+          Value::Array(vec![
+            Value::Integer(1),
+            Value::Number(-2500.0),
+            Value::Bool(true),
+            Value::Bool(false),
+            Value::Null,
+          ])
+        ),
+        (
+          "b".to_owned(),
+          Value::Object(vec![("c".to_owned(), Value::String("d".to_owned()))])
+        ),
+      ])
+    );
+    assert_eq!(
+      value.get("b").and_then(|b| b.get("c")),
+      Some(&Value::String("d".to_owned()))
+    );
+    assert_eq!(parse("[]"), Ok(Value::Array(Vec::new())));
+    assert_eq!(parse("{}"), Ok(Value::Object(Vec::new())));
+    assert_eq!(parse("0"), Ok(Value::Integer(0)));
+    assert_eq!(parse("-0"), Ok(Value::Integer(0)));
+    assert_eq!(parse("1.0"), Ok(Value::Number(1.0)));
+    assert_eq!(parse("-0.5E-1"), Ok(Value::Number(-0.05)));
+  }
+
+  #[test]
+  fn keeps_the_order_of_the_keys() {
+    let text = "{\"z\":1,\"a\":[2,{\"m\":null}],\"k\":\"\\\"\"}";
+    assert_eq!(parse(text).unwrap().to_string(), text);
+  }
+
+  #[test]
+  fn rejects_trailing_commas() {
+    assert_eq!(error("[1, 2,]"), (1, 7, unexpected(Some(']'), "a value")));
+    assert_eq!(
+      error("{\"a\": 1,}"),
+      (1, 9, unexpected(Some('}'), "a string key"))
+    );
+  }
+
+  #[test]
+  fn rejects_leading_zeros() {
+    assert_eq!(
+      error("01"),
+      (1, 2, unexpected(Some('1'), "the end of the text"))
+    );
+    assert_eq!(error("[-007]"), (1, 4, unexpected(Some('0'), "`,` or `]`")));
+    assert_eq!(error("1."), (1, 3, unexpected(None, "a digit")));
+    assert_eq!(error(".5"), (1, 1, unexpected(Some('.'), "a value")));
+    assert_eq!(error("+1"), (1, 1, unexpected(Some('+'), "a value")));
+    assert_eq!(
+      error("1e999"),
+      (1, 1, ErrorKind::NumberOutOfRange("1e999".to_owned()))
+    );
+  }
+
+  #[test]
+  fn rejects_duplicate_keys() {
+    assert_eq!(
+      error("{\"a\": 1,\n \"b\": 2,\n \"a\": 3}"),
+      (3, 2, ErrorKind::DuplicateKey("a".to_owned()))
+    );
+    // Only within the same object
+    assert!(parse("{\"a\": {\"a\": 1}}").is_ok());
+  }
+
+  #[test]
+  fn surrogate_pairs() {
+    assert_eq!(
+      parse("\"\\ud83d\\ude00 \\u00e9\\u0041\""),
+      Ok(Value::String("😀 éA".to_owned()))
+    );
+    // Half a pair is not a character
+    assert_eq!(
+      error("\"\\ud83d\""),
+      (1, 2, ErrorKind::InvalidEscape("\\ud83d".to_owned()))
+    );
+    assert_eq!(
+      error("\"\\ud83d\\u0041\""),
+      (1, 2, ErrorKind::InvalidEscape("\\ud83d\\u0041".to_owned()))
+    );
+    assert_eq!(
+      error("\"\\ude00\""),
+      (1, 2, ErrorKind::InvalidEscape("\\ude00".to_owned()))
+    );
+    assert_eq!(
+      error("\"\\u12g4\""),
+      (1, 2, ErrorKind::InvalidEscape("\\u12g4".to_owned()))
+    );
+    // Written back unescaped, and read again
+    let value = parse("\"\\ud83d\\ude00\"").unwrap();
+    assert_eq!(parse(&value.to_string()), Ok(value));
+  }
+
+  #[test]
+  fn strings() {
+    assert_eq!(
+      parse("\"a\\\"\\\\\\/\\b\\f\\n\\r\\t\""),
+      Ok(Value::String("a\"\\/\u{8}\u{c}\n\r\t".to_owned()))
+    );
+    assert_eq!(
+      error("\"tab\there\""),
+      (1, 5, ErrorKind::ControlCharacter('\t'))
+    );
+    assert_eq!(
+      error("\"a\\x\""),
+      (1, 3, ErrorKind::InvalidEscape("\\x".to_owned()))
+    );
+    assert_eq!(error("\"open"), (1, 6, unexpected(None, "a closing `\"`")));
+    assert_eq!(error("'a'"), (1, 1, unexpected(Some('\''), "a value")));
+  }
+
+  #[test]
+  fn max_depth() {
+    let nested = |depth: usize| "[".repeat(depth) + &"]".repeat(depth);
+    assert!(parse(&nested(MAX_DEPTH)).is_ok());
+    assert_eq!(
+      error(&nested(MAX_DEPTH + 1)),
+      (1, MAX_DEPTH + 1, ErrorKind::TooDeep)
+    );
+    // Far deeper than the stack would allow
+    assert_eq!(error(&nested(100_000)).2, ErrorKind::TooDeep);
+  }
+
+  #[test]
+  fn line_and_column() {
+    assert_eq!(
+      error("{\n  \"a\": [1, 2],\n  \"b\": tru\n}"),
+      (3, 11, unexpected(Some('\n'), "`true`"))
+    );
+    // Columns count characters, not bytes
+    assert_eq!(
+      error("[\"é😀\" 1]"),
+      (1, 7, unexpected(Some('1'), "`,` or `]`"))
+    );
+    assert_eq!(error(""), (1, 1, unexpected(None, "a value")));
+    assert_eq!(error("{\"a\" 1}"), (1, 6, unexpected(Some('1'), "`:`")));
+    assert_eq!(
+      error("1 // comment"),
+      (1, 3, unexpected(Some('/'), "the end of the text"))
+    );
+    assert_eq!(
+      parse("[\n1,\n2 3]").unwrap_err().to_string(),
+      "line 3, column 3: expected `,` or `]`, found '3'"
+    );
+  }
+
+  #[test]
+  fn pretty_printing() {
+    let value = parse("{\"a\":[1,[]],\"b\":{}}").unwrap();
+    assert_eq!(
+      format!("{:#}", value),
+      "{\n  \"a\": [\n    1,\n    []\n  ],\n  \"b\": {}\n}"
+    );
+    assert_eq!(parse(&format!("{:#}", value)), Ok(value));
+  }
+
+  #[test]
+  fn integers_must_fit() {
+    let decode = |text: &str| parse(text).unwrap();
+    assert_eq!(
+      u64::from_json(&decode("18446744073709549568")),
+      Ok(18_446_744_073_709_549_568)
+    );
+    assert!(u64::from_json(&decode("18446744073709551616")).is_err());
+    assert!(usize::from_json(&decode("1e30")).is_err());
+    assert!(u64::from_json(&decode("-1")).is_err());
+    assert_eq!(
+      i64::from_json(&decode("-9223372036854775808")),
+      Ok(i64::MIN)
+    );
+    assert!(i64::from_json(&decode("9223372036854775808")).is_err());
+    assert!(isize::from_json(&decode("-9223372036854777856")).is_err());
+    assert_eq!(u8::from_json(&decode("255")), Ok(255));
+    assert!(u8::from_json(&decode("256")).is_err());
+    assert_eq!(i8::from_json(&decode("-128")), Ok(-128));
+    assert!(i8::from_json(&decode("-129")).is_err());
+    assert!(i32::from_json(&decode("1.5")).is_err());
+    assert_eq!(i32::from_json(&decode("2e3")), Ok(2000));
+    assert_eq!(f64::from_json(&decode("1e300")), Ok(1e300));
+    assert!(f64::from_json(&decode("\"1\"")).is_err());
+  }
+
+  #[test]
+  fn integers_are_exact() {
+    fn round_trip<T: ToJson + FromJson>(n: T) -> Result<T, DecodeError> {
+      T::from_json(&parse(&n.to_json().to_string()).unwrap())
+    }
+    assert_eq!(i64::MAX.to_json().to_string(), "9223372036854775807");
+    assert_eq!(round_trip(i64::MIN), Ok(i64::MIN));
+    assert_eq!(round_trip(i64::MAX), Ok(i64::MAX));
+    assert_eq!(round_trip(u64::MAX), Ok(u64::MAX));
+    assert_eq!(
+      round_trip(9_007_199_254_740_993u64),
+      Ok(9_007_199_254_740_993)
+    );
+    assert_eq!(round_trip(usize::MAX), Ok(usize::MAX));
+    assert!(i64::from_json(&u64::MAX.to_json()).is_err());
+    // Too long for `i128`, so it's an `f64`, which no integer type takes
+    let huge = parse("1000000000000000000000000000000000000000").unwrap();
+    assert_eq!(huge, Value::Number(1e39));
+    assert!(u64::from_json(&huge).is_err());
+  }
+}
@@ -0,0 +1,163 @@
+/*
+ * A generic version of the `List` from `c01::testcase_list`.
+ *
+ * Instead of hard-coding `[`, `, ` and `]`, the delimiters and the separator
+ * can be configured, each element can be prefixed with its index, and long
+ * lists can be truncated: `[1, 2, 3, … 997 more]`.
+ *
+ * The alternate flag (`{:#}`) switches to a pretty mode with one element per
+ * line. Elements are formatted with the same flag, so a list of lists gets
+ * nested indentation.
+ */
+
+use std::fmt::{self, Display, Formatter};
+
+const INDENT: &str = "  ";
+
+pub struct List<T> {
+  items: Vec<T>,
+  open: String,
+  close: String,
+  separator: String,
+  indexed: bool,
+  limit: Option<usize>,
+}
+
+impl<T> List<T> {
+  // A list that prints like the one in `c01`: `[1, 2, 3]`
+  pub fn new(items: Vec<T>) -> List<T> {
+    List {
+      items,
+      open: "[".to_owned(),
+      close: "]".to_owned(),
+      separator: ", ".to_owned(),
+      indexed: false,
+      limit: None,
+    }
+  }
+
+  pub fn delimiters(mut self, open: &str, close: &str) -> List<T> {
+    self.open = open.to_owned();
+    self.close = close.to_owned();
+    self
+  }
+
+  pub fn separator(mut self, separator: &str) -> List<T> {
+    self.separator = separator.to_owned();
+    self
+  }
+
+  // Prefix every element with its position: `[0: 9, 1: 8]`
+  pub fn indexed(mut self, indexed: bool) -> List<T> {
+    self.indexed = indexed;
+    self
+  }
+
+  // Show at most `limit` elements, followed by how many were left out
+  pub fn truncate(mut self, limit: usize) -> List<T> {
+    self.limit = Some(limit);
+    self
+  }
+
+  pub fn push(&mut self, item: T) {
+    self.items.push(item);
+  }
+
+  pub fn items(&self) -> &[T] {
+    &self.items
+  }
+
+  pub fn len(&self) -> usize {
+    self.items.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.items.is_empty()
+  }
+
+  // Number of elements that are printed, the rest are summarized
+  fn shown(&self) -> usize {
+    match self.limit {
+      Some(limit) => limit.min(self.items.len()),
+      None => self.items.len(),
+    }
+  }
+}
+
+impl<T: Display> List<T> {
+  // Format a single element, forwarding the alternate and precision flags
+  fn entry(&self, index: usize, item: &T, f: &Formatter) -> String {
+    let value = match (f.alternate(), f.precision()) {
+      (true, Some(p)) => format!("{:#.*}", p, item),
+      (true, None) => format!("{:#}", item),
+      (false, Some(p)) => format!("{:.*}", p, item),
+      (false, None) => item.to_string(),
+    };
+
+    if self.indexed {
+      format!("{}: {}", index, value)
+    } else {
+      value
+    }
+  }
+
+  fn fmt_compact(&self, f: &mut Formatter) -> fmt::Result {
+    let shown = self.shown();
+    let hidden = self.items.len() - shown;
+
+    write!(f, "{}", self.open)?;
+    for (count, item) in self.items[..shown].iter().enumerate() {
+      if count != 0 {
+        write!(f, "{}", self.separator)?;
+      }
+      write!(f, "{}", self.entry(count, item, f))?;
+    }
+    if hidden > 0 {
+      if shown > 0 {
+        write!(f, "{}", self.separator)?;
+      }
+      write!(f, "… {} more", hidden)?;
+    }
+    write!(f, "{}", self.close)
+  }
+
+  fn fmt_pretty(&self, f: &mut Formatter) -> fmt::Result {
+    if self.items.is_empty() {
+      return write!(f, "{}{}", self.open, self.close);
+    }
+
+    let shown = self.shown();
+    let hidden = self.items.len() - shown;
+    // At the end of a line the trailing space of `, ` is just noise
+    let separator = self.separator.trim_end();
+
+    writeln!(f, "{}", self.open)?;
+    for (count, item) in self.items[..shown].iter().enumerate() {
+      // Nested elements may span several lines, indent all of them
+      for (line_no, line) in self.entry(count, item, f).lines().enumerate() {
+        if line_no != 0 {
+          writeln!(f)?;
+        }
+        write!(f, "{}{}", INDENT, line)?;
+      }
+      if count + 1 < shown || hidden > 0 {
+        write!(f, "{}", separator)?;
+      }
+      writeln!(f)?;
+    }
+    if hidden > 0 {
+      writeln!(f, "{}… {} more", INDENT, hidden)?;
+    }
+    write!(f, "{}", self.close)
+  }
+}
+
+impl<T: Display> Display for List<T> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    if f.alternate() {
+      self.fmt_pretty(f)
+    } else {
+      self.fmt_compact(f)
+    }
+  }
+}
@@ -0,0 +1,6 @@
+pub mod csv;
+pub mod json;
+pub mod list;
+pub mod number;
+pub mod table;
+pub mod width;
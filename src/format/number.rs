@@ -0,0 +1,240 @@
+/*
+ * Human-friendly numbers.
+ *
+ * In source code `1_000_000u32` is easier to read than `1000000u32`, but
+ * printing it gives back the plain digits. The `Humanize` trait is
+ * implemented for every integer and float primitive, and adds:
+ *
+ *  - thousands separators for a given locale: `1,000,000` or `1.000.000`
+ *  - byte sizes in SI (`2.0 kB`) or IEC (`2.0 KiB`) units
+ *  - scientific (`1.23e4`) and engineering (`12.3e3`) notation
+ *  - ordinal suffixes: `1st`, `2nd`, `3rd`, `11th`
+ */
+
+use std::fmt::{self, Alignment, Display, Formatter, LowerExp};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Locale {
+  pub thousands: char,
+  pub decimal: char,
+}
+
+impl Locale {
+  pub const EN: Locale = Locale {
+    thousands: ',',
+    decimal: '.',
+  };
+  pub const DE: Locale = Locale {
+    thousands: '.',
+    decimal: ',',
+  };
+  // French uses a narrow no-break space between groups
+  pub const FR: Locale = Locale {
+    thousands: '\u{202F}',
+    decimal: ',',
+  };
+  pub const CH: Locale = Locale {
+    thousands: '\'',
+    decimal: '.',
+  };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByteUnits {
+  // Powers of 1000: kB, MB, GB, ...
+  Si,
+  // Powers of 1024: KiB, MiB, GiB, ...
+  Iec,
+}
+
+const SI_UNITS: [&str; 9] = ["B", "kB", "MB", "GB", "TB", "PB", "EB", "ZB", "YB"];
+const IEC_UNITS: [&str; 9] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB", "ZiB", "YiB"];
+
+// Insert `separator` every three digits, counting from the right
+fn group(digits: &str, separator: char) -> String {
+  let mut out = String::new();
+  for (i, digit) in digits.chars().enumerate() {
+    if i != 0 && (digits.len() - i).is_multiple_of(3) {
+      out.push(separator);
+    }
+    out.push(digit);
+  }
+  out
+}
+
+// Split the output of `{:e}` into mantissa and exponent: "1.5e3" -> (1.5, 3)
+fn split_exp(sci: &str) -> (f64, i32) {
+  let mut parts = sci.splitn(2, 'e');
+  let mantissa = parts.next().and_then(|m| m.parse().ok()).unwrap_or(0.0);
+  let exponent = parts.next().and_then(|e| e.parse().ok()).unwrap_or(0);
+  (mantissa, exponent)
+}
+
+// Write an already formatted number, honoring the width, fill and alignment
+// of `f`. Unlike `Formatter::pad`, the precision is not a maximum width:
+// numbers use it for their own digits.
+pub fn pad_number(f: &mut Formatter, text: &str) -> fmt::Result {
+  let fill = f.width().unwrap_or(0).saturating_sub(text.chars().count());
+  // Numbers are right-aligned by default
+  let (left, right) = match f.align() {
+    Some(Alignment::Left) => (0, fill),
+    Some(Alignment::Center) => (fill / 2, fill - fill / 2),
+    Some(Alignment::Right) | None => (fill, 0),
+  };
+  let padding = |count: usize| f.fill().to_string().repeat(count);
+  let (before, after) = (padding(left), padding(right));
+  write!(f, "{}{}{}", before, text, after)
+}
+
+pub trait Humanize: Copy + Display + LowerExp {
+  fn to_f64(self) -> f64;
+
+  // Whether the value has no fractional part (always true for integers)
+  fn is_whole(self) -> bool;
+
+  // `1234567.5` -> `1,234,567.5` with `Locale::EN`
+  fn separated(self, locale: Locale) -> String {
+    let text = self.to_string();
+    // Infinity and NaN have no digits to group
+    if !text.starts_with(|c: char| c == '-' || c.is_ascii_digit()) {
+      return text;
+    }
+
+    let (sign, unsigned) = match text.strip_prefix('-') {
+      Some(rest) => ("-", rest),
+      None => ("", text.as_str()),
+    };
+    match unsigned.find('.') {
+      Some(dot) => format!(
+        "{}{}{}{}",
+        sign,
+        group(&unsigned[..dot], locale.thousands),
+        locale.decimal,
+        &unsigned[dot + 1..]
+      ),
+      None => format!("{}{}", sign, group(unsigned, locale.thousands)),
+    }
+  }
+
+  // `2048` -> `2.0 KiB` with `ByteUnits::Iec`
+  fn bytes(self, units: ByteUnits) -> String {
+    let (base, names) = match units {
+      ByteUnits::Si => (1000.0, SI_UNITS),
+      ByteUnits::Iec => (1024.0, IEC_UNITS),
+    };
+
+    let value = self.to_f64();
+    if !value.is_finite() {
+      return format!("{} B", value);
+    }
+
+    let mut size = value.abs();
+    let mut unit = 0;
+    while size >= base && unit < names.len() - 1 {
+      size /= base;
+      unit += 1;
+    }
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    if unit == 0 {
+      // Whole bytes don't need a decimal point
+      if self.is_whole() {
+        format!("{}{} B", sign, size)
+      } else {
+        format!("{}{:.1} B", sign, size)
+      }
+    } else {
+      format!("{}{:.1} {}", sign, size, names[unit])
+    }
+  }
+
+  // `12345` -> `1.23e4` with a precision of 2
+  fn scientific(self, precision: usize) -> String {
+    format!("{:.*e}", precision, self)
+  }
+
+  // Like `scientific`, but the exponent is always a multiple of three:
+  // `12345` -> `12.35e3` with a precision of 2
+  fn engineering(self, precision: usize) -> String {
+    let value = self.to_f64();
+    if !value.is_finite() {
+      return value.to_string();
+    }
+    if value == 0.0 {
+      return format!("{:.*}e0", precision, 0.0);
+    }
+
+    // `{:e}` gives the exact decimal exponent, even for `u128`
+    let (mantissa, exponent) = split_exp(&format!("{:e}", self));
+    let shift = exponent.rem_euclid(3);
+    let mut exponent = exponent - shift;
+    let mut mantissa = mantissa * 10f64.powi(shift);
+
+    // Rounding may carry over to the next group: 999.96 -> 1000.0
+    let rounded: f64 = format!("{:.*}", precision, mantissa)
+      .parse()
+      .unwrap_or(mantissa);
+    if rounded.abs() >= 1000.0 {
+      mantissa /= 1000.0;
+      exponent += 3;
+    }
+    format!("{:.*}e{}", precision, mantissa, exponent)
+  }
+
+  // `1` -> `1st`, `12` -> `12th`, `23` -> `23rd`. Only whole numbers have an
+  // ordinal.
+  fn ordinal(self) -> Option<String> {
+    if !self.is_whole() {
+      return None;
+    }
+
+    let text = self.to_string();
+    let digits = text.trim_start_matches('-').as_bytes();
+    let last = digits[digits.len() - 1] - b'0';
+    let tens = if digits.len() > 1 {
+      digits[digits.len() - 2] - b'0'
+    } else {
+      0
+    };
+
+    let suffix = match (tens, last) {
+      (1, _) => "th",
+      (_, 1) => "st",
+      (_, 2) => "nd",
+      (_, 3) => "rd",
+      _ => "th",
+    };
+    Some(format!("{}{}", text, suffix))
+  }
+}
+
+macro_rules! humanize_int {
+  ($($t:ty)*) => ($(
+    impl Humanize for $t {
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
+      fn is_whole(self) -> bool {
+        true
+      }
+    }
+  )*)
+}
+
+macro_rules! humanize_float {
+  ($($t:ty)*) => ($(
+    impl Humanize for $t {
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
+      fn is_whole(self) -> bool {
+        self.is_finite() && self.fract() == 0.0
+      }
+    }
+  )*)
+}
+
+humanize_int! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+humanize_float! { f32 f64 }
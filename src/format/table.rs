@@ -0,0 +1,205 @@
+/*
+ * Tables for collections of records.
+ *
+ * Printing records one line at a time, like `formatting()` does for cities
+ * and colors, doesn't line the columns up. A `Table` collects all the rows
+ * first and then works out the width of every column, measured with
+ * `width::str_width` so that `°` or wide characters don't break the layout.
+ *
+ * Any type can become a row by implementing `Record`. The same table can be
+ * rendered with box-drawing characters, as Markdown or as CSV.
+ */
+
+use super::csv;
+use super::width::str_width;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Align {
+  Left,
+  Right,
+  Center,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Style {
+  // Box-drawing characters: `┌─┬─┐`
+  Plain,
+  // A GitHub-flavored Markdown table
+  Markdown,
+  // Comma separated values, without any padding
+  Csv,
+}
+
+#[derive(Clone, Debug)]
+pub struct Column {
+  pub header: String,
+  pub align: Align,
+}
+
+impl Column {
+  pub fn new(header: &str, align: Align) -> Column {
+    Column {
+      header: header.to_owned(),
+      align,
+    }
+  }
+}
+
+// A type that can be shown as a row of a `Table`
+pub trait Record {
+  // The header and alignment of every column
+  fn columns() -> Vec<Column>;
+
+  // The cells of this record, in the same order as `columns`
+  fn cells(&self) -> Vec<String>;
+}
+
+pub struct Table {
+  columns: Vec<Column>,
+  rows: Vec<Vec<String>>,
+}
+
+// Pad `text` with spaces up to `width` columns
+pub fn pad(text: &str, width: usize, align: Align) -> String {
+  let fill = width.saturating_sub(str_width(text));
+  let (left, right) = match align {
+    Align::Left => (0, fill),
+    Align::Right => (fill, 0),
+    Align::Center => (fill / 2, fill - fill / 2),
+  };
+  format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+impl Table {
+  pub fn new(columns: Vec<Column>) -> Table {
+    Table {
+      columns,
+      rows: Vec::new(),
+    }
+  }
+
+  pub fn from_records<R: Record>(records: &[R]) -> Table {
+    let mut table = Table::new(R::columns());
+    for record in records {
+      table.push(record.cells());
+    }
+    table
+  }
+
+  // Add a row. Missing cells are left empty and extra cells are dropped.
+  pub fn push(&mut self, mut cells: Vec<String>) {
+    cells.resize(self.columns.len(), String::new());
+    self.rows.push(cells);
+  }
+
+  // Change the alignment of the column at `index`
+  pub fn align(mut self, index: usize, align: Align) -> Table {
+    if let Some(column) = self.columns.get_mut(index) {
+      column.align = align;
+    }
+    self
+  }
+
+  pub fn len(&self) -> usize {
+    self.rows.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.rows.is_empty()
+  }
+
+  // The widest cell of every column, header included
+  fn widths(&self) -> Vec<usize> {
+    self
+      .columns
+      .iter()
+      .enumerate()
+      .map(|(i, column)| {
+        self
+          .rows
+          .iter()
+          .map(|row| str_width(&row[i]))
+          .fold(str_width(&column.header), usize::max)
+      })
+      .collect()
+  }
+
+  fn headers(&self) -> Vec<String> {
+    self.columns.iter().map(|c| c.header.clone()).collect()
+  }
+
+  pub fn render(&self, style: Style) -> String {
+    match style {
+      Style::Plain => self.render_plain(),
+      Style::Markdown => self.render_markdown(),
+      Style::Csv => self.render_csv(),
+    }
+  }
+
+  fn padded(&self, row: &[String], widths: &[usize]) -> Vec<String> {
+    row
+      .iter()
+      .zip(widths)
+      .zip(&self.columns)
+      .map(|((cell, &width), column)| pad(cell, width, column.align))
+      .collect()
+  }
+
+  fn render_plain(&self) -> String {
+    let widths = self.widths();
+    let rule = |left: &str, middle: &str, right: &str| {
+      let segments: Vec<String> = widths.iter().map(|w| "─".repeat(w + 2)).collect();
+      format!("{}{}{}\n", left, segments.join(middle), right)
+    };
+    let line = |row: &[String]| format!("│ {} │\n", self.padded(row, &widths).join(" │ "));
+
+    let mut out = rule("┌", "┬", "┐");
+    out.push_str(&line(&self.headers()));
+    out.push_str(&rule("├", "┼", "┤"));
+    for row in &self.rows {
+      out.push_str(&line(row));
+    }
+    out.push_str(&rule("└", "┴", "┘"));
+    out
+  }
+
+  fn render_markdown(&self) -> String {
+    // Markdown needs at least three dashes in the delimiter row
+    let widths: Vec<usize> = self.widths().into_iter().map(|w| w.max(3)).collect();
+    let line = |row: &[String]| format!("| {} |\n", self.padded(row, &widths).join(" | "));
+    let delimiters: Vec<String> = self
+      .columns
+      .iter()
+      .zip(&widths)
+      .map(|(column, &width)| match column.align {
+        Align::Left => format!(":{}", "-".repeat(width - 1)),
+        Align::Right => format!("{}:", "-".repeat(width - 1)),
+        Align::Center => format!(":{}:", "-".repeat(width - 2)),
+      })
+      .collect();
+
+    let mut out = line(&self.headers());
+    out.push_str(&format!("| {} |\n", delimiters.join(" | ")));
+    for row in &self.rows {
+      out.push_str(&line(row));
+    }
+    out
+  }
+
+  fn render_csv(&self) -> String {
+    let line = |row: &[String]| format!("{}\n", csv::record(row));
+
+    let mut out = line(&self.headers());
+    for row in &self.rows {
+      out.push_str(&line(row));
+    }
+    out
+  }
+}
+
+impl Display for Table {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.render(Style::Plain).trim_end_matches('\n'))
+  }
+}
@@ -0,0 +1,108 @@
+/*
+ * Display width of text in a terminal.
+ *
+ * `str::len` counts bytes, so `°` (2 bytes in UTF-8) would count twice, and
+ * `chars().count()` treats a CJK ideograph or an emoji as a single column
+ * although terminals draw them two columns wide. Combining marks and
+ * zero-width characters take no column at all.
+ *
+ * The ranges below are a compact subset of the Unicode East Asian Width and
+ * general category tables, enough for aligning the output of this crate.
+ */
+
+// Characters that are drawn on top of the previous one, or not drawn at all
+const ZERO_WIDTH: &[(u32, u32)] = &[
+  (0x0300, 0x036F),   // Combining Diacritical Marks
+  (0x0483, 0x0489),   // Combining Cyrillic
+  (0x0591, 0x05BD),   // Hebrew points
+  (0x0610, 0x061A),   // Arabic marks
+  (0x064B, 0x065F),   // Arabic harakat
+  (0x1AB0, 0x1AFF),   // Combining Diacritical Marks Extended
+  (0x1DC0, 0x1DFF),   // Combining Diacritical Marks Supplement
+  (0x200B, 0x200F),   // Zero width space, joiners and direction marks
+  (0x2028, 0x202E),   // Separators and embedding controls
+  (0x2060, 0x2064),   // Word joiner and invisible operators
+  (0x20D0, 0x20FF),   // Combining Diacritical Marks for Symbols
+  (0xFE00, 0xFE0F),   // Variation Selectors
+  (0xFE20, 0xFE2F),   // Combining Half Marks
+  (0xFEFF, 0xFEFF),   // Zero width no-break space
+  (0xE0100, 0xE01EF), // Variation Selectors Supplement
+];
+
+// Characters that take two columns
+const WIDE: &[(u32, u32)] = &[
+  (0x1100, 0x115F),   // Hangul Jamo initials
+  (0x231A, 0x231B),   // Watch, hourglass
+  (0x2329, 0x232A),   // Angle brackets
+  (0x23E9, 0x23EC),   // Media controls
+  (0x25FD, 0x25FE),   // Medium small squares
+  (0x2614, 0x2615),   // Umbrella, hot beverage
+  (0x2648, 0x2653),   // Zodiac
+  (0x26A1, 0x26A1),   // High voltage
+  (0x26BD, 0x26BE),   // Soccer ball, baseball
+  (0x26D4, 0x26D4),   // No entry
+  (0x2705, 0x2705),   // Check mark button
+  (0x270A, 0x270B),   // Raised fist, raised hand
+  (0x2728, 0x2728),   // Sparkles
+  (0x274C, 0x274C),   // Cross mark
+  (0x2753, 0x2755),   // Question marks
+  (0x2795, 0x2797),   // Heavy plus, minus, division
+  (0x2B1B, 0x2B1C),   // Large squares
+  (0x2B50, 0x2B50),   // Star
+  (0x2E80, 0x303E),   // CJK Radicals .. CJK Symbols and Punctuation
+  (0x3041, 0x33FF),   // Hiragana .. CJK Compatibility
+  (0x3400, 0x4DBF),   // CJK Unified Ideographs Extension A
+  (0x4E00, 0x9FFF),   // CJK Unified Ideographs
+  (0xA000, 0xA4CF),   // Yi
+  (0xA960, 0xA97F),   // Hangul Jamo Extended-A
+  (0xAC00, 0xD7A3),   // Hangul Syllables
+  (0xF900, 0xFAFF),   // CJK Compatibility Ideographs
+  (0xFE10, 0xFE19),   // Vertical forms
+  (0xFE30, 0xFE6F),   // CJK Compatibility Forms, Small Form Variants
+  (0xFF00, 0xFF60),   // Fullwidth Forms
+  (0xFFE0, 0xFFE6),   // Fullwidth signs
+  (0x1F004, 0x1F004), // Mahjong tile
+  (0x1F0CF, 0x1F0CF), // Playing card
+  (0x1F18E, 0x1F18E), // AB button
+  (0x1F191, 0x1F19A), // Squared letters
+  (0x1F200, 0x1F2FF), // Enclosed Ideographic Supplement
+  (0x1F300, 0x1F64F), // Miscellaneous Symbols and Pictographs, Emoticons
+  (0x1F680, 0x1F6FF), // Transport and Map Symbols
+  (0x1F7E0, 0x1F7EB), // Colored circles and squares
+  (0x1F90C, 0x1F9FF), // Supplemental Symbols and Pictographs
+  (0x1FA70, 0x1FAFF), // Symbols and Pictographs Extended-A
+  (0x20000, 0x2FFFD), // CJK Unified Ideographs Extension B ..
+  (0x30000, 0x3FFFD), // CJK Unified Ideographs Extension G ..
+];
+
+fn in_ranges(ranges: &[(u32, u32)], c: char) -> bool {
+  let c = c as u32;
+  // The tables are sorted, so a binary search finds the candidate range
+  ranges
+    .binary_search_by(|&(start, end)| {
+      if end < c {
+        std::cmp::Ordering::Less
+      } else if start > c {
+        std::cmp::Ordering::Greater
+      } else {
+        std::cmp::Ordering::Equal
+      }
+    })
+    .is_ok()
+}
+
+// Number of terminal columns `c` takes: 0, 1 or 2
+pub fn char_width(c: char) -> usize {
+  if c.is_control() || in_ranges(ZERO_WIDTH, c) {
+    0
+  } else if in_ranges(WIDE, c) {
+    2
+  } else {
+    1
+  }
+}
+
+// Number of terminal columns `s` takes
+pub fn str_width(s: &str) -> usize {
+  s.chars().map(char_width).sum()
+}
@@ -0,0 +1,261 @@
+/*
+ * Drawings in the terminal.
+ *
+ * A `Canvas` is at most `width` by `height` characters. Everything added to
+ * it is scaled to fit, keeping its proportions: a character is about twice
+ * as tall as it is wide, so one unit of `y` takes half as many rows as one
+ * unit of `x` takes columns.
+ *
+ * Rectangle borders are drawn with box characters, joined where they meet.
+ * The inside of a rectangle is shaded by how many rectangles cover it:
+ *
+ *    ░  one       ▒  two       ▓  three or more
+ *
+ * Points are drawn last, on top of everything. The drawing only depends on
+ * what was added, so the same shapes always give the same characters.
+ */
+
+use crate::chapters::c03::{Point, Rectangle};
+use std::fmt::{self, Display, Formatter};
+
+// The directions a border leaves a cell in
+const UP: u8 = 1;
+const DOWN: u8 = 2;
+const LEFT: u8 = 4;
+const RIGHT: u8 = 8;
+
+// How much taller than wide a character is
+const CELL_ASPECT: f32 = 2.0;
+
+const SHADES: [char; 3] = ['░', '▒', '▓'];
+
+fn border(directions: u8) -> char {
+  match directions {
+    0 => '□',
+    d if d == LEFT || d == RIGHT || d == LEFT | RIGHT => '─',
+    d if d == UP || d == DOWN || d == UP | DOWN => '│',
+    d if d == DOWN | RIGHT => '┌',
+    d if d == DOWN | LEFT => '┐',
+    d if d == UP | RIGHT => '└',
+    d if d == UP | LEFT => '┘',
+    d if d == UP | DOWN | RIGHT => '├',
+    d if d == UP | DOWN | LEFT => '┤',
+    d if d == DOWN | LEFT | RIGHT => '┬',
+    d if d == UP | LEFT | RIGHT => '┴',
+    _ => '┼',
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Canvas {
+  width: usize,
+  height: usize,
+  rectangles: Vec<Rectangle>,
+  points: Vec<(Point, char)>,
+}
+
+// What ends up in one cell
+#[derive(Clone, Copy, Default)]
+struct Cell {
+  // `None` when no border goes through it
+  border: Option<u8>,
+  covered: usize,
+  mark: Option<char>,
+}
+
+impl Cell {
+  fn symbol(&self) -> char {
+    match (self.mark, self.border, self.covered) {
+      (Some(mark), _, _) => mark,
+      (None, Some(directions), _) => border(directions),
+      (None, None, 0) => ' ',
+      (None, None, covered) => SHADES[covered.min(SHADES.len()) - 1],
+    }
+  }
+}
+
+impl Canvas {
+  // A canvas of at most `width` columns and `height` rows
+  pub fn new(width: usize, height: usize) -> Canvas {
+    Canvas {
+      width: width.max(1),
+      height: height.max(1),
+      rectangles: Vec::new(),
+      points: Vec::new(),
+    }
+  }
+
+  pub fn rectangle(mut self, rectangle: Rectangle) -> Canvas {
+    self.rectangles.push(rectangle.normalize());
+    self
+  }
+
+  // `mark` is the character the point is drawn with
+  pub fn point(mut self, point: Point, mark: char) -> Canvas {
+    self.points.push((point, mark));
+    self
+  }
+
+  // Everything that was added
+  fn bounds(&self) -> Option<Rectangle> {
+    let points = self
+      .points
+      .iter()
+      .map(|(p, _)| Rectangle::from_corners(*p, *p));
+    self
+      .rectangles
+      .iter()
+      .copied()
+      .chain(points)
+      .reduce(|all, bounds| all.union(&bounds))
+  }
+
+  // The rows of characters, without trailing spaces
+  pub fn lines(&self) -> Vec<String> {
+    let bounds = match self.bounds() {
+      Some(bounds) => bounds,
+      None => return Vec::new(),
+    };
+    // Columns per unit, as many as both directions allow. A flat drawing
+    // only has one direction to fit.
+    let by_width = (self.width - 1) as f32 / bounds.width();
+    let by_height = (self.height - 1) as f32 * CELL_ASPECT / bounds.height();
+    let scale = match (bounds.width() > 0.0, bounds.height() > 0.0) {
+      (true, true) => by_width.min(by_height),
+      (true, false) => by_width,
+      (false, true) => by_height,
+      (false, false) => 0.0,
+    };
+    let column = |x: f32| ((x - bounds.left()) * scale).round() as usize;
+    let row = |y: f32| ((bounds.top() - y) * scale / CELL_ASPECT).round() as usize;
+    let (columns, rows) = (column(bounds.right()) + 1, row(bounds.bottom()) + 1);
+
+    let mut cells = vec![vec![Cell::default(); columns]; rows];
+    for rectangle in &self.rectangles {
+      let (left, right) = (column(rectangle.left()), column(rectangle.right()));
+      let (top, bottom) = (row(rectangle.top()), row(rectangle.bottom()));
+      for (y, row) in cells.iter_mut().enumerate().take(bottom + 1).skip(top) {
+        for (x, cell) in row.iter_mut().enumerate().take(right + 1).skip(left) {
+          let on_edge = y == top || y == bottom || x == left || x == right;
+          if !on_edge {
+            cell.covered += 1;
+            continue;
+          }
+          // A border continues towards the neighbours on the same edge
+          let mut directions = 0;
+          if (x == left || x == right) && y > top {
+            directions |= UP;
+          }
+          if (x == left || x == right) && y < bottom {
+            directions |= DOWN;
+          }
+          if (y == top || y == bottom) && x > left {
+            directions |= LEFT;
+          }
+          if (y == top || y == bottom) && x < right {
+            directions |= RIGHT;
+          }
+          cell.border = Some(cell.border.unwrap_or(0) | directions);
+        }
+      }
+    }
+    for (point, mark) in &self.points {
+      cells[row(point.y)][column(point.x)].mark = Some(*mark);
+    }
+
+    cells
+      .iter()
+      .map(|row| {
+        let line: String = row.iter().map(Cell::symbol).collect();
+        line.trim_end().to_owned()
+      })
+      .collect()
+  }
+}
+
+impl Display for Canvas {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.lines().join("\n"))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(left: f32, bottom: f32, size: f32) -> Rectangle {
+    Rectangle::from_corners(
+      Point::new(left, bottom),
+      Point::new(left + size, bottom + size),
+    )
+  }
+
+  #[test]
+  fn overlapping_squares() {
+    let canvas = Canvas::new(12, 6)
+      .rectangle(square(0.0, 0.0, 8.0))
+      .rectangle(Rectangle::from_corners(
+        Point::new(3.0, 4.0),
+        Point::new(11.0, -2.0),
+      ));
+    let snapshot = "\
+┌───────┐
+│░░░░░░░│
+│░░┌────┼──┐
+│░░│▒▒▒▒│░░│
+└──┼────┘░░│
+   └───────┘";
+    assert_eq!(canvas.to_string(), snapshot);
+  }
+
+  #[test]
+  fn three_overlaps_are_darkest() {
+    let canvas = Canvas::new(13, 7)
+      .rectangle(square(0.0, 0.0, 12.0))
+      .rectangle(square(2.0, 2.0, 8.0))
+      .rectangle(square(4.0, 4.0, 4.0));
+    let snapshot = "\
+┌───────────┐
+│░┌───────┐░│
+│░│▒┌───┐▒│░│
+│░│▒│▓▓▓│▒│░│
+│░│▒└───┘▒│░│
+│░└───────┘░│
+└───────────┘";
+    assert_eq!(canvas.to_string(), snapshot);
+  }
+
+  #[test]
+  fn lone_point() {
+    let canvas = Canvas::new(10, 5).point(Point::new(3.0, -7.0), '●');
+    assert_eq!(canvas.lines(), ["●"]);
+  }
+
+  #[test]
+  fn points_are_drawn_on_top() {
+    // Three rows leave room for five columns
+    let canvas = Canvas::new(9, 3)
+      .rectangle(square(0.0, 0.0, 8.0))
+      .point(Point::new(0.0, 8.0), '×')
+      .point(Point::new(4.0, 4.0), '+');
+    assert_eq!(canvas.to_string(), "×───┐\n│░+░│\n└───┘");
+  }
+
+  #[test]
+  fn zero_size_rectangles() {
+    let dot = Canvas::new(10, 5).rectangle(square(2.0, 2.0, 0.0));
+    assert_eq!(dot.lines(), ["□"]);
+
+    let flat = Canvas::new(7, 5).rectangle(Rectangle::from_corners(
+      Point::new(0.0, 1.0),
+      Point::new(6.0, 1.0),
+    ));
+    assert_eq!(flat.lines(), ["───────"]);
+  }
+
+  #[test]
+  fn empty_canvas() {
+    assert!(Canvas::new(10, 5).lines().is_empty());
+    assert_eq!(Canvas::new(0, 0).to_string(), "");
+  }
+}
@@ -0,0 +1,5 @@
+pub mod canvas;
+pub mod point;
+pub mod rectangle;
+pub mod shape;
+pub mod svg;
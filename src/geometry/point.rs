@@ -0,0 +1,85 @@
+/*
+ * Points of the plane.
+ *
+ * `Point` is defined in the `structures` chapter with two `f32` fields. The
+ * geometry modules use the mathematical convention: `x` grows to the right
+ * and `y` grows upwards, so the top of a shape has the largest `y`.
+ */
+
+use crate::chapters::c03::Point;
+use crate::format::json::{self, DecodeError, FromJson, ToJson, Value};
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Sub};
+
+impl Point {
+  pub fn new(x: f32, y: f32) -> Point {
+    Point { x, y }
+  }
+
+  pub fn origin() -> Point {
+    Point::new(0.0, 0.0)
+  }
+
+  pub fn distance(&self, other: &Point) -> f32 {
+    (self.x - other.x).hypot(self.y - other.y)
+  }
+
+  pub fn translate(&self, dx: f32, dy: f32) -> Point {
+    Point::new(self.x + dx, self.y + dy)
+  }
+
+  // Move away from `center`, `factor` times as far as it was
+  pub fn scale(&self, factor: f32, center: &Point) -> Point {
+    Point::new(
+      center.x + (self.x - center.x) * factor,
+      center.y + (self.y - center.y) * factor,
+    )
+  }
+}
+
+// Points add up like vectors
+impl Add for Point {
+  type Output = Point;
+
+  fn add(self, other: Point) -> Point {
+    Point::new(self.x + other.x, self.y + other.y)
+  }
+}
+
+impl Sub for Point {
+  type Output = Point;
+
+  fn sub(self, other: Point) -> Point {
+    Point::new(self.x - other.x, self.y - other.y)
+  }
+}
+
+// `(x, y)`, the precision applies to both coordinates
+impl Display for Point {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match f.precision() {
+      Some(precision) => write!(f, "({:.*}, {:.*})", precision, self.x, precision, self.y),
+      None => write!(f, "({}, {})", self.x, self.y),
+    }
+  }
+}
+
+// `{"x": 1.5, "y": -2}`
+impl ToJson for Point {
+  fn to_json(&self) -> Value {
+    Value::Object(vec![
+      ("x".to_owned(), self.x.to_json()),
+      ("y".to_owned(), self.y.to_json()),
+    ])
+  }
+}
+
+impl FromJson for Point {
+  fn from_json(value: &Value) -> Result<Point, DecodeError> {
+    json::object(value, &["x", "y"])?;
+    Ok(Point::new(
+      json::field(value, "x")?,
+      json::field(value, "y")?,
+    ))
+  }
+}
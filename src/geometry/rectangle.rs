@@ -0,0 +1,261 @@
+/*
+ * Axis-aligned rectangles.
+ *
+ * A `Rectangle` from the `structures` chapter is two corners, and nothing
+ * stops them from being swapped: `cal_area` used to return a negative area
+ * for those. `from_corners` takes any two opposite corners and normalizes
+ * them, so that `top_left` is left of and above `bottom_right` (`y` grows
+ * upwards). The methods below also read the edges with `min` and `max`, so
+ * they give the right answer on a rectangle built by hand the wrong way
+ * around:
+ *
+ *  - `width`, `height`, `area` and `perimeter` are never negative
+ *  - `contains` includes the edges
+ *  - `intersection` is `None` when the rectangles don't touch, and
+ *    `union` is the smallest rectangle around both
+ */
+
+use crate::chapters::c03::{Point, Rectangle};
+use crate::format::json::{self, DecodeError, FromJson, ToJson, Value};
+
+impl Rectangle {
+  // The rectangle with `a` and `b` as opposite corners, in any order
+  pub fn from_corners(a: Point, b: Point) -> Rectangle {
+    Rectangle {
+      top_left: Point::new(a.x.min(b.x), a.y.max(b.y)),
+      bottom_right: Point::new(a.x.max(b.x), a.y.min(b.y)),
+    }
+  }
+
+  // The same rectangle, with its corners where they belong
+  pub fn normalize(&self) -> Rectangle {
+    Rectangle::from_corners(self.top_left, self.bottom_right)
+  }
+
+  pub fn is_normalized(&self) -> bool {
+    self.top_left.x <= self.bottom_right.x && self.top_left.y >= self.bottom_right.y
+  }
+
+  pub fn left(&self) -> f32 {
+    self.top_left.x.min(self.bottom_right.x)
+  }
+
+  pub fn right(&self) -> f32 {
+    self.top_left.x.max(self.bottom_right.x)
+  }
+
+  pub fn top(&self) -> f32 {
+    self.top_left.y.max(self.bottom_right.y)
+  }
+
+  pub fn bottom(&self) -> f32 {
+    self.top_left.y.min(self.bottom_right.y)
+  }
+
+  pub fn width(&self) -> f32 {
+    self.right() - self.left()
+  }
+
+  pub fn height(&self) -> f32 {
+    self.top() - self.bottom()
+  }
+
+  pub fn area(&self) -> f32 {
+    self.width() * self.height()
+  }
+
+  pub fn perimeter(&self) -> f32 {
+    2.0 * (self.width() + self.height())
+  }
+
+  pub fn center(&self) -> Point {
+    Point::new(
+      (self.left() + self.right()) / 2.0,
+      (self.bottom() + self.top()) / 2.0,
+    )
+  }
+
+  pub fn contains(&self, point: &Point) -> bool {
+    (self.left()..=self.right()).contains(&point.x)
+      && (self.bottom()..=self.top()).contains(&point.y)
+  }
+
+  // The part both rectangles cover. Rectangles that only share an edge or a
+  // corner intersect in a rectangle with no area.
+  pub fn intersection(&self, other: &Rectangle) -> Option<Rectangle> {
+    let (left, right) = (
+      self.left().max(other.left()),
+      self.right().min(other.right()),
+    );
+    let (bottom, top) = (
+      self.bottom().max(other.bottom()),
+      self.top().min(other.top()),
+    );
+    if left > right || bottom > top {
+      return None;
+    }
+    Some(Rectangle::from_corners(
+      Point::new(left, top),
+      Point::new(right, bottom),
+    ))
+  }
+
+  // The smallest rectangle that contains both
+  pub fn union(&self, other: &Rectangle) -> Rectangle {
+    Rectangle::from_corners(
+      Point::new(self.left().min(other.left()), self.top().max(other.top())),
+      Point::new(
+        self.right().max(other.right()),
+        self.bottom().min(other.bottom()),
+      ),
+    )
+  }
+
+  pub fn translate(&self, dx: f32, dy: f32) -> Rectangle {
+    Rectangle::from_corners(
+      self.top_left.translate(dx, dy),
+      self.bottom_right.translate(dx, dy),
+    )
+  }
+
+  // Grow or shrink around the center. A negative factor also turns the
+  // rectangle around, which normalizing undoes.
+  pub fn scale(&self, factor: f32) -> Rectangle {
+    let center = self.center();
+    Rectangle::from_corners(
+      self.top_left.scale(factor, &center),
+      self.bottom_right.scale(factor, &center),
+    )
+  }
+}
+
+// The corners as they are, normalized or not
+impl ToJson for Rectangle {
+  fn to_json(&self) -> Value {
+    Value::Object(vec![
+      ("top_left".to_owned(), self.top_left.to_json()),
+      ("bottom_right".to_owned(), self.bottom_right.to_json()),
+    ])
+  }
+}
+
+impl FromJson for Rectangle {
+  fn from_json(value: &Value) -> Result<Rectangle, DecodeError> {
+    json::object(value, &["top_left", "bottom_right"])?;
+    Ok(Rectangle {
+      top_left: json::field(value, "top_left")?,
+      bottom_right: json::field(value, "bottom_right")?,
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::random::XorShift;
+
+  fn random_point(rng: &mut XorShift) -> Point {
+    Point::new(
+      (rng.next_f64() * 200.0 - 100.0) as f32,
+      (rng.next_f64() * 200.0 - 100.0) as f32,
+    )
+  }
+
+  // Corners in any order, half of them left as they are
+  fn random_rectangles(seed: u64) -> Vec<Rectangle> {
+    let mut rng = XorShift::new(seed);
+    (0..1000)
+      .map(|_| Rectangle {
+        top_left: random_point(&mut rng),
+        bottom_right: random_point(&mut rng),
+      })
+      .collect()
+  }
+
+  fn close(a: f32, b: f32) -> bool {
+    (a - b).abs() <= 1e-3 * a.abs().max(b.abs()).max(1.0)
+  }
+
+  #[test]
+  fn sizes_are_never_negative() {
+    for r in random_rectangles(44) {
+      assert!(r.width() >= 0.0 && r.height() >= 0.0, "{:?}", r);
+      assert!(r.area() >= 0.0, "{:?}", r);
+      assert!(r.perimeter() >= 0.0, "{:?}", r);
+      assert!(close(r.scale(-2.0).area(), 4.0 * r.area()), "{:?}", r);
+      assert!(close(r.translate(3.5, -2.0).area(), r.area()), "{:?}", r);
+    }
+  }
+
+  #[test]
+  fn normalize_keeps_the_rectangle() {
+    for r in random_rectangles(45) {
+      let normalized = r.normalize();
+      assert!(normalized.is_normalized());
+      assert_eq!(normalized.area(), r.area());
+      assert_eq!(normalized.normalize(), normalized);
+      assert_eq!(
+        Rectangle::from_corners(r.bottom_right, r.top_left),
+        normalized
+      );
+    }
+  }
+
+  #[test]
+  fn intersection_and_union_contain_what_they_should() {
+    let rectangles = random_rectangles(46);
+    for pair in rectangles.chunks(2) {
+      let (a, b) = (&pair[0], &pair[1]);
+      let union = a.union(b);
+      assert!(union.is_normalized());
+      assert!(union.area() >= a.area().max(b.area()));
+      for corner in &[a.top_left, a.bottom_right, b.top_left, b.bottom_right] {
+        assert!(union.contains(corner), "{:?} in {:?}", corner, union);
+      }
+      assert_eq!(a.intersection(b), b.intersection(a));
+      match a.intersection(b) {
+        Some(overlap) => {
+          assert!(overlap.is_normalized());
+          assert!(overlap.area() <= a.area().min(b.area()));
+          assert!(a.contains(&overlap.center()) && b.contains(&overlap.center()));
+        }
+        // Then no corner of one is inside the other
+        None => {
+          assert!(!a.contains(&b.top_left) && !a.contains(&b.bottom_right));
+          assert!(!b.contains(&a.top_left) && !b.contains(&a.bottom_right));
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn edges_touch_but_do_not_overlap() {
+    let a = Rectangle::from_corners(Point::new(0.0, 0.0), Point::new(2.0, 2.0));
+    let b = Rectangle::from_corners(Point::new(2.0, 1.0), Point::new(4.0, 3.0));
+    let edge = a.intersection(&b).unwrap();
+    assert_eq!(edge.area(), 0.0);
+    assert_eq!((edge.left(), edge.right()), (2.0, 2.0));
+    assert_eq!((edge.bottom(), edge.top()), (1.0, 2.0));
+    let far = b.translate(0.5, 0.0);
+    assert_eq!(a.intersection(&far), None);
+  }
+
+  #[test]
+  fn contains_includes_the_edges() {
+    // Built the wrong way around on purpose
+    let r = Rectangle {
+      top_left: Point::new(4.0, 0.0),
+      bottom_right: Point::new(0.0, 2.0),
+    };
+    for &(x, y) in &[(0.0, 0.0), (4.0, 2.0), (2.0, 0.0), (0.0, 1.0), (2.0, 1.0)] {
+      assert!(r.contains(&Point::new(x, y)), "({}, {})", x, y);
+    }
+    for &(x, y) in &[(-0.1, 1.0), (4.1, 1.0), (2.0, -0.1), (2.0, 2.1)] {
+      assert!(!r.contains(&Point::new(x, y)), "({}, {})", x, y);
+    }
+    for r in random_rectangles(47) {
+      assert!(r.contains(&r.center()));
+      assert!(r.contains(&r.top_left) && r.contains(&r.bottom_right));
+    }
+  }
+}
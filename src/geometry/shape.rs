@@ -0,0 +1,523 @@
+/*
+ * Shapes of the plane.
+ *
+ * `Shape` is what every closed figure can answer: its area, perimeter,
+ * bounding box, centroid, and whether a point is inside. The trait is
+ * object safe, so different shapes can share a `Vec<Box<dyn Shape>>`.
+ *
+ * A `Polygon` is any simple polygon, convex or not, given by its vertices in
+ * order. Its area comes from the shoelace formula, which sums the cross
+ * products of consecutive vertices, and `contains` casts a ray to the right
+ * of the point and counts the edges it crosses: an odd count means inside.
+ */
+
+use crate::chapters::c03::{Point, Rectangle};
+use crate::format::table::{Align, Column, Table};
+use std::f32::consts::PI;
+use std::fmt::{self, Display, Formatter};
+
+pub trait Shape {
+  fn name(&self) -> &'static str;
+
+  fn area(&self) -> f32;
+
+  fn perimeter(&self) -> f32;
+
+  // The smallest rectangle around the shape
+  fn bounding_box(&self) -> Rectangle;
+
+  // The center of mass of the surface
+  fn centroid(&self) -> Point;
+
+  // Points on the edges are inside
+  fn contains(&self, point: &Point) -> bool;
+}
+
+impl Shape for Rectangle {
+  fn name(&self) -> &'static str {
+    "rectangle"
+  }
+
+  fn area(&self) -> f32 {
+    Rectangle::area(self)
+  }
+
+  fn perimeter(&self) -> f32 {
+    Rectangle::perimeter(self)
+  }
+
+  fn bounding_box(&self) -> Rectangle {
+    self.normalize()
+  }
+
+  fn centroid(&self) -> Point {
+    self.center()
+  }
+
+  fn contains(&self, point: &Point) -> bool {
+    Rectangle::contains(self, point)
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Circle {
+  pub center: Point,
+  pub radius: f32,
+}
+
+impl Circle {
+  pub fn new(center: Point, radius: f32) -> Circle {
+    Circle {
+      center,
+      radius: radius.abs(),
+    }
+  }
+}
+
+impl Shape for Circle {
+  fn name(&self) -> &'static str {
+    "circle"
+  }
+
+  fn area(&self) -> f32 {
+    PI * self.radius * self.radius
+  }
+
+  fn perimeter(&self) -> f32 {
+    2.0 * PI * self.radius
+  }
+
+  fn bounding_box(&self) -> Rectangle {
+    let r = self.radius;
+    Rectangle::from_corners(self.center.translate(-r, r), self.center.translate(r, -r))
+  }
+
+  fn centroid(&self) -> Point {
+    self.center
+  }
+
+  fn contains(&self, point: &Point) -> bool {
+    self.center.distance(point) <= self.radius
+  }
+}
+
+// The cross product of `b - a` and `c - a`: positive when `a`, `b`, `c` turn
+// counterclockwise, and twice the area of the triangle they make
+fn cross(a: &Point, b: &Point, c: &Point) -> f32 {
+  (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+}
+
+// The smallest rectangle around some points
+fn bounds<'a, I: IntoIterator<Item = &'a Point>>(points: I) -> Rectangle {
+  let mut points = points.into_iter();
+  let first = *points.next().expect("a shape has at least one point");
+  let start = Rectangle::from_corners(first, first);
+  points.fold(start, |bounds, &p| {
+    bounds.union(&Rectangle::from_corners(p, p))
+  })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Triangle {
+  pub vertices: [Point; 3],
+}
+
+impl Triangle {
+  pub fn new(a: Point, b: Point, c: Point) -> Triangle {
+    Triangle {
+      vertices: [a, b, c],
+    }
+  }
+}
+
+impl Shape for Triangle {
+  fn name(&self) -> &'static str {
+    "triangle"
+  }
+
+  fn area(&self) -> f32 {
+    let [a, b, c] = &self.vertices;
+    cross(a, b, c).abs() / 2.0
+  }
+
+  fn perimeter(&self) -> f32 {
+    let [a, b, c] = &self.vertices;
+    a.distance(b) + b.distance(c) + c.distance(a)
+  }
+
+  fn bounding_box(&self) -> Rectangle {
+    bounds(&self.vertices)
+  }
+
+  fn centroid(&self) -> Point {
+    let [a, b, c] = self.vertices;
+    Point::new((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0)
+  }
+
+  // Inside when the point is on the same side of all three edges. When the
+  // vertices are aligned every point of their line is on no side at all, so
+  // the point must be on one of the edges instead.
+  fn contains(&self, point: &Point) -> bool {
+    let [a, b, c] = &self.vertices;
+    if cross(a, b, c) == 0.0 {
+      return on_segment(point, a, b) || on_segment(point, b, c) || on_segment(point, c, a);
+    }
+    let sides = [cross(a, b, point), cross(b, c, point), cross(c, a, point)];
+    sides.iter().all(|&side| side >= 0.0) || sides.iter().all(|&side| side <= 0.0)
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  TooFewVertices(usize),
+  // Two edges that are not neighbours cross, given by their first vertex
+  SelfIntersecting(usize, usize),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::TooFewVertices(count) => {
+        write!(f, "a polygon needs at least 3 vertices, not {}", count)
+      }
+      Error::SelfIntersecting(i, j) => {
+        write!(f, "the polygon is not simple: edges {} and {} cross", i, j)
+      }
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Polygon {
+  vertices: Vec<Point>,
+}
+
+// `p` is within the box of the segment `qr`, which means on it when the
+// three are aligned
+fn within(p: &Point, q: &Point, r: &Point) -> bool {
+  (q.x.min(r.x)..=q.x.max(r.x)).contains(&p.x) && (q.y.min(r.y)..=q.y.max(r.y)).contains(&p.y)
+}
+
+// Whether `p` is on the segment `qr`, up to rounding errors
+fn on_segment(p: &Point, q: &Point, r: &Point) -> bool {
+  let length = q.distance(r);
+  cross(q, r, p).abs() <= 4.0 * f32::EPSILON * length * length.max(1.0) && within(p, q, r)
+}
+
+// Whether the segments `ab` and `cd` have a point in common
+fn segments_cross(a: &Point, b: &Point, c: &Point, d: &Point) -> bool {
+  let (d1, d2) = (cross(c, d, a), cross(c, d, b));
+  let (d3, d4) = (cross(a, b, c), cross(a, b, d));
+  // Each segment has its ends on both sides of the other one
+  let straddle = |x: f32, y: f32| (x > 0.0 && y < 0.0) || (x < 0.0 && y > 0.0);
+  (straddle(d1, d2) && straddle(d3, d4))
+    || (d1 == 0.0 && within(a, c, d))
+    || (d2 == 0.0 && within(b, c, d))
+    || (d3 == 0.0 && within(c, a, b))
+    || (d4 == 0.0 && within(d, a, b))
+}
+
+impl Polygon {
+  // The polygon through `vertices`, in either direction. Checking that no
+  // two edges cross compares every pair, which is fine for a few hundred
+  // vertices.
+  pub fn new(vertices: Vec<Point>) -> Result<Polygon, Error> {
+    let n = vertices.len();
+    if n < 3 {
+      return Err(Error::TooFewVertices(n));
+    }
+    for i in 0..n {
+      // Neighbouring edges share a vertex, skip them
+      for j in i + 2..n {
+        if i == 0 && j == n - 1 {
+          continue;
+        }
+        let (a, b) = (&vertices[i], &vertices[(i + 1) % n]);
+        let (c, d) = (&vertices[j], &vertices[(j + 1) % n]);
+        if segments_cross(a, b, c, d) {
+          return Err(Error::SelfIntersecting(i, j));
+        }
+      }
+    }
+    Ok(Polygon { vertices })
+  }
+
+  pub fn vertices(&self) -> &[Point] {
+    &self.vertices
+  }
+
+  // Every edge, from each vertex to the next one
+  fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+    self
+      .vertices
+      .iter()
+      .zip(self.vertices.iter().cycle().skip(1))
+  }
+
+  // Positive when the vertices go counterclockwise
+  fn signed_area(&self) -> f32 {
+    self
+      .edges()
+      .map(|(p, q)| p.x * q.y - q.x * p.y)
+      .sum::<f32>()
+      / 2.0
+  }
+}
+
+impl Shape for Polygon {
+  fn name(&self) -> &'static str {
+    "polygon"
+  }
+
+  // The shoelace formula
+  fn area(&self) -> f32 {
+    self.signed_area().abs()
+  }
+
+  fn perimeter(&self) -> f32 {
+    self.edges().map(|(p, q)| p.distance(q)).sum()
+  }
+
+  fn bounding_box(&self) -> Rectangle {
+    bounds(&self.vertices)
+  }
+
+  // The triangles between the origin and each edge, weighted by their signed
+  // areas
+  fn centroid(&self) -> Point {
+    let area = self.signed_area();
+    if area == 0.0 {
+      // All the vertices are aligned, the centroid is their average
+      let n = self.vertices.len() as f32;
+      let sum = self
+        .vertices
+        .iter()
+        .fold(Point::origin(), |sum, &p| sum + p);
+      return Point::new(sum.x / n, sum.y / n);
+    }
+    let (x, y) = self.edges().fold((0.0, 0.0), |(x, y), (p, q)| {
+      let weight = p.x * q.y - q.x * p.y;
+      (x + (p.x + q.x) * weight, y + (p.y + q.y) * weight)
+    });
+    Point::new(x / (6.0 * area), y / (6.0 * area))
+  }
+
+  // Count the edges crossed by a ray going right from the point. Points on
+  // an edge are inside, which the ray can't tell, so they are checked first.
+  fn contains(&self, point: &Point) -> bool {
+    let on_edge = self.edges().any(|(p, q)| on_segment(point, p, q));
+    let crossings = self
+      .edges()
+      .filter(|(p, q)| {
+        // The edge spans the height of the point, counting its lower end only
+        // so that a vertex on the ray isn't counted twice
+        (p.y > point.y) != (q.y > point.y) && {
+          let x = p.x + (point.y - p.y) * (q.x - p.x) / (q.y - p.y);
+          point.x < x
+        }
+      })
+      .count();
+    on_edge || crossings % 2 == 1
+  }
+}
+
+// The shapes from the largest to the smallest, with their measures
+pub fn area_report(shapes: &[Box<dyn Shape>]) -> Table {
+  let mut sorted: Vec<&dyn Shape> = shapes.iter().map(|shape| shape.as_ref()).collect();
+  sorted.sort_by(|a, b| b.area().total_cmp(&a.area()));
+
+  let mut table = Table::new(vec![
+    Column::new("Shape", Align::Left),
+    Column::new("Area", Align::Right),
+    Column::new("Perimeter", Align::Right),
+    Column::new("Centroid", Align::Left),
+    Column::new("Bounding box", Align::Left),
+  ]);
+  for shape in sorted {
+    let bounds = shape.bounding_box();
+    table.push(vec![
+      shape.name().to_owned(),
+      format!("{:.2}", shape.area()),
+      format!("{:.2}", shape.perimeter()),
+      format!("{:.2}", shape.centroid()),
+      format!("{:.1} to {:.1}", bounds.top_left, bounds.bottom_right),
+    ]);
+  }
+  table
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::random::XorShift;
+
+  #[test]
+  fn triangle_contains() {
+    let triangle = Triangle::new(Point::origin(), Point::new(4.0, 0.0), Point::new(0.0, 4.0));
+    assert!(triangle.contains(&Point::new(1.0, 1.0)));
+    assert!(triangle.contains(&Point::new(2.0, 2.0)));
+    assert!(triangle.contains(&Point::new(4.0, 0.0)));
+    assert!(!triangle.contains(&Point::new(3.0, 3.0)));
+    assert!(!triangle.contains(&Point::new(-0.1, 1.0)));
+  }
+
+  #[test]
+  fn flat_triangle_only_contains_its_edges() {
+    let flat = Triangle::new(Point::origin(), Point::new(1.0, 1.0), Point::new(3.0, 3.0));
+    assert_eq!(flat.area(), 0.0);
+    assert!(flat.contains(&Point::new(2.0, 2.0)));
+    assert!(flat.contains(&Point::new(3.0, 3.0)));
+    // On the same line, but past the ends
+    assert!(!flat.contains(&Point::new(4.0, 4.0)));
+    assert!(!flat.contains(&Point::new(-10.0, -10.0)));
+    assert!(!flat.contains(&Point::new(1.0, 2.0)));
+
+    let dot = Triangle::new(
+      Point::new(1.0, 1.0),
+      Point::new(1.0, 1.0),
+      Point::new(1.0, 1.0),
+    );
+    assert!(dot.contains(&Point::new(1.0, 1.0)));
+    assert!(!dot.contains(&Point::new(2.0, 2.0)));
+  }
+
+  fn l_shape() -> Polygon {
+    Polygon::new(vec![
+      Point::new(0.0, 0.0),
+      Point::new(4.0, 0.0),
+      Point::new(4.0, 1.0),
+      Point::new(1.0, 1.0),
+      Point::new(1.0, 3.0),
+      Point::new(0.0, 3.0),
+    ])
+    .unwrap()
+  }
+
+  #[test]
+  fn polygon_measures() {
+    let l = l_shape();
+    assert_eq!(l.area(), 6.0);
+    assert_eq!(l.perimeter(), 14.0);
+    // A 4x1 bar and a 1x2 one, weighted by their areas
+    assert_eq!(l.centroid(), Point::new(1.5, 1.0));
+    assert_eq!(
+      l.bounding_box(),
+      Rectangle::from_corners(Point::origin(), Point::new(4.0, 3.0))
+    );
+
+    // Clockwise, the signed area is negative but nothing else changes
+    let mut reversed = l.vertices().to_vec();
+    reversed.reverse();
+    let reversed = Polygon::new(reversed).unwrap();
+    assert_eq!(reversed.area(), 6.0);
+    assert_eq!(reversed.centroid(), Point::new(1.5, 1.0));
+
+    // Aligned vertices have no area, and their average as the centroid
+    let flat = Polygon::new(vec![
+      Point::origin(),
+      Point::new(1.0, 0.0),
+      Point::new(5.0, 0.0),
+    ])
+    .unwrap();
+    assert_eq!(flat.area(), 0.0);
+    assert_eq!(flat.centroid(), Point::new(2.0, 0.0));
+  }
+
+  #[test]
+  fn polygon_contains() {
+    let l = l_shape();
+    // The notch of the L is in its bounding box only
+    let notch = Point::new(2.0, 2.0);
+    assert!(!l.contains(&notch));
+    assert!(l.bounding_box().contains(&notch));
+    assert!(l.contains(&Point::new(0.5, 2.0)));
+    assert!(l.contains(&Point::new(3.0, 0.5)));
+
+    // Vertices, including the inner corner, and points on horizontal edges
+    for &(x, y) in &[(0.0, 0.0), (4.0, 1.0), (1.0, 1.0), (0.0, 3.0)] {
+      assert!(l.contains(&Point::new(x, y)), "({}, {})", x, y);
+    }
+    for &(x, y) in &[(2.0, 0.0), (2.5, 1.0), (0.5, 3.0)] {
+      assert!(l.contains(&Point::new(x, y)), "({}, {})", x, y);
+    }
+
+    // Rays that run along a horizontal edge or through a vertex
+    assert!(l.contains(&Point::new(0.5, 1.0)));
+    assert!(!l.contains(&Point::new(-1.0, 1.0)));
+    assert!(!l.contains(&Point::new(-1.0, 0.0)));
+    assert!(!l.contains(&Point::new(-1.0, 3.0)));
+    assert!(!l.contains(&Point::new(4.5, 1.0)));
+    assert!(!l.contains(&Point::new(1.5, 3.0)));
+  }
+
+  #[test]
+  fn polygon_errors() {
+    assert_eq!(Polygon::new(Vec::new()), Err(Error::TooFewVertices(0)));
+    assert_eq!(
+      Polygon::new(vec![Point::origin(), Point::new(1.0, 1.0)]),
+      Err(Error::TooFewVertices(2))
+    );
+    let bow_tie = Polygon::new(vec![
+      Point::new(0.0, 0.0),
+      Point::new(2.0, 2.0),
+      Point::new(2.0, 0.0),
+      Point::new(0.0, 2.0),
+    ]);
+    assert_eq!(bow_tie, Err(Error::SelfIntersecting(0, 2)));
+    // A vertex that touches an edge it doesn't belong to
+    let pinched = Polygon::new(vec![
+      Point::new(0.0, 0.0),
+      Point::new(4.0, 0.0),
+      Point::new(2.0, 0.0),
+      Point::new(2.0, 2.0),
+    ]);
+    assert!(matches!(pinched, Err(Error::SelfIntersecting(_, _))));
+  }
+
+  // A triangle and a rectangle, as polygons, must measure the same and
+  // contain the same random points
+  #[test]
+  fn polygons_agree_with_triangles_and_rectangles() {
+    let mut rng = XorShift::new(45);
+    let mut random_point = || {
+      Point::new(
+        (rng.next_f64() * 200.0 - 100.0) as f32,
+        (rng.next_f64() * 200.0 - 100.0) as f32,
+      )
+    };
+    let close = |x: f32, y: f32| (x - y).abs() <= 1e-3 * x.abs().max(1.0);
+    for _ in 0..200 {
+      let (a, b, c) = (random_point(), random_point(), random_point());
+      let triangle = Triangle::new(a, b, c);
+      let rectangle = Rectangle::from_corners(a, b);
+      let corners = vec![
+        rectangle.top_left,
+        Point::new(rectangle.right(), rectangle.top()),
+        rectangle.bottom_right,
+        Point::new(rectangle.left(), rectangle.bottom()),
+      ];
+      // Random points are almost never aligned
+      let as_triangle = match Polygon::new(vec![a, b, c]) {
+        Ok(polygon) => polygon,
+        Err(_) => continue,
+      };
+      let as_rectangle = Polygon::new(corners).unwrap();
+      assert!(close(as_triangle.area(), triangle.area()), "{:?}", triangle);
+      assert!(close(as_rectangle.area(), Rectangle::area(&rectangle)));
+      assert!(close(
+        as_rectangle.perimeter(),
+        Rectangle::perimeter(&rectangle)
+      ));
+      for _ in 0..20 {
+        let p = random_point();
+        assert_eq!(as_triangle.contains(&p), triangle.contains(&p), "{:?}", p);
+        assert_eq!(
+          as_rectangle.contains(&p),
+          Rectangle::contains(&rectangle, &p),
+          "{:?}",
+          p
+        );
+      }
+    }
+  }
+}
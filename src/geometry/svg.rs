@@ -0,0 +1,441 @@
+/*
+ * SVG drawings of shapes.
+ *
+ * SVG puts the origin in the top left corner with `y` growing downwards,
+ * while the geometry modules have `y` growing upwards. `Svg` fits a view
+ * around every element, then maps each point to pixels itself, flipping `y`
+ * on the way: labels would be drawn upside down by a flipping `transform`.
+ *
+ *    Svg::new(400)
+ *      .grid(true)
+ *      .add(square, Style::new().fill(green).label("square"))
+ *      .save("shapes.svg")
+ *
+ * Colors are the `Color` of the formatting chapter. The result is a
+ * standalone document, that any browser opens.
+ */
+
+use super::shape::{Circle, Polygon, Triangle};
+use crate::chapters::c01::Color;
+use crate::chapters::c03::{Point, Rectangle};
+use std::fs;
+use std::io;
+use std::path::Path;
+
+// What can be drawn
+#[derive(Clone, Debug, PartialEq)]
+pub enum Element {
+  Rectangle(Rectangle),
+  Circle(Circle),
+  Polygon(Vec<Point>),
+  // A dot
+  Point(Point),
+}
+
+impl Element {
+  // `None` for a polygon without points, which is not drawn
+  fn bounds(&self) -> Option<Rectangle> {
+    let bounds = match self {
+      Element::Rectangle(rectangle) => rectangle.normalize(),
+      Element::Circle(circle) => {
+        let r = circle.radius;
+        Rectangle::from_corners(
+          circle.center.translate(-r, r),
+          circle.center.translate(r, -r),
+        )
+      }
+      Element::Polygon(points) => points
+        .iter()
+        .map(|&p| Rectangle::from_corners(p, p))
+        .reduce(|bounds, p| bounds.union(&p))?,
+      Element::Point(point) => Rectangle::from_corners(*point, *point),
+    };
+    Some(bounds)
+  }
+
+  // Where its label goes
+  fn anchor(&self) -> Option<Point> {
+    self.bounds().map(|bounds| bounds.center())
+  }
+}
+
+impl From<Rectangle> for Element {
+  fn from(rectangle: Rectangle) -> Element {
+    Element::Rectangle(rectangle)
+  }
+}
+
+impl From<Circle> for Element {
+  fn from(circle: Circle) -> Element {
+    Element::Circle(circle)
+  }
+}
+
+impl From<Triangle> for Element {
+  fn from(triangle: Triangle) -> Element {
+    Element::Polygon(triangle.vertices.to_vec())
+  }
+}
+
+impl From<Polygon> for Element {
+  fn from(polygon: Polygon) -> Element {
+    Element::Polygon(polygon.vertices().to_vec())
+  }
+}
+
+impl From<Point> for Element {
+  fn from(point: Point) -> Element {
+    Element::Point(point)
+  }
+}
+
+// How an element is painted: no fill and a black outline by default
+#[derive(Clone, Debug, PartialEq)]
+pub struct Style {
+  fill: Option<Color>,
+  stroke: Option<Color>,
+  stroke_width: f32,
+  label: Option<String>,
+}
+
+impl Style {
+  pub fn new() -> Style {
+    Style {
+      fill: None,
+      stroke: Some(Color {
+        red: 0,
+        green: 0,
+        blue: 0,
+      }),
+      stroke_width: 1.5,
+      label: None,
+    }
+  }
+
+  pub fn fill(mut self, color: Color) -> Style {
+    self.fill = Some(color);
+    self
+  }
+
+  pub fn stroke(mut self, color: Color) -> Style {
+    self.stroke = Some(color);
+    self
+  }
+
+  pub fn no_stroke(mut self) -> Style {
+    self.stroke = None;
+    self
+  }
+
+  // In pixels
+  pub fn stroke_width(mut self, width: f32) -> Style {
+    self.stroke_width = width;
+    self
+  }
+
+  // Text written in the middle of the element
+  pub fn label(mut self, text: &str) -> Style {
+    self.label = Some(text.to_owned());
+    self
+  }
+
+  fn attributes(&self) -> String {
+    let paint = |color: &Option<Color>| match color {
+      Some(color) => hex(color),
+      None => "none".to_owned(),
+    };
+    // A translucent fill keeps overlapping shapes visible
+    format!(
+      "fill=\"{}\" fill-opacity=\"0.6\" stroke=\"{}\" stroke-width=\"{}\"",
+      paint(&self.fill),
+      paint(&self.stroke),
+      self.stroke_width
+    )
+  }
+}
+
+impl Default for Style {
+  fn default() -> Style {
+    Style::new()
+  }
+}
+
+fn hex(color: &Color) -> String {
+  format!("#{:02x}{:02x}{:02x}", color.red, color.green, color.blue)
+}
+
+// Text can't contain markup characters
+fn escape(text: &str) -> String {
+  text
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+// A round distance between grid lines, about a tenth of `span`: 1, 2 or 5
+// times a power of ten
+fn grid_step(span: f32) -> f32 {
+  let raw = (span / 10.0).max(f32::MIN_POSITIVE);
+  let magnitude = 10f32.powf(raw.log10().floor());
+  let step = match raw / magnitude {
+    x if x < 1.5 => 1.0,
+    x if x < 3.5 => 2.0,
+    x if x < 7.5 => 5.0,
+    _ => 10.0,
+  };
+  step * magnitude
+}
+
+// Twice the widest margin, the one left for ticks, and some room to draw
+pub const MIN_WIDTH: u32 = 100;
+
+pub struct Svg {
+  width: f32,
+  elements: Vec<(Element, Style)>,
+  axes: bool,
+  grid: bool,
+  ticks: bool,
+}
+
+// The part of the plane that is drawn, and how it maps to pixels
+struct View {
+  bounds: Rectangle,
+  scale: f32,
+  margin: f32,
+}
+
+impl View {
+  fn x(&self, x: f32) -> f32 {
+    self.margin + (x - self.bounds.left()) * self.scale
+  }
+
+  // Flipped, the top of the view is at pixel 0
+  fn y(&self, y: f32) -> f32 {
+    self.margin + (self.bounds.top() - y) * self.scale
+  }
+
+  fn point(&self, point: &Point) -> String {
+    format!("{:.2},{:.2}", self.x(point.x), self.y(point.y))
+  }
+}
+
+impl Svg {
+  // A drawing `width` pixels wide, as high as the shapes need. A narrower
+  // width than `MIN_WIDTH` is widened to it, to leave room for the margins.
+  pub fn new(width: u32) -> Svg {
+    Svg {
+      width: width.max(MIN_WIDTH) as f32,
+      elements: Vec::new(),
+      axes: false,
+      grid: false,
+      ticks: false,
+    }
+  }
+
+  pub fn add<E: Into<Element>>(mut self, element: E, style: Style) -> Svg {
+    self.elements.push((element.into(), style));
+    self
+  }
+
+  // The `x` and `y` axes, through the origin
+  pub fn axes(mut self, axes: bool) -> Svg {
+    self.axes = axes;
+    self
+  }
+
+  // Light lines at round coordinates
+  pub fn grid(mut self, grid: bool) -> Svg {
+    self.grid = grid;
+    self
+  }
+
+  // The coordinates of the grid lines, along the edges
+  pub fn ticks(mut self, ticks: bool) -> Svg {
+    self.ticks = ticks;
+    self
+  }
+
+  // Every element with some room around it, and the origin when the axes
+  // are drawn
+  fn view(&self) -> View {
+    let mut bounds = self
+      .elements
+      .iter()
+      .filter_map(|(element, _)| element.bounds())
+      .reduce(|all, bounds| all.union(&bounds))
+      .unwrap_or_else(|| Rectangle::from_corners(Point::new(-1.0, 1.0), Point::new(1.0, -1.0)));
+    if self.axes {
+      bounds = bounds.union(&Rectangle::from_corners(Point::origin(), Point::origin()));
+    }
+    // A single point or a line still needs some room
+    let size = bounds.width().max(bounds.height()).max(1.0);
+    let bounds = Rectangle::from_corners(
+      bounds.top_left.translate(-size * 0.05, size * 0.05),
+      bounds.bottom_right.translate(size * 0.05, -size * 0.05),
+    );
+    let margin = if self.ticks { 40.0 } else { 10.0 };
+    View {
+      scale: (self.width - 2.0 * margin) / bounds.width(),
+      bounds,
+      margin,
+    }
+  }
+
+  pub fn render(&self) -> String {
+    let view = self.view();
+    let height = view.bounds.height() * view.scale + 2.0 * view.margin;
+    let mut out = format!(
+      "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w:.0}\" height=\"{h:.0}\" viewBox=\"0 0 {w:.0} {h:.0}\" font-family=\"sans-serif\" font-size=\"12\">\n",
+      w = self.width,
+      h = height
+    );
+    out.push_str("  <rect width=\"100%\" height=\"100%\" fill=\"white\"/>\n");
+
+    let step = grid_step(view.bounds.width().max(view.bounds.height()));
+    // The round coordinates between `low` and `high`
+    let lines = |low: f32, high: f32| {
+      ((low / step).ceil() as i64..=(high / step).floor() as i64).map(move |k| k as f32 * step)
+    };
+    let decimals = (-step.log10().floor()).max(0.0) as usize;
+    let (left, right) = (view.x(view.bounds.left()), view.x(view.bounds.right()));
+    let (top, bottom) = (view.y(view.bounds.top()), view.y(view.bounds.bottom()));
+
+    if self.grid {
+      for x in lines(view.bounds.left(), view.bounds.right()) {
+        out.push_str(&format!(
+          "  <line x1=\"{x:.2}\" y1=\"{:.2}\" x2=\"{x:.2}\" y2=\"{:.2}\" stroke=\"#dddddd\"/>\n",
+          top,
+          bottom,
+          x = view.x(x)
+        ));
+      }
+      for y in lines(view.bounds.bottom(), view.bounds.top()) {
+        out.push_str(&format!(
+          "  <line x1=\"{:.2}\" y1=\"{y:.2}\" x2=\"{:.2}\" y2=\"{y:.2}\" stroke=\"#dddddd\"/>\n",
+          left,
+          right,
+          y = view.y(y)
+        ));
+      }
+    }
+    if self.ticks {
+      for x in lines(view.bounds.left(), view.bounds.right()) {
+        out.push_str(&format!(
+          "  <text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" fill=\"#666666\">{:.*}</text>\n",
+          view.x(x),
+          bottom + 16.0,
+          decimals,
+          x
+        ));
+      }
+      for y in lines(view.bounds.bottom(), view.bounds.top()) {
+        out.push_str(&format!(
+          "  <text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"end\" dominant-baseline=\"middle\" fill=\"#666666\">{:.*}</text>\n",
+          left - 6.0,
+          view.y(y),
+          decimals,
+          y
+        ));
+      }
+    }
+    if self.axes {
+      let (x0, y0) = (view.x(0.0), view.y(0.0));
+      out.push_str(&format!(
+        "  <line x1=\"{:.2}\" y1=\"{y0:.2}\" x2=\"{:.2}\" y2=\"{y0:.2}\" stroke=\"#888888\"/>\n",
+        left,
+        right,
+        y0 = y0
+      ));
+      out.push_str(&format!(
+        "  <line x1=\"{x0:.2}\" y1=\"{:.2}\" x2=\"{x0:.2}\" y2=\"{:.2}\" stroke=\"#888888\"/>\n",
+        top,
+        bottom,
+        x0 = x0
+      ));
+    }
+
+    for (element, style) in &self.elements {
+      if element.bounds().is_none() {
+        continue;
+      }
+      let attributes = style.attributes();
+      let shape = match element {
+        Element::Rectangle(rectangle) => format!(
+          "<rect x=\"{:.2}\" y=\"{:.2}\" width=\"{:.2}\" height=\"{:.2}\" {}/>",
+          view.x(rectangle.left()),
+          view.y(rectangle.top()),
+          rectangle.width() * view.scale,
+          rectangle.height() * view.scale,
+          attributes
+        ),
+        Element::Circle(circle) => format!(
+          "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"{:.2}\" {}/>",
+          view.x(circle.center.x),
+          view.y(circle.center.y),
+          circle.radius * view.scale,
+          attributes
+        ),
+        Element::Polygon(points) => {
+          let points: Vec<String> = points.iter().map(|p| view.point(p)).collect();
+          format!("<polygon points=\"{}\" {}/>", points.join(" "), attributes)
+        }
+        Element::Point(point) => format!(
+          "<circle cx=\"{:.2}\" cy=\"{:.2}\" r=\"3\" {}/>",
+          view.x(point.x),
+          view.y(point.y),
+          attributes
+        ),
+      };
+      out.push_str(&format!("  {}\n", shape));
+    }
+    // Labels last, so that no shape covers them
+    for (element, style) in &self.elements {
+      if let (Some(label), Some(anchor)) = (&style.label, element.anchor()) {
+        out.push_str(&format!(
+          "  <text x=\"{:.2}\" y=\"{:.2}\" text-anchor=\"middle\" dominant-baseline=\"middle\">{}</text>\n",
+          view.x(anchor.x),
+          view.y(anchor.y),
+          escape(label)
+        ));
+      }
+    }
+    out.push_str("</svg>\n");
+    out
+  }
+
+  pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+    fs::write(path, self.render())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_polygons_are_skipped() {
+    let svg = Svg::new(200)
+      .add(Element::Polygon(Vec::new()), Style::new().label("nothing"))
+      .add(Point::new(1.0, 2.0), Style::new())
+      .render();
+    assert!(!svg.contains("<polygon"));
+    assert!(!svg.contains("nothing"));
+    assert_eq!(svg.matches("<circle").count(), 1);
+    assert!(Svg::new(200)
+      .add(Element::Polygon(Vec::new()), Style::new())
+      .render()
+      .ends_with("</svg>\n"));
+  }
+
+  #[test]
+  fn narrow_drawings_are_widened() {
+    let square = Rectangle::from_corners(Point::origin(), Point::new(1.0, 1.0));
+    let svg = Svg::new(20).ticks(true).add(square, Style::new()).render();
+    assert!(svg.contains(&format!("width=\"{}\"", MIN_WIDTH)));
+    // Not mirrored: the left edge of the square is left of its right edge
+    let view = Svg::new(20).ticks(true).add(square, Style::new()).view();
+    assert!(view.scale > 0.0);
+    assert!(view.x(0.0) < view.x(1.0) && view.y(1.0) < view.y(0.0));
+  }
+}
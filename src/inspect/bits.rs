@@ -0,0 +1,300 @@
+/*
+ * Bit-level integer inspector.
+ *
+ * `primitives_and_operations` prints `0011 AND 0101` with `{:04b}`. `Bits`
+ * does the same for any value of any integer type: the binary digits grouped
+ * in nibbles, hex, octal, how a signed value is stored in two's complement,
+ * and the results of the bitwise operators lined up bit by bit.
+ *
+ * Every type fits in a `u128`, so the value is kept as its raw bit pattern
+ * together with its type, and each operation masks the result back to the
+ * width of the type.
+ */
+
+use super::overflow::{Error, IntType};
+use crate::format::table::{Align, Column, Table};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Bits {
+  ty: IntType,
+  // The bit pattern, with everything above the width of `ty` cleared
+  raw: u128,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BitOp {
+  And,
+  Or,
+  Xor,
+  Shl,
+  Shr,
+  RotateLeft,
+  SwapBytes,
+}
+
+impl BitOp {
+  pub const ALL: [BitOp; 7] = [
+    BitOp::And,
+    BitOp::Or,
+    BitOp::Xor,
+    BitOp::Shl,
+    BitOp::Shr,
+    BitOp::RotateLeft,
+    BitOp::SwapBytes,
+  ];
+
+  pub fn symbol(self) -> &'static str {
+    match self {
+      BitOp::And => "&",
+      BitOp::Or => "|",
+      BitOp::Xor => "^",
+      BitOp::Shl => "<<",
+      BitOp::Shr => ">>",
+      BitOp::RotateLeft => "rotate_left",
+      BitOp::SwapBytes => "swap_bytes",
+    }
+  }
+}
+
+fn mask(bits: u32) -> u128 {
+  if bits == 128 {
+    u128::MAX
+  } else {
+    (1 << bits) - 1
+  }
+}
+
+// Split `0x`, `0o` and `0b` prefixes off a literal
+fn radix(text: &str) -> (u32, &str) {
+  match text.get(..2) {
+    Some("0x") | Some("0X") => (16, &text[2..]),
+    Some("0o") | Some("0O") => (8, &text[2..]),
+    Some("0b") | Some("0B") => (2, &text[2..]),
+    _ => (10, text),
+  }
+}
+
+impl Bits {
+  // Keep the lowest bits of `raw` that fit in `ty`
+  pub fn new(ty: IntType, raw: u128) -> Bits {
+    Bits {
+      ty,
+      raw: raw & mask(ty.bits()),
+    }
+  }
+
+  // Parse a literal like `-5`, `0xff` or `0b1010_0101`, which must be in
+  // range for `ty`
+  pub fn parse(ty: IntType, text: &str) -> Result<Bits, Error> {
+    let invalid = || Error::InvalidOperand {
+      ty,
+      text: text.to_owned(),
+    };
+    let cleaned = text.replace('_', "");
+    let (negative, digits) = match cleaned.strip_prefix('-') {
+      Some(rest) => (true, rest),
+      None => (false, cleaned.as_str()),
+    };
+    let (radix, digits) = radix(digits);
+    let magnitude = u128::from_str_radix(digits, radix).map_err(|_| invalid())?;
+
+    let bits = ty.bits();
+    let max = if ty.is_signed() {
+      mask(bits - 1)
+    } else {
+      mask(bits)
+    };
+    if negative {
+      // The most negative value has no positive counterpart: -128i8
+      if !ty.is_signed() || magnitude > max + 1 {
+        return Err(invalid());
+      }
+      Ok(Bits::new(ty, magnitude.wrapping_neg()))
+    } else if magnitude > max {
+      Err(invalid())
+    } else {
+      Ok(Bits::new(ty, magnitude))
+    }
+  }
+
+  pub fn ty(&self) -> IntType {
+    self.ty
+  }
+
+  pub fn width(&self) -> u32 {
+    self.ty.bits()
+  }
+
+  pub fn raw(&self) -> u128 {
+    self.raw
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.ty.is_signed() && self.raw >> (self.width() - 1) == 1
+  }
+
+  // The value, read as a signed number whatever the type
+  pub fn signed(&self) -> i128 {
+    let unused = 128 - self.width();
+    // Move the sign bit to the top, then shift back extending it
+    ((self.raw << unused) as i128) >> unused
+  }
+
+  // The value, as the type reads it
+  pub fn value(&self) -> String {
+    if self.ty.is_signed() {
+      self.signed().to_string()
+    } else {
+      self.raw.to_string()
+    }
+  }
+
+  // All the bits, in groups of four: `0000 0101`
+  pub fn binary(&self) -> String {
+    let digits = format!("{:0width$b}", self.raw, width = self.width() as usize);
+    let nibbles: Vec<&str> = (0..digits.len())
+      .step_by(4)
+      .map(|i| &digits[i..i + 4])
+      .collect();
+    nibbles.join(" ")
+  }
+
+  pub fn hex(&self) -> String {
+    format!("0x{:0width$x}", self.raw, width = self.width() as usize / 4)
+  }
+
+  pub fn octal(&self) -> String {
+    format!("0o{:o}", self.raw)
+  }
+
+  pub fn count_ones(&self) -> u32 {
+    self.raw.count_ones()
+  }
+
+  pub fn leading_zeros(&self) -> u32 {
+    self.raw.leading_zeros() - (128 - self.width())
+  }
+
+  pub fn trailing_zeros(&self) -> u32 {
+    self.raw.trailing_zeros().min(self.width())
+  }
+
+  // How a signed value is read from its bits: the top bit weighs
+  // `-2^(width - 1)` instead of `2^(width - 1)`
+  pub fn twos_complement(&self) -> Option<String> {
+    if !self.ty.is_signed() {
+      return None;
+    }
+    let top = self.width() - 1;
+    let rest = self.raw & mask(top);
+    if self.is_negative() {
+      Some(format!("-2^{} + {} = {}", top, rest, self.signed()))
+    } else {
+      Some(format!("0 + {} = {}", rest, self.signed()))
+    }
+  }
+
+  // The steps to store a negative number: invert the bits of its magnitude
+  // and add one
+  pub fn negation_steps(&self) -> Option<[Bits; 3]> {
+    if !self.is_negative() {
+      return None;
+    }
+    let magnitude = Bits::new(self.ty, self.raw.wrapping_neg());
+    let inverted = Bits::new(self.ty, !magnitude.raw);
+    Some([magnitude, inverted, *self])
+  }
+
+  // Apply `op` with `other` as the right hand side. Shifts and rotations use
+  // `other` as the amount; `None` when it is too big, like `checked_shl`.
+  pub fn apply(&self, op: BitOp, other: &Bits) -> Option<Bits> {
+    let width = self.width();
+    let amount = if other.raw < u128::from(width) {
+      Some(other.raw as u32)
+    } else {
+      None
+    };
+
+    let raw = match op {
+      BitOp::And => self.raw & other.raw,
+      BitOp::Or => self.raw | other.raw,
+      BitOp::Xor => self.raw ^ other.raw,
+      BitOp::Shl => self.raw << amount?,
+      // Signed types shift the sign bit in, unsigned types shift zeros in
+      BitOp::Shr if self.ty.is_signed() => (self.signed() >> amount?) as u128,
+      BitOp::Shr => self.raw >> amount?,
+      BitOp::RotateLeft => {
+        // Rotating by the width is a full turn
+        let amount = (other.raw % u128::from(width)) as u32;
+        if amount == 0 {
+          self.raw
+        } else {
+          (self.raw << amount) | (self.raw >> (width - amount))
+        }
+      }
+      BitOp::SwapBytes => {
+        let bytes = width / 8;
+        (0..bytes).fold(0, |acc, i| {
+          let byte = (self.raw >> (8 * i)) & 0xff;
+          acc | byte << (8 * (bytes - 1 - i))
+        })
+      }
+    };
+    Some(Bits::new(self.ty, raw))
+  }
+
+  // `a op b = c`, one line per operand so that the bits line up
+  pub fn operation(&self, op: BitOp, other: &Bits) -> String {
+    let pad = " ".repeat(self.binary().len());
+    let line = |prefix: &str, bits: &str, value: &str| {
+      let text = format!("{:>11} {}  {}", prefix, bits, value);
+      format!("{}\n", text.trim_end())
+    };
+
+    let mut out = line("", &self.binary(), &self.value());
+    match op {
+      BitOp::And | BitOp::Or | BitOp::Xor => {
+        out.push_str(&line(op.symbol(), &other.binary(), &other.value()));
+      }
+      BitOp::Shl | BitOp::Shr | BitOp::RotateLeft => {
+        out.push_str(&line(op.symbol(), &pad, &other.value()));
+      }
+      BitOp::SwapBytes => out.push_str(&line(op.symbol(), &pad, "")),
+    }
+    match self.apply(op, other) {
+      Some(result) => out.push_str(&line("=", &result.binary(), &result.value())),
+      None => out.push_str(&line(
+        "=",
+        "overflow",
+        &format!("shift amount must be below {}", self.width()),
+      )),
+    }
+    out
+  }
+
+  pub fn table(&self) -> Table {
+    let mut table = Table::new(vec![
+      Column::new("Property", Align::Left),
+      Column::new("Value", Align::Right),
+    ]);
+    let mut row = |name: &str, value: String| table.push(vec![name.to_owned(), value]);
+
+    row("value", format!("{}{}", self.value(), self.ty));
+    row("binary", self.binary());
+    row("hex", self.hex());
+    row("octal", self.octal());
+    if let Some(reading) = self.twos_complement() {
+      row("two's complement", reading);
+      row("as unsigned", self.raw.to_string());
+    }
+    if let Some([magnitude, inverted, stored]) = self.negation_steps() {
+      row("magnitude", magnitude.binary());
+      row("inverted", inverted.binary());
+      row("plus one", stored.binary());
+    }
+    row("count_ones", self.count_ones().to_string());
+    row("leading_zeros", self.leading_zeros().to_string());
+    row("trailing_zeros", self.trailing_zeros().to_string());
+    table
+  }
+}
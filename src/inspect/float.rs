@@ -0,0 +1,593 @@
+/*
+ * IEEE-754 float inspector.
+ *
+ * `let pi: f32 = 3.141562654` doesn't store 3.141562654: an `f32` only has 24
+ * bits of precision, so the literal is rounded to the nearest value of the
+ * form `±1.mantissa × 2^exponent`. `Float` splits `f32` and `f64` values into
+ * those fields, classifies them, and prints the exact decimal value that is
+ * stored, the gap to the neighboring values and the rounding error against
+ * the typed literal.
+ *
+ * Like `Bits`, the value is kept as its raw bit pattern next to its type.
+ */
+
+use crate::format::table::{Align, Column, Table};
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::num::FpCategory;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FloatType {
+  F32,
+  F64,
+}
+
+impl FloatType {
+  pub fn name(self) -> &'static str {
+    match self {
+      FloatType::F32 => "f32",
+      FloatType::F64 => "f64",
+    }
+  }
+
+  pub fn exponent_bits(self) -> u32 {
+    match self {
+      FloatType::F32 => 8,
+      FloatType::F64 => 11,
+    }
+  }
+
+  // Stored mantissa bits, without the implicit leading one
+  pub fn mantissa_bits(self) -> u32 {
+    match self {
+      FloatType::F32 => 23,
+      FloatType::F64 => 52,
+    }
+  }
+
+  pub fn bias(self) -> i32 {
+    (1 << (self.exponent_bits() - 1)) - 1
+  }
+
+  pub fn bits(self) -> u32 {
+    1 + self.exponent_bits() + self.mantissa_bits()
+  }
+
+  // The number of decimal places of the smallest subnormal, 1075 for `f64`.
+  // A literal with a bigger decimal exponent rounds to zero or infinity.
+  fn max_decimal_exponent(self) -> u32 {
+    self.bias() as u32 + self.mantissa_bits()
+  }
+}
+
+impl Display for FloatType {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for FloatType {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<FloatType, Error> {
+    match s {
+      "f32" => Ok(FloatType::F32),
+      "f64" => Ok(FloatType::F64),
+      _ => Err(Error::UnknownType(s.to_owned())),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  UnknownType(String),
+  InvalidLiteral(String),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::UnknownType(name) => write!(f, "unknown float type `{}`, expected f32 or f64", name),
+      Error::InvalidLiteral(text) => write!(f, "`{}` is not a valid float literal", text),
+    }
+  }
+}
+
+// A natural number in base 10^9 limbs, least significant first. It only does
+// what exact decimal expansions need.
+#[derive(Clone, Debug, PartialEq)]
+struct Natural(Vec<u32>);
+
+const LIMB: u64 = 1_000_000_000;
+
+impl Natural {
+  fn new(mut n: u64) -> Natural {
+    let mut limbs = Vec::new();
+    while n > 0 {
+      limbs.push((n % LIMB) as u32);
+      n /= LIMB;
+    }
+    Natural(limbs)
+  }
+
+  fn mul_small(&mut self, k: u32) {
+    let mut carry = 0;
+    for limb in self.0.iter_mut() {
+      let product = u64::from(*limb) * u64::from(k) + carry;
+      *limb = (product % LIMB) as u32;
+      carry = product / LIMB;
+    }
+    while carry > 0 {
+      self.0.push((carry % LIMB) as u32);
+      carry /= LIMB;
+    }
+  }
+
+  fn mul_pow(&mut self, base: u32, exponent: u32) {
+    for _ in 0..exponent {
+      self.mul_small(base);
+    }
+  }
+
+  fn trim(&mut self) {
+    while self.0.last() == Some(&0) {
+      self.0.pop();
+    }
+  }
+
+  fn compare(&self, other: &Natural) -> Ordering {
+    self
+      .0
+      .len()
+      .cmp(&other.0.len())
+      .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+  }
+
+  fn add(&self, other: &Natural) -> Natural {
+    let mut limbs = Vec::new();
+    let mut carry = 0;
+    for i in 0..self.0.len().max(other.0.len()) {
+      let sum =
+        u64::from(*self.0.get(i).unwrap_or(&0)) + u64::from(*other.0.get(i).unwrap_or(&0)) + carry;
+      limbs.push((sum % LIMB) as u32);
+      carry = sum / LIMB;
+    }
+    if carry > 0 {
+      limbs.push(carry as u32);
+    }
+    Natural(limbs)
+  }
+
+  // `self - other`, with `self >= other`
+  fn sub(&self, other: &Natural) -> Natural {
+    let mut limbs = Vec::new();
+    let mut borrow = 0;
+    for (i, &limb) in self.0.iter().enumerate() {
+      let subtrahend = i64::from(*other.0.get(i).unwrap_or(&0)) + borrow;
+      let mut difference = i64::from(limb) - subtrahend;
+      borrow = 0;
+      if difference < 0 {
+        difference += LIMB as i64;
+        borrow = 1;
+      }
+      limbs.push(difference as u32);
+    }
+    let mut result = Natural(limbs);
+    result.trim();
+    result
+  }
+}
+
+impl Display for Natural {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self.0.split_last() {
+      None => write!(f, "0"),
+      Some((top, rest)) => {
+        write!(f, "{}", top)?;
+        for limb in rest.iter().rev() {
+          write!(f, "{:09}", limb)?;
+        }
+        Ok(())
+      }
+    }
+  }
+}
+
+// An exact decimal number: `±digits / 10^scale`
+#[derive(Clone, Debug, PartialEq)]
+struct Exact {
+  negative: bool,
+  digits: Natural,
+  scale: u32,
+}
+
+impl Exact {
+  // `mantissa × 2^exponent`. A negative power of two is exact in decimal,
+  // because `2^-k = 5^k / 10^k`.
+  fn from_binary(negative: bool, mantissa: u64, exponent: i32) -> Exact {
+    let mut digits = Natural::new(mantissa);
+    let scale = if exponent >= 0 {
+      digits.mul_pow(2, exponent as u32);
+      0
+    } else {
+      digits.mul_pow(5, exponent.unsigned_abs());
+      exponent.unsigned_abs()
+    };
+    digits.trim();
+    Exact {
+      negative,
+      digits,
+      scale,
+    }
+  }
+
+  // A decimal literal such as `-3.14`, `1e-3` or `2.5E10`. `None` when the
+  // exponent is beyond `max_exponent` either way, which would make the
+  // digits huge.
+  fn parse(text: &str, max_exponent: u32) -> Option<Exact> {
+    let text = text.replace('_', "");
+    let (negative, text) = match text.strip_prefix('-') {
+      Some(rest) => (true, rest.to_owned()),
+      None => (false, text.trim_start_matches('+').to_owned()),
+    };
+    let (number, exponent) = match text.find(&['e', 'E'][..]) {
+      Some(at) => (&text[..at], text[at + 1..].parse::<i32>().ok()?),
+      None => (text.as_str(), 0),
+    };
+    if exponent.unsigned_abs() > max_exponent {
+      return None;
+    }
+    let (whole, fraction) = match number.find('.') {
+      Some(dot) => (&number[..dot], &number[dot + 1..]),
+      None => (number, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+      return None;
+    }
+
+    let mut digits = Natural::new(0);
+    for c in whole.chars().chain(fraction.chars()) {
+      digits.mul_small(10);
+      digits = digits.add(&Natural::new(u64::from(c.to_digit(10)?)));
+    }
+    digits.trim();
+
+    let scale = fraction.len() as i32 - exponent;
+    if scale < 0 {
+      digits.mul_pow(10, scale.unsigned_abs());
+    }
+    Some(Exact {
+      negative,
+      digits,
+      scale: scale.max(0) as u32,
+    })
+  }
+
+  // The digits of `self` for a bigger `scale`
+  fn rescaled(&self, scale: u32) -> Natural {
+    let mut digits = self.digits.clone();
+    digits.mul_pow(10, scale - self.scale);
+    digits
+  }
+
+  fn sub(&self, other: &Exact) -> Exact {
+    let scale = self.scale.max(other.scale);
+    let (a, b) = (self.rescaled(scale), other.rescaled(scale));
+    let (negative, digits) = match (self.negative, other.negative) {
+      // Different signs: the magnitudes add up
+      (x, y) if x != y => (x, a.add(&b)),
+      (sign, _) => match a.compare(&b) {
+        Ordering::Less => (!sign, b.sub(&a)),
+        _ => (sign, a.sub(&b)),
+      },
+    };
+    let is_zero = digits.0.is_empty();
+    Exact {
+      negative: negative && !is_zero,
+      digits,
+      scale,
+    }
+  }
+}
+
+impl Display for Exact {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let digits = self.digits.to_string();
+    let scale = self.scale as usize;
+    let sign = if self.negative { "-" } else { "" };
+    if scale == 0 {
+      return write!(f, "{}{}", sign, digits);
+    }
+
+    let padded = format!("{:0>width$}", digits, width = scale + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - scale);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+      write!(f, "{}{}", sign, whole)
+    } else {
+      write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Float {
+  ty: FloatType,
+  raw: u64,
+}
+
+impl From<f32> for Float {
+  fn from(value: f32) -> Float {
+    Float {
+      ty: FloatType::F32,
+      raw: u64::from(value.to_bits()),
+    }
+  }
+}
+
+impl From<f64> for Float {
+  fn from(value: f64) -> Float {
+    Float {
+      ty: FloatType::F64,
+      raw: value.to_bits(),
+    }
+  }
+}
+
+impl Float {
+  // Round a literal like the compiler does: `3.141562654` or `1e-3`
+  pub fn parse(ty: FloatType, text: &str) -> Result<Float, Error> {
+    let invalid = || Error::InvalidLiteral(text.to_owned());
+    let cleaned = text.replace('_', "");
+    match ty {
+      FloatType::F32 => cleaned.parse::<f32>().map(Float::from),
+      FloatType::F64 => cleaned.parse::<f64>().map(Float::from),
+    }
+    .map_err(|_| invalid())
+  }
+
+  pub fn ty(&self) -> FloatType {
+    self.ty
+  }
+
+  pub fn raw(&self) -> u64 {
+    self.raw
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.raw >> (self.ty.bits() - 1) == 1
+  }
+
+  pub fn biased_exponent(&self) -> u32 {
+    let mask = (1 << self.ty.exponent_bits()) - 1;
+    (self.raw >> self.ty.mantissa_bits()) as u32 & mask
+  }
+
+  // The power of two the significand is multiplied by. Subnormals use the
+  // smallest normal exponent, and infinities and NaN have none.
+  pub fn unbiased_exponent(&self) -> Option<i32> {
+    match self.category() {
+      FpCategory::Normal => Some(self.biased_exponent() as i32 - self.ty.bias()),
+      FpCategory::Subnormal => Some(1 - self.ty.bias()),
+      _ => None,
+    }
+  }
+
+  pub fn mantissa(&self) -> u64 {
+    self.raw & ((1 << self.ty.mantissa_bits()) - 1)
+  }
+
+  pub fn category(&self) -> FpCategory {
+    match self.ty {
+      FloatType::F32 => f32::from_bits(self.raw as u32).classify(),
+      FloatType::F64 => f64::from_bits(self.raw).classify(),
+    }
+  }
+
+  pub fn is_finite(&self) -> bool {
+    !matches!(self.category(), FpCategory::Infinite | FpCategory::Nan)
+  }
+
+  // The shortest text that reads back as the same value. Very small and
+  // very big values switch to scientific notation, `{}` would print every
+  // zero.
+  pub fn shortest(&self) -> String {
+    let value = self.to_f64();
+    let magnitude = value.abs();
+    let scientific = magnitude != 0.0 && !(1e-5..1e16).contains(&magnitude);
+    match (self.ty, scientific) {
+      (FloatType::F32, true) => format!("{:e}", value as f32),
+      (FloatType::F32, false) => (value as f32).to_string(),
+      (FloatType::F64, true) => format!("{:e}", value),
+      (FloatType::F64, false) => value.to_string(),
+    }
+  }
+
+  // Widening an `f32` to `f64` is exact
+  pub fn to_f64(&self) -> f64 {
+    match self.ty {
+      FloatType::F32 => f64::from(f32::from_bits(self.raw as u32)),
+      FloatType::F64 => f64::from_bits(self.raw),
+    }
+  }
+
+  // The bits split in fields: `sign exponent mantissa`
+  pub fn fields(&self) -> String {
+    let digits = format!("{:0width$b}", self.raw, width = self.ty.bits() as usize);
+    let (sign, rest) = digits.split_at(1);
+    let (exponent, mantissa) = rest.split_at(self.ty.exponent_bits() as usize);
+    format!("{} {} {}", sign, exponent, mantissa)
+  }
+
+  // The significand, implicit bit included, as an integer
+  fn significand(&self) -> u64 {
+    match self.category() {
+      FpCategory::Normal => self.mantissa() | (1 << self.ty.mantissa_bits()),
+      _ => self.mantissa(),
+    }
+  }
+
+  fn exact(&self) -> Option<Exact> {
+    let exponent = self.unbiased_exponent().unwrap_or(0) - self.ty.mantissa_bits() as i32;
+    if self.is_finite() {
+      Some(Exact::from_binary(
+        self.is_negative(),
+        self.significand(),
+        exponent,
+      ))
+    } else {
+      None
+    }
+  }
+
+  // Every digit of the value that is actually stored
+  pub fn exact_decimal(&self) -> Option<String> {
+    self.exact().map(|exact| exact.to_string())
+  }
+
+  // `±1.significand × 2^exponent`, with the significand written out exactly
+  pub fn formula(&self) -> Option<String> {
+    let exponent = self.unbiased_exponent()?;
+    let significand =
+      Exact::from_binary(false, self.significand(), -(self.ty.mantissa_bits() as i32));
+    let sign = if self.is_negative() { '-' } else { '+' };
+    Some(format!("{}{} × 2^{}", sign, significand, exponent))
+  }
+
+  fn with_raw(&self, raw: u64) -> Float {
+    Float { ty: self.ty, raw }
+  }
+
+  // The next representable value towards +∞
+  pub fn next_up(&self) -> Option<Float> {
+    match self.category() {
+      FpCategory::Nan => None,
+      FpCategory::Infinite if !self.is_negative() => None,
+      // Both zeros step to the smallest positive subnormal
+      FpCategory::Zero => Some(self.with_raw(1)),
+      _ if self.is_negative() => {
+        // -min_subnormal steps to -0.0, whose bits are just the sign
+        Some(self.with_raw(self.raw - 1))
+      }
+      _ => Some(self.with_raw(self.raw + 1)),
+    }
+  }
+
+  // The next representable value towards -∞
+  pub fn next_down(&self) -> Option<Float> {
+    let sign_bit = 1 << (self.ty.bits() - 1);
+    match self.category() {
+      FpCategory::Nan => None,
+      FpCategory::Infinite if self.is_negative() => None,
+      FpCategory::Zero => Some(self.with_raw(sign_bit | 1)),
+      _ if self.is_negative() => Some(self.with_raw(self.raw + 1)),
+      _ => Some(self.with_raw(self.raw - 1)),
+    }
+  }
+
+  // The power of two between this value and the next one away from zero
+  fn ulp_exponent(&self) -> i32 {
+    let biased = self.biased_exponent().max(1) as i32;
+    biased - self.ty.bias() - self.ty.mantissa_bits() as i32
+  }
+
+  // Exponent of the gap between two neighbors, `2^k`. The gap is the ULP of
+  // the neighbor closer to zero.
+  fn gap(&self, other: &Float) -> Option<i32> {
+    if !self.is_finite() || !other.is_finite() {
+      return None;
+    }
+    let smaller =
+      if self.raw & !(1 << (self.ty.bits() - 1)) < other.raw & !(1 << (other.ty.bits() - 1)) {
+        self
+      } else {
+        other
+      };
+    Some(smaller.ulp_exponent())
+  }
+
+  // `stored - literal`, exactly. `None` for infinities, NaN or a literal that
+  // is not a plain decimal number, or whose exponent is out of range.
+  pub fn rounding_error(&self, literal: &str) -> Option<String> {
+    let error = self.exact()?.sub(&self.parse_exact(literal)?);
+    Some(error.to_string())
+  }
+
+  fn parse_exact(&self, literal: &str) -> Option<Exact> {
+    Exact::parse(literal, self.ty.max_decimal_exponent())
+  }
+
+  // The rounding error measured in ULPs of the stored value
+  pub fn rounding_error_ulps(&self, literal: &str) -> Option<f64> {
+    let mut error = self.exact()?.sub(&self.parse_exact(literal)?);
+    let exponent = self.ulp_exponent();
+    if exponent < 0 {
+      // Dividing by `2^-k` is multiplying by `2^k`, which stays exact. An
+      // `f64` could underflow for subnormal errors.
+      error.digits.mul_pow(2, exponent.unsigned_abs());
+      error.to_string().parse().ok()
+    } else {
+      let error: f64 = error.to_string().parse().ok()?;
+      Some(error / 2f64.powi(exponent))
+    }
+  }
+
+  pub fn table(&self, literal: Option<&str>) -> Table {
+    let mut table = Table::new(vec![
+      Column::new("Property", Align::Left),
+      Column::new("Value", Align::Left),
+    ]);
+    let mut row = |name: &str, value: String| table.push(vec![name.to_owned(), value]);
+    let or_na = |value: Option<String>| value.unwrap_or_else(|| "n/a".to_owned());
+
+    row("value", format!("{}{}", self.shortest(), self.ty));
+    row("class", format!("{:?}", self.category()));
+    row("bits", self.fields());
+    row(
+      "hex",
+      format!(
+        "0x{:0width$x}",
+        self.raw,
+        width = self.ty.bits() as usize / 4
+      ),
+    );
+    row(
+      "sign",
+      format!(
+        "{} ({})",
+        self.raw >> (self.ty.bits() - 1),
+        if self.is_negative() { '-' } else { '+' }
+      ),
+    );
+    row(
+      "exponent",
+      format!("{} (bias {})", self.biased_exponent(), self.ty.bias()),
+    );
+    row(
+      "unbiased exponent",
+      or_na(self.unbiased_exponent().map(|e| e.to_string())),
+    );
+    row("mantissa", format!("0x{:x}", self.mantissa()));
+    row("formula", or_na(self.formula()));
+    row("exact value", or_na(self.exact_decimal()));
+
+    for (name, neighbor) in [("next up", self.next_up()), ("next down", self.next_down())].iter() {
+      let text = neighbor.map(|n| match self.gap(&n) {
+        Some(k) => format!("{} (gap 2^{})", n.shortest(), k),
+        None => n.shortest(),
+      });
+      row(name, or_na(text));
+    }
+
+    // Left out when there is nothing exact to compare
+    if let Some(error) = literal.and_then(|literal| self.rounding_error(literal)) {
+      let error = match literal.and_then(|literal| self.rounding_error_ulps(literal)) {
+        Some(ulps) => format!("{} ({:.3} ULP)", error, ulps),
+        None => error,
+      };
+      row("rounding error", error);
+    }
+    table
+  }
+}
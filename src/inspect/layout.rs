@@ -0,0 +1,407 @@
+/*
+ * Memory layout of types.
+ *
+ * `arrays_and_slices` prints `mem::size_of_val(&xs)`. A `Layout` also keeps
+ * the alignment of a type, the size of `Option<T>` (the same size means the
+ * compiler found a niche, an invalid bit pattern like a null `Box`, to store
+ * `None` in) and where each field lives. Drawn byte by byte, the gaps the
+ * compiler leaves between fields to keep them aligned become visible.
+ *
+ * Struct fields are found with `mem::offset_of!`. Enum variants can't be
+ * named there, so their fields are found by comparing the address of a field
+ * with the address of a sample value.
+ */
+
+use crate::chapters::{c01, c03};
+use crate::format::table::{Align, Column, Table};
+use crate::math::matrix::Matrix;
+use std::any;
+use std::fmt::{self, Display, Formatter};
+use std::mem;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Field {
+  pub name: String,
+  pub ty: String,
+  pub offset: usize,
+  pub size: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Variant {
+  pub name: String,
+  pub fields: Vec<Field>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Layout {
+  pub name: String,
+  pub size: usize,
+  pub align: usize,
+  // Size of `Option<T>`
+  pub option_size: usize,
+  // One unnamed variant for a struct, one per variant for an enum, none for
+  // types whose fields are private or which have none
+  pub variants: Vec<Variant>,
+  is_enum: bool,
+}
+
+// `alloc::boxed::Box<rust_by_example::chapters::c03::List>` -> `Box<List>`
+fn short_type_name(name: &str) -> String {
+  let mut out = String::new();
+  for c in name.chars() {
+    out.push(c);
+    if out.ends_with("::") {
+      out.truncate(out.len() - 2);
+      while out.ends_with(|c: char| c.is_alphanumeric() || c == '_') {
+        out.pop();
+      }
+    }
+  }
+  out
+}
+
+// `1 byte`, `4 bytes`
+fn bytes(count: usize) -> String {
+  if count == 1 {
+    "1 byte".to_owned()
+  } else {
+    format!("{} bytes", count)
+  }
+}
+
+// The size of the field a closure like `|p: &Point| &p.x` points to
+pub fn field_size<T, U, F: Fn(&T) -> &U>(_: F) -> (usize, String) {
+  (mem::size_of::<U>(), short_type_name(any::type_name::<U>()))
+}
+
+// Where `field` starts inside `value`
+pub fn offset_in<T, U>(value: &T, field: &U) -> usize {
+  field as *const U as usize - value as *const T as usize
+}
+
+impl Field {
+  // A field of a struct, from `mem::offset_of!` and `field_size`
+  pub fn new(name: &str, offset: usize, (size, ty): (usize, String)) -> Field {
+    Field {
+      name: name.to_owned(),
+      ty,
+      offset,
+      size,
+    }
+  }
+
+  // A field of an enum variant, found inside a sample value
+  pub fn at<T, U>(name: &str, value: &T, field: &U) -> Field {
+    Field {
+      name: name.to_owned(),
+      ty: short_type_name(any::type_name::<U>()),
+      offset: offset_in(value, field),
+      size: mem::size_of::<U>(),
+    }
+  }
+}
+
+// The fields of a struct: `fields!(Point { x, y })`, `fields!(Pair { 0, 1 })`
+macro_rules! fields {
+  ($t:ty { $($field:tt),* }) => {
+    vec![$(
+      Field::new(
+        stringify!($field),
+        mem::offset_of!($t, $field),
+        field_size(|v: &$t| &v.$field),
+      )
+    ),*]
+  };
+}
+
+impl Layout {
+  pub fn of<T>(name: &str) -> Layout {
+    Layout {
+      name: name.to_owned(),
+      size: mem::size_of::<T>(),
+      align: mem::align_of::<T>(),
+      option_size: mem::size_of::<Option<T>>(),
+      variants: Vec::new(),
+      is_enum: false,
+    }
+  }
+
+  pub fn fields(mut self, fields: Vec<Field>) -> Self {
+    self.variants = vec![Variant {
+      name: self.name.clone(),
+      fields,
+    }];
+    self.is_enum = false;
+    self
+  }
+
+  pub fn variant(mut self, name: &str, fields: Vec<Field>) -> Self {
+    if !self.is_enum {
+      self.variants.clear();
+      self.is_enum = true;
+    }
+    self.variants.push(Variant {
+      name: name.to_owned(),
+      fields,
+    });
+    self
+  }
+
+  pub fn is_enum(&self) -> bool {
+    self.is_enum
+  }
+
+  // Whether `None` is stored in a bit pattern `T` never uses
+  pub fn has_niche(&self) -> bool {
+    self.option_size == self.size
+  }
+
+  // Bytes no field of a struct covers
+  pub fn padding(&self) -> Option<usize> {
+    match self.variants.as_slice() {
+      [only] if !self.is_enum => {
+        Some(self.size - only.fields.iter().map(|f| f.size).sum::<usize>())
+      }
+      _ => None,
+    }
+  }
+
+  // One letter per byte and one line per variant, with the fields listed
+  // below. Bytes outside every field are padding, or for an enum the tag.
+  pub fn diagram(&self) -> String {
+    let gap = if self.is_enum {
+      "tag or padding"
+    } else {
+      "padding"
+    };
+    let label_width = self
+      .variants
+      .iter()
+      .map(|v| v.name.chars().count())
+      .max()
+      .unwrap_or(0)
+      .max(self.name.chars().count());
+
+    let mut out = String::new();
+    let mut ruler = String::new();
+    for group in (0..self.size).step_by(8) {
+      ruler.push_str(&format!("{:<9}", group));
+    }
+    out.push_str(&format!(
+      "{:w$}  {}\n",
+      "",
+      ruler.trim_end(),
+      w = label_width
+    ));
+
+    let mut legend = String::new();
+    let mut letters = (b'a'..=b'z').chain(b'A'..=b'Z').map(char::from).cycle();
+    let mut row = |name: &str, cells: Vec<char>| {
+      let bytes: Vec<String> = cells
+        .chunks(8)
+        .map(|group| group.iter().collect())
+        .collect();
+      out.push_str(&format!(
+        "{:w$}  {}\n",
+        name,
+        bytes.join(" "),
+        w = label_width
+      ));
+    };
+
+    if self.variants.is_empty() {
+      row(&self.name, vec!['#'; self.size]);
+      legend.push_str(&format!("  # = the whole value, {}\n", bytes(self.size)));
+    }
+    for variant in &self.variants {
+      let mut cells = vec!['.'; self.size];
+      for field in &variant.fields {
+        let letter = letters.next().unwrap_or('?');
+        for cell in &mut cells[field.offset..field.offset + field.size] {
+          *cell = letter;
+        }
+        let name = if self.is_enum {
+          format!("{}.{}", variant.name, field.name)
+        } else {
+          field.name.clone()
+        };
+        legend.push_str(&format!(
+          "  {} = {}: {}, offset {}, {}\n",
+          letter,
+          name,
+          field.ty,
+          field.offset,
+          bytes(field.size)
+        ));
+      }
+      row(&variant.name, cells);
+    }
+
+    out.push_str(&legend);
+    if !self.variants.is_empty() {
+      out.push_str(&format!("  . = {}", gap));
+      if let Some(padding) = self.padding() {
+        out.push_str(&format!(", {}", bytes(padding)));
+      }
+      out.push('\n');
+    }
+    out
+  }
+}
+
+impl Display for Layout {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    writeln!(
+      f,
+      "{}: {}, align {}, Option<{}>: {} ({})",
+      self.name,
+      bytes(self.size),
+      self.align,
+      self.name,
+      bytes(self.option_size),
+      if self.has_niche() {
+        "niche"
+      } else {
+        "no niche"
+      }
+    )?;
+    write!(f, "{}", self.diagram().trim_end())
+  }
+}
+
+pub fn primitives() -> Vec<Layout> {
+  vec![
+    Layout::of::<i8>("i8"),
+    Layout::of::<i16>("i16"),
+    Layout::of::<i32>("i32"),
+    Layout::of::<i64>("i64"),
+    Layout::of::<i128>("i128"),
+    Layout::of::<isize>("isize"),
+    Layout::of::<u8>("u8"),
+    Layout::of::<u16>("u16"),
+    Layout::of::<u32>("u32"),
+    Layout::of::<u64>("u64"),
+    Layout::of::<u128>("u128"),
+    Layout::of::<usize>("usize"),
+    Layout::of::<f32>("f32"),
+    Layout::of::<f64>("f64"),
+    Layout::of::<char>("char"),
+    Layout::of::<bool>("bool"),
+    Layout::of::<()>("()"),
+    Layout::of::<[i32; 5]>("[i32; 5]"),
+    Layout::of::<&u8>("&u8"),
+    Layout::of::<&str>("&str"),
+    Layout::of::<&[i32]>("&[i32]"),
+    Layout::of::<String>("String"),
+    Layout::of::<Box<c03::List>>("Box<List>"),
+  ]
+}
+
+fn web_event() -> Layout {
+  use c03::WebEvent;
+
+  let key = WebEvent::KeyPress('x');
+  let paste = WebEvent::Paste("my text".to_owned());
+  let click = WebEvent::Click { x: 20, y: 80 };
+
+  let mut layout = Layout::of::<WebEvent>("WebEvent")
+    .variant("PageLoad", vec![])
+    .variant("PageUnload", vec![]);
+  if let WebEvent::KeyPress(c) = &key {
+    layout = layout.variant("KeyPress", vec![Field::at("0", &key, c)]);
+  }
+  if let WebEvent::Paste(s) = &paste {
+    layout = layout.variant("Paste", vec![Field::at("0", &paste, s)]);
+  }
+  if let WebEvent::Click { x, y } = &click {
+    layout = layout.variant(
+      "Click",
+      vec![Field::at("x", &click, x), Field::at("y", &click, y)],
+    );
+  }
+  layout
+}
+
+fn list() -> Layout {
+  use c03::List;
+
+  let cons = List::Cons(1, Box::new(List::Nil));
+  let mut layout = Layout::of::<List>("List");
+  if let List::Cons(head, tail) = &cons {
+    layout = layout.variant(
+      "Cons",
+      vec![Field::at("0", &cons, head), Field::at("1", &cons, tail)],
+    );
+  }
+  layout.variant("Nil", vec![])
+}
+
+fn color() -> Layout {
+  use c03::Color;
+
+  let mut layout = Layout::of::<Color>("c03::Color");
+  for &(name, color) in [
+    ("Red", Color::Red),
+    ("Green", Color::Green),
+    ("Blue", Color::Blue),
+  ]
+  .iter()
+  {
+    let name = format!("{} = {:#08x}", name, color as i32);
+    layout = layout.variant(&name, vec![]);
+  }
+  layout
+}
+
+// Every custom type defined outside a function in the chapters
+pub fn custom_types() -> Vec<Layout> {
+  vec![
+    Layout::of::<c01::City>("City").fields(fields!(c01::City { name, lat, lon })),
+    Layout::of::<c01::Color>("c01::Color").fields(fields!(c01::Color { red, green, blue })),
+    // The entries of a `Matrix` are private, it is drawn as a whole
+    Layout::of::<Matrix<f32, 2, 2>>("Matrix<f32, 2, 2>"),
+    Layout::of::<c03::Person>("Person").fields(fields!(c03::Person<'static> { name, age })),
+    Layout::of::<c03::Pair>("Pair").fields(fields!(c03::Pair { 0, 1 })),
+    Layout::of::<c03::Point>("Point").fields(fields!(c03::Point { x, y })),
+    Layout::of::<c03::Rectangle>("Rectangle").fields(fields!(c03::Rectangle {
+      top_left,
+      bottom_right
+    })),
+    web_event(),
+    list(),
+    color(),
+  ]
+}
+
+// Look a type up by the name it is listed under
+pub fn find(name: &str) -> Option<Layout> {
+  primitives()
+    .into_iter()
+    .chain(custom_types())
+    .find(|layout| layout.name == name)
+}
+
+pub fn summary(layouts: &[Layout]) -> Table {
+  let mut table = Table::new(vec![
+    Column::new("Type", Align::Left),
+    Column::new("Size", Align::Right),
+    Column::new("Align", Align::Right),
+    Column::new("Padding", Align::Right),
+    Column::new("Option<T>", Align::Right),
+    Column::new("Niche", Align::Center),
+  ]);
+  for layout in layouts {
+    table.push(vec![
+      layout.name.clone(),
+      layout.size.to_string(),
+      layout.align.to_string(),
+      layout
+        .padding()
+        .map_or_else(|| "-".to_owned(), |p| p.to_string()),
+      layout.option_size.to_string(),
+      if layout.has_niche() { "yes" } else { "no" }.to_owned(),
+    ]);
+  }
+  table
+}
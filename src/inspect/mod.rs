@@ -0,0 +1,7 @@
+pub mod bits;
+pub mod float;
+pub mod layout;
+pub mod overflow;
+pub mod slices;
+pub mod sorting;
+pub mod unicode;
@@ -0,0 +1,484 @@
+/*
+ * Integer overflow explorer.
+ *
+ * `1i32 - 2` is `-1`, but `1u32 - 2` has no answer in `u32`: a debug build
+ * panics with "attempt to subtract with overflow" and a release build wraps
+ * around to `4294967295`. The standard library offers a method for each way
+ * of dealing with it:
+ *
+ *  - checked: `checked_sub` returns `None`
+ *  - wrapping: `wrapping_sub` wraps around, like a release build
+ *  - saturating: `saturating_sub` clamps to the bounds of the type
+ *  - overflowing: `overflowing_sub` wraps, and tells whether it did
+ *
+ * `explore` computes all of them, for any operator and any integer type.
+ */
+
+use crate::format::table::{Align, Column, Table};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IntType {
+  I8,
+  I16,
+  I32,
+  I64,
+  I128,
+  Isize,
+  U8,
+  U16,
+  U32,
+  U64,
+  U128,
+  Usize,
+}
+
+impl IntType {
+  pub const ALL: [IntType; 12] = [
+    IntType::I8,
+    IntType::I16,
+    IntType::I32,
+    IntType::I64,
+    IntType::I128,
+    IntType::Isize,
+    IntType::U8,
+    IntType::U16,
+    IntType::U32,
+    IntType::U64,
+    IntType::U128,
+    IntType::Usize,
+  ];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      IntType::I8 => "i8",
+      IntType::I16 => "i16",
+      IntType::I32 => "i32",
+      IntType::I64 => "i64",
+      IntType::I128 => "i128",
+      IntType::Isize => "isize",
+      IntType::U8 => "u8",
+      IntType::U16 => "u16",
+      IntType::U32 => "u32",
+      IntType::U64 => "u64",
+      IntType::U128 => "u128",
+      IntType::Usize => "usize",
+    }
+  }
+
+  pub fn bits(self) -> u32 {
+    match self {
+      IntType::I8 | IntType::U8 => 8,
+      IntType::I16 | IntType::U16 => 16,
+      IntType::I32 | IntType::U32 => 32,
+      IntType::I64 | IntType::U64 => 64,
+      IntType::I128 | IntType::U128 => 128,
+      IntType::Isize | IntType::Usize => usize::BITS,
+    }
+  }
+
+  pub fn is_signed(self) -> bool {
+    matches!(
+      self,
+      IntType::I8 | IntType::I16 | IntType::I32 | IntType::I64 | IntType::I128 | IntType::Isize
+    )
+  }
+}
+
+impl Display for IntType {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.name())
+  }
+}
+
+impl FromStr for IntType {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<IntType, Error> {
+    IntType::ALL
+      .iter()
+      .copied()
+      .find(|ty| ty.name() == s)
+      .ok_or_else(|| Error::UnknownType(s.to_owned()))
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Op {
+  Add,
+  Sub,
+  Mul,
+  Div,
+  Rem,
+  Shl,
+  Shr,
+}
+
+impl Op {
+  pub fn symbol(self) -> &'static str {
+    match self {
+      Op::Add => "+",
+      Op::Sub => "-",
+      Op::Mul => "*",
+      Op::Div => "/",
+      Op::Rem => "%",
+      Op::Shl => "<<",
+      Op::Shr => ">>",
+    }
+  }
+
+  // The suffix of the `checked_*`, `wrapping_*`, ... methods
+  pub fn method(self) -> &'static str {
+    match self {
+      Op::Add => "add",
+      Op::Sub => "sub",
+      Op::Mul => "mul",
+      Op::Div => "div",
+      Op::Rem => "rem",
+      Op::Shl => "shl",
+      Op::Shr => "shr",
+    }
+  }
+}
+
+impl Display for Op {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.symbol())
+  }
+}
+
+impl FromStr for Op {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Op, Error> {
+    match s {
+      "+" => Ok(Op::Add),
+      "-" => Ok(Op::Sub),
+      "*" | "x" => Ok(Op::Mul),
+      "/" => Ok(Op::Div),
+      "%" => Ok(Op::Rem),
+      "<<" => Ok(Op::Shl),
+      ">>" => Ok(Op::Shr),
+      _ => Err(Error::UnknownOperator(s.to_owned())),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  UnknownType(String),
+  UnknownOperator(String),
+  // The operand doesn't fit in the type, e.g. `300` for `u8`
+  InvalidOperand { ty: IntType, text: String },
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::UnknownType(name) => write!(f, "unknown integer type `{}`", name),
+      Error::UnknownOperator(op) => write!(
+        f,
+        "unknown operator `{}`, expected one of + - * / % << >>",
+        op
+      ),
+      Error::InvalidOperand { ty, text } => write!(f, "`{}` is not a valid {}", text, ty),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Outcome {
+  Value(String),
+  // A `checked_*` method returned `None`
+  Overflow,
+  // The operation panics with this message
+  Panic(&'static str),
+  // There is no such method, e.g. `saturating_rem`
+  Unsupported,
+}
+
+impl Display for Outcome {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Outcome::Value(value) => write!(f, "{}", value),
+      Outcome::Overflow => write!(f, "None"),
+      Outcome::Panic(message) => write!(f, "panic: {}", message),
+      Outcome::Unsupported => write!(f, "n/a"),
+    }
+  }
+}
+
+// The names of the fields of `Outcomes`, in order
+const MODES: [&str; 6] = [
+  "checked",
+  "wrapping",
+  "saturating",
+  "overflowing",
+  "debug build",
+  "release build",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Outcomes {
+  pub checked: Outcome,
+  pub wrapping: Outcome,
+  pub saturating: Outcome,
+  pub overflowing: Outcome,
+  // The bare operator in a debug build, where overflow checks are enabled
+  pub debug: Outcome,
+  // The bare operator in a release build
+  pub release: Outcome,
+}
+
+impl Outcomes {
+  fn new<T: Display>(
+    checked: Option<T>,
+    wrapping: T,
+    saturating: Option<T>,
+    overflowing: (T, bool),
+    message: &'static str,
+    // Division overflow panics even without overflow checks
+    always_panics: bool,
+  ) -> Outcomes {
+    let (debug, release) = match &checked {
+      Some(value) => (
+        Outcome::Value(value.to_string()),
+        Outcome::Value(value.to_string()),
+      ),
+      None if always_panics => (Outcome::Panic(message), Outcome::Panic(message)),
+      None => (
+        Outcome::Panic(message),
+        Outcome::Value(wrapping.to_string()),
+      ),
+    };
+
+    Outcomes {
+      checked: match checked {
+        Some(value) => Outcome::Value(format!("Some({})", value)),
+        None => Outcome::Overflow,
+      },
+      wrapping: Outcome::Value(wrapping.to_string()),
+      saturating: match saturating {
+        Some(value) => Outcome::Value(value.to_string()),
+        None => Outcome::Unsupported,
+      },
+      overflowing: Outcome::Value(format!("({}, {})", overflowing.0, overflowing.1)),
+      debug,
+      release,
+    }
+  }
+
+  // Only `checked_*` survives a division by zero
+  fn division_by_zero(message: &'static str, saturating: bool) -> Outcomes {
+    Outcomes {
+      checked: Outcome::Overflow,
+      wrapping: Outcome::Panic(message),
+      saturating: if saturating {
+        Outcome::Panic(message)
+      } else {
+        Outcome::Unsupported
+      },
+      overflowing: Outcome::Panic(message),
+      debug: Outcome::Panic(message),
+      release: Outcome::Panic(message),
+    }
+  }
+
+  fn all(&self) -> [&Outcome; 6] {
+    [
+      &self.checked,
+      &self.wrapping,
+      &self.saturating,
+      &self.overflowing,
+      &self.debug,
+      &self.release,
+    ]
+  }
+}
+
+fn parse_operand<T: FromStr>(ty: IntType, text: &str) -> Result<T, Error> {
+  // Allow the same underscores as in literals: `1_000`
+  text
+    .replace('_', "")
+    .parse()
+    .map_err(|_| Error::InvalidOperand {
+      ty,
+      text: text.to_owned(),
+    })
+}
+
+macro_rules! outcomes_for {
+  ($t:ty, $ty:expr, $lhs:expr, $op:expr, $rhs:expr) => {{
+    let lhs: $t = parse_operand($ty, $lhs)?;
+    match $op {
+      // The right hand side of a shift is always a `u32`
+      Op::Shl => {
+        let rhs: u32 = parse_operand(IntType::U32, $rhs)?;
+        Outcomes::new(
+          lhs.checked_shl(rhs),
+          lhs.wrapping_shl(rhs),
+          None,
+          lhs.overflowing_shl(rhs),
+          "attempt to shift left with overflow",
+          false,
+        )
+      }
+      Op::Shr => {
+        let rhs: u32 = parse_operand(IntType::U32, $rhs)?;
+        Outcomes::new(
+          lhs.checked_shr(rhs),
+          lhs.wrapping_shr(rhs),
+          None,
+          lhs.overflowing_shr(rhs),
+          "attempt to shift right with overflow",
+          false,
+        )
+      }
+      op => {
+        let rhs: $t = parse_operand($ty, $rhs)?;
+        match op {
+          Op::Add => Outcomes::new(
+            lhs.checked_add(rhs),
+            lhs.wrapping_add(rhs),
+            Some(lhs.saturating_add(rhs)),
+            lhs.overflowing_add(rhs),
+            "attempt to add with overflow",
+            false,
+          ),
+          Op::Sub => Outcomes::new(
+            lhs.checked_sub(rhs),
+            lhs.wrapping_sub(rhs),
+            Some(lhs.saturating_sub(rhs)),
+            lhs.overflowing_sub(rhs),
+            "attempt to subtract with overflow",
+            false,
+          ),
+          Op::Mul => Outcomes::new(
+            lhs.checked_mul(rhs),
+            lhs.wrapping_mul(rhs),
+            Some(lhs.saturating_mul(rhs)),
+            lhs.overflowing_mul(rhs),
+            "attempt to multiply with overflow",
+            false,
+          ),
+          Op::Div if rhs == 0 => Outcomes::division_by_zero("attempt to divide by zero", true),
+          Op::Div => Outcomes::new(
+            lhs.checked_div(rhs),
+            lhs.wrapping_div(rhs),
+            Some(lhs.saturating_div(rhs)),
+            lhs.overflowing_div(rhs),
+            "attempt to divide with overflow",
+            true,
+          ),
+          Op::Rem if rhs == 0 => Outcomes::division_by_zero(
+            "attempt to calculate the remainder with a divisor of zero",
+            false,
+          ),
+          Op::Rem => Outcomes::new(
+            lhs.checked_rem(rhs),
+            lhs.wrapping_rem(rhs),
+            None,
+            lhs.overflowing_rem(rhs),
+            "attempt to calculate the remainder with overflow",
+            true,
+          ),
+          Op::Shl | Op::Shr => unreachable!("shifts are handled above"),
+        }
+      }
+    }
+  }};
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Report {
+  pub ty: IntType,
+  pub lhs: String,
+  pub op: Op,
+  pub rhs: String,
+  pub outcomes: Outcomes,
+}
+
+// Compute `lhs op rhs` as `ty` in every overflow mode
+pub fn explore(ty: IntType, lhs: &str, op: Op, rhs: &str) -> Result<Report, Error> {
+  let outcomes = match ty {
+    IntType::I8 => outcomes_for!(i8, ty, lhs, op, rhs),
+    IntType::I16 => outcomes_for!(i16, ty, lhs, op, rhs),
+    IntType::I32 => outcomes_for!(i32, ty, lhs, op, rhs),
+    IntType::I64 => outcomes_for!(i64, ty, lhs, op, rhs),
+    IntType::I128 => outcomes_for!(i128, ty, lhs, op, rhs),
+    IntType::Isize => outcomes_for!(isize, ty, lhs, op, rhs),
+    IntType::U8 => outcomes_for!(u8, ty, lhs, op, rhs),
+    IntType::U16 => outcomes_for!(u16, ty, lhs, op, rhs),
+    IntType::U32 => outcomes_for!(u32, ty, lhs, op, rhs),
+    IntType::U64 => outcomes_for!(u64, ty, lhs, op, rhs),
+    IntType::U128 => outcomes_for!(u128, ty, lhs, op, rhs),
+    IntType::Usize => outcomes_for!(usize, ty, lhs, op, rhs),
+  };
+
+  Ok(Report {
+    ty,
+    lhs: lhs.to_owned(),
+    op,
+    rhs: rhs.to_owned(),
+    outcomes,
+  })
+}
+
+impl Report {
+  pub fn table(&self) -> Table {
+    let mut table = Table::new(vec![
+      Column::new("Mode", Align::Left),
+      Column::new("Expression", Align::Left),
+      Column::new("Result", Align::Right),
+    ]);
+    let lhs = format!("{}{}", self.lhs, self.ty);
+    // A method call binds tighter than the unary minus
+    let receiver = if self.lhs.starts_with('-') {
+      format!("({})", lhs)
+    } else {
+      lhs.clone()
+    };
+
+    for (mode, outcome) in MODES.iter().zip(self.outcomes.all().iter()) {
+      let expression = if mode.ends_with("build") {
+        format!("{} {} {}", lhs, self.op, self.rhs)
+      } else {
+        format!("{}.{}_{}({})", receiver, mode, self.op.method(), self.rhs)
+      };
+      table.push(vec![mode.to_string(), expression, outcome.to_string()]);
+    }
+    table
+  }
+}
+
+impl Display for Report {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    writeln!(f, "{} {} {} as {}", self.lhs, self.op, self.rhs, self.ty)?;
+    write!(f, "{}", self.table())
+  }
+}
+
+// `lhs op rhs` for every integer type, one row per type
+pub fn comparison(lhs: &str, op: Op, rhs: &str) -> Table {
+  let mut columns = vec![Column::new("Type", Align::Left)];
+  for mode in MODES.iter() {
+    columns.push(Column::new(mode, Align::Right));
+  }
+  let mut table = Table::new(columns);
+
+  for &ty in IntType::ALL.iter() {
+    let mut row = vec![ty.to_string()];
+    match explore(ty, lhs, op, rhs) {
+      Ok(report) => {
+        for outcome in report.outcomes.all().iter() {
+          row.push(outcome.to_string());
+        }
+      }
+      // The remaining cells are left empty
+      Err(error) => row.push(error.to_string()),
+    }
+    table.push(row);
+  }
+  table
+}
@@ -0,0 +1,293 @@
+/*
+ * Slice explorer.
+ *
+ * A slice is a pointer to its first item and a length, so every slice taken
+ * from an array points somewhere inside of it. `Explorer` calls the slice
+ * methods on an array, and works out from the pointer of every slice that
+ * comes back which items it covers:
+ *
+ *    xs.chunks(2)
+ *    index   0  1  2  3  4
+ *    xs      1  2  3  4  5
+ *           └───┘            #0  xs[0..2]  ptr +0 bytes, len 2
+ *                 └───┘      #1  xs[2..4]  ptr +8 bytes, len 2
+ *                       └┘   #2  xs[4..5]  ptr +16 bytes, len 1
+ *
+ * The standard library panics on a zero chunk size or an index past the end.
+ * The explorer checks first, and returns an `Error` instead.
+ */
+
+use crate::format::table::{pad, Align};
+use crate::format::width::str_width;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::mem;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  // The size given to `windows`, `chunks` and friends
+  ZeroSize { method: &'static str },
+  OutOfBounds { index: usize, len: usize },
+  StartAfterEnd { start: usize, end: usize },
+  // Every item of a slice of `()` has the same address
+  ZeroSizedType,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::ZeroSize { method } => write!(f, "{} size must be non-zero", method),
+      Error::OutOfBounds { index, len } => write!(
+        f,
+        "index {} is out of range for a slice of length {}",
+        index, len
+      ),
+      Error::StartAfterEnd { start, end } => {
+        write!(f, "slice index starts at {} but ends at {}", start, end)
+      }
+      Error::ZeroSizedType => write!(
+        f,
+        "the items take no space, so the pointers can't tell them apart"
+      ),
+    }
+  }
+}
+
+// One of the slices returned by a method
+#[derive(Clone, Debug, PartialEq)]
+pub struct Piece {
+  pub label: String,
+  // The index of the first item, and the number of items
+  pub start: usize,
+  pub len: usize,
+  // How far its pointer is from the pointer of the array
+  pub offset: usize,
+}
+
+// The slices returned by one method call, drawn under the array
+#[derive(Clone, Debug, PartialEq)]
+pub struct View {
+  pub call: String,
+  pub name: String,
+  pub cells: Vec<String>,
+  pub pieces: Vec<Piece>,
+  // The items that matched the predicate of `split`
+  pub separators: Vec<usize>,
+}
+
+pub struct Explorer<'a, T> {
+  name: String,
+  items: &'a [T],
+}
+
+impl<'a, T: Debug> Explorer<'a, T> {
+  pub fn new(name: &str, items: &'a [T]) -> Result<Explorer<'a, T>, Error> {
+    if mem::size_of::<T>() == 0 {
+      return Err(Error::ZeroSizedType);
+    }
+    Ok(Explorer {
+      name: name.to_owned(),
+      items,
+    })
+  }
+
+  // Where `part` is in the array, from its pointer
+  fn piece(&self, label: String, part: &[T]) -> Piece {
+    let offset = part.as_ptr() as usize - self.items.as_ptr() as usize;
+    Piece {
+      label,
+      start: offset / mem::size_of::<T>(),
+      len: part.len(),
+      offset,
+    }
+  }
+
+  // The pieces for `parts`, numbered in the order they came
+  fn numbered<'p, I: Iterator<Item = &'p [T]>>(&self, parts: I) -> Vec<Piece>
+  where
+    T: 'p,
+  {
+    parts
+      .enumerate()
+      .map(|(i, part)| self.piece(format!("#{}", i), part))
+      .collect()
+  }
+
+  fn view(&self, call: String, pieces: Vec<Piece>) -> View {
+    View {
+      call,
+      name: self.name.clone(),
+      cells: self
+        .items
+        .iter()
+        .map(|item| format!("{:?}", item))
+        .collect(),
+      pieces,
+      separators: Vec::new(),
+    }
+  }
+
+  fn check_size(method: &'static str, size: usize) -> Result<(), Error> {
+    if size == 0 {
+      return Err(Error::ZeroSize { method });
+    }
+    Ok(())
+  }
+
+  // Every run of `size` consecutive items, overlapping
+  pub fn windows(&self, size: usize) -> Result<View, Error> {
+    Self::check_size("window", size)?;
+    let pieces = self.numbered(self.items.windows(size));
+    Ok(self.view(format!("{}.windows({})", self.name, size), pieces))
+  }
+
+  // Runs of `size` items that don't overlap, the last one may be shorter
+  pub fn chunks(&self, size: usize) -> Result<View, Error> {
+    Self::check_size("chunk", size)?;
+    let pieces = self.numbered(self.items.chunks(size));
+    Ok(self.view(format!("{}.chunks({})", self.name, size), pieces))
+  }
+
+  // Like `chunks`, but the items that don't fill a chunk are left over
+  pub fn chunks_exact(&self, size: usize) -> Result<View, Error> {
+    Self::check_size("chunk", size)?;
+    let chunks = self.items.chunks_exact(size);
+    let remainder = chunks.remainder();
+    let mut pieces = self.numbered(chunks);
+    if !remainder.is_empty() {
+      pieces.push(self.piece("remainder".to_owned(), remainder));
+    }
+    Ok(self.view(format!("{}.chunks_exact({})", self.name, size), pieces))
+  }
+
+  // Like `chunks`, starting from the end, so the first one may be shorter
+  pub fn rchunks(&self, size: usize) -> Result<View, Error> {
+    Self::check_size("chunk", size)?;
+    let pieces = self.numbered(self.items.rchunks(size));
+    Ok(self.view(format!("{}.rchunks({})", self.name, size), pieces))
+  }
+
+  // The items before `mid`, and the rest
+  pub fn split_at(&self, mid: usize) -> Result<View, Error> {
+    if mid > self.items.len() {
+      return Err(Error::OutOfBounds {
+        index: mid,
+        len: self.items.len(),
+      });
+    }
+    let (left, right) = self.items.split_at(mid);
+    let pieces = vec![
+      self.piece("left".to_owned(), left),
+      self.piece("right".to_owned(), right),
+    ];
+    Ok(self.view(format!("{}.split_at({})", self.name, mid), pieces))
+  }
+
+  // The runs of items between those that match `pred`, which is described
+  // by `description` since a closure can't be printed
+  pub fn split<F: FnMut(&T) -> bool>(&self, description: &str, pred: F) -> View {
+    let pieces = self.numbered(self.items.split(pred));
+    // Each separator is the item right after a piece, except the last one
+    let separators = pieces[..pieces.len() - 1]
+      .iter()
+      .map(|piece| piece.start + piece.len)
+      .collect();
+    let mut view = self.view(format!("{}.split({})", self.name, description), pieces);
+    view.separators = separators;
+    view
+  }
+
+  // `&xs[start..end]`
+  pub fn range(&self, start: usize, end: usize) -> Result<View, Error> {
+    let part = match self.items.get(start..end) {
+      Some(part) => part,
+      None if end > self.items.len() => {
+        return Err(Error::OutOfBounds {
+          index: end,
+          len: self.items.len(),
+        })
+      }
+      None => return Err(Error::StartAfterEnd { start, end }),
+    };
+    let call = format!("&{}[{}..{}]", self.name, start, end);
+    let pieces = vec![self.piece("slice".to_owned(), part)];
+    Ok(self.view(call, pieces))
+  }
+}
+
+impl Display for View {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let label = str_width(&self.name).max("index".len());
+    // Every cell is as wide as the widest value or index, plus a space on
+    // each side
+    let width = self
+      .cells
+      .iter()
+      .map(|cell| str_width(cell))
+      .chain(Some(self.cells.len().to_string().len()))
+      .max()
+      .unwrap_or(0)
+      .max(1)
+      + 2;
+    // The column where item `i` starts
+    let column = |i: usize| label + 2 + i * width;
+    // The column of the notes on the right of the brackets
+    let notes = column(self.cells.len()) + 2;
+    let row = |name: &str, cells: Vec<String>| {
+      let cells: Vec<String> = cells
+        .iter()
+        .map(|c| format!("{} ", pad(c, width - 1, Align::Right)))
+        .collect();
+      let line = format!("{}  {}", pad(name, label, Align::Left), cells.join(""));
+      line.trim_end().to_owned()
+    };
+
+    writeln!(f, "{}", self.call)?;
+    let indices = (0..self.cells.len()).map(|i| i.to_string()).collect();
+    writeln!(f, "{}", row("index", indices))?;
+    write!(f, "{}", row(&self.name, self.cells.clone()))?;
+    if !self.separators.is_empty() {
+      let mut marks = vec![String::new(); self.cells.len()];
+      for &i in &self.separators {
+        marks[i] = "^".to_owned();
+      }
+      write!(
+        f,
+        "\n{}separators",
+        pad(&row("", marks), notes, Align::Left)
+      )?;
+    }
+
+    let names: Vec<String> = self
+      .pieces
+      .iter()
+      .map(|p| format!("{}[{}..{}]", self.name, p.start, p.start + p.len))
+      .collect();
+    let name_width = names.iter().map(|n| str_width(n)).max().unwrap_or(0);
+    let label_width = self.pieces.iter().map(|p| p.label.len()).max().unwrap_or(0);
+    for (piece, name) in self.pieces.iter().zip(&names) {
+      // The bracket leaves out the space after the last item, so that
+      // adjacent pieces stay apart. An empty piece is a mark before an item.
+      let start = column(piece.start);
+      let bracket = if piece.len == 0 {
+        "┆".to_owned()
+      } else {
+        let inner = piece.len * width - 3;
+        format!("└{}┘", "─".repeat(inner))
+      };
+      let line = format!("{}{}", " ".repeat(start), bracket);
+      write!(
+        f,
+        "\n{}{}  {}  ptr +{} bytes, len {}",
+        pad(&line, notes, Align::Left),
+        pad(&piece.label, label_width, Align::Left),
+        pad(name, name_width, Align::Left),
+        piece.offset,
+        piece.len
+      )?;
+    }
+    if self.pieces.is_empty() {
+      write!(f, "\nno slices")?;
+    }
+    Ok(())
+  }
+}
@@ -0,0 +1,554 @@
+/*
+ * Sorting and searching, step by step.
+ *
+ * Every algorithm here sorts a `&mut [T]` in place and reports what it does
+ * to a `Trace`, one `Event` at a time:
+ *
+ *  - `Compare(i, j)`: `xs[i]` was compared with `xs[j]`
+ *  - `Swap(i, j)`: `xs[i]` and `xs[j]` were swapped
+ *  - `Set(i, value)`: merge sort wrote `value` back into `xs[i]`
+ *  - `Probe { low, mid, high }`: binary search looked at `xs[mid]`, the
+ *    target being somewhere in `xs[low..high]`
+ *
+ * `()` ignores the events, a `Vec<Event<T>>` keeps them. `replay` applies
+ * them one by one to a copy of the input, which gives a frame for every
+ * step, and `to_json` turns them into JSON for other tools.
+ */
+
+use crate::format::json::{ToJson, Value};
+use crate::format::table::{pad, Align};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Algorithm {
+  Bubble,
+  Insertion,
+  Merge,
+  Quick,
+  Heap,
+}
+
+impl Algorithm {
+  pub const ALL: [Algorithm; 5] = [
+    Algorithm::Bubble,
+    Algorithm::Insertion,
+    Algorithm::Merge,
+    Algorithm::Quick,
+    Algorithm::Heap,
+  ];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      Algorithm::Bubble => "bubble",
+      Algorithm::Insertion => "insertion",
+      Algorithm::Merge => "merge",
+      Algorithm::Quick => "quick",
+      Algorithm::Heap => "heap",
+    }
+  }
+
+  pub fn sort<T: Ord + Clone, R: Trace<T>>(self, items: &mut [T], trace: &mut R) {
+    let len = items.len();
+    let mut tracked = Tracked { items, trace };
+    match self {
+      Algorithm::Bubble => bubble(&mut tracked),
+      Algorithm::Insertion => insertion(&mut tracked),
+      Algorithm::Merge => merge(&mut tracked, 0, len),
+      Algorithm::Quick => quick(&mut tracked, 0, len),
+      Algorithm::Heap => heap(&mut tracked),
+    }
+  }
+}
+
+impl Display for Algorithm {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    f.pad(self.name())
+  }
+}
+
+impl FromStr for Algorithm {
+  type Err = String;
+
+  fn from_str(s: &str) -> Result<Algorithm, String> {
+    Algorithm::ALL
+      .iter()
+      .copied()
+      .find(|algorithm| algorithm.name() == s)
+      .ok_or_else(|| {
+        let names: Vec<&str> = Algorithm::ALL.iter().map(|a| a.name()).collect();
+        format!(
+          "unknown algorithm `{}`, expected one of: {}",
+          s,
+          names.join(", ")
+        )
+      })
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<T> {
+  Compare(usize, usize),
+  Swap(usize, usize),
+  Set(usize, T),
+  Probe { low: usize, mid: usize, high: usize },
+}
+
+// Where the events go
+pub trait Trace<T> {
+  fn record(&mut self, event: Event<T>);
+}
+
+impl<T> Trace<T> for () {
+  fn record(&mut self, _event: Event<T>) {}
+}
+
+impl<T> Trace<T> for Vec<Event<T>> {
+  fn record(&mut self, event: Event<T>) {
+    self.push(event);
+  }
+}
+
+// The items being sorted, which record every operation on them
+struct Tracked<'a, T, R> {
+  items: &'a mut [T],
+  trace: &'a mut R,
+}
+
+impl<'a, T: Ord + Clone, R: Trace<T>> Tracked<'a, T, R> {
+  fn less(&mut self, i: usize, j: usize) -> bool {
+    self.trace.record(Event::Compare(i, j));
+    self.items[i] < self.items[j]
+  }
+
+  fn swap(&mut self, i: usize, j: usize) {
+    self.trace.record(Event::Swap(i, j));
+    self.items.swap(i, j);
+  }
+
+  fn set(&mut self, i: usize, value: T) {
+    self.trace.record(Event::Set(i, value.clone()));
+    self.items[i] = value;
+  }
+}
+
+// Swap neighbours until a pass has nothing left to swap. After each pass the
+// largest remaining item has bubbled up to the end.
+fn bubble<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>) {
+  let mut end = xs.items.len();
+  while end > 1 {
+    let mut last_swap = 0;
+    for i in 1..end {
+      if xs.less(i, i - 1) {
+        xs.swap(i, i - 1);
+        last_swap = i;
+      }
+    }
+    // Everything after the last swap is in place
+    end = last_swap;
+  }
+}
+
+// Move each item left until the item before it is not larger
+fn insertion<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>) {
+  for i in 1..xs.items.len() {
+    let mut j = i;
+    while j > 0 && xs.less(j, j - 1) {
+      xs.swap(j, j - 1);
+      j -= 1;
+    }
+  }
+}
+
+// Sort both halves of `xs[low..high]`, then merge them. Nothing is written
+// while merging, so the comparisons refer to the items where they are, and
+// the merged run is written back at the end.
+fn merge<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>, low: usize, high: usize) {
+  if high - low < 2 {
+    return;
+  }
+  let mid = low + (high - low) / 2;
+  merge(xs, low, mid);
+  merge(xs, mid, high);
+
+  let mut merged = Vec::with_capacity(high - low);
+  let (mut i, mut j) = (low, mid);
+  while i < mid && j < high {
+    // Take from the left on ties, which keeps the sort stable
+    if xs.less(j, i) {
+      merged.push(xs.items[j].clone());
+      j += 1;
+    } else {
+      merged.push(xs.items[i].clone());
+      i += 1;
+    }
+  }
+  merged.extend_from_slice(&xs.items[i..mid]);
+  merged.extend_from_slice(&xs.items[j..high]);
+  for (k, value) in merged.into_iter().enumerate() {
+    if xs.items[low + k] != value {
+      xs.set(low + k, value);
+    }
+  }
+}
+
+// Partition `xs[low..high]` around its middle item (Lomuto), then sort each
+// side. Recursing on the smaller side only keeps the stack shallow.
+fn quick<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>, mut low: usize, mut high: usize) {
+  while high - low > 1 {
+    // The middle item, so that sorted input is not the worst case
+    let (middle, pivot) = (low + (high - low) / 2, high - 1);
+    if middle != pivot {
+      xs.swap(middle, pivot);
+    }
+    let mut store = low;
+    for i in low..pivot {
+      if xs.less(i, pivot) {
+        if i != store {
+          xs.swap(i, store);
+        }
+        store += 1;
+      }
+    }
+    if store != pivot {
+      xs.swap(store, pivot);
+    }
+
+    if store - low < high - store {
+      quick(xs, low, store);
+      low = store + 1;
+    } else {
+      quick(xs, store + 1, high);
+      high = store;
+    }
+  }
+}
+
+// Turn the items into a max-heap, then move its root to the end and restore
+// the heap on what is left, until it is empty
+fn heap<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>) {
+  let len = xs.items.len();
+  for root in (0..len / 2).rev() {
+    sift_down(xs, root, len);
+  }
+  for end in (1..len).rev() {
+    xs.swap(0, end);
+    sift_down(xs, 0, end);
+  }
+}
+
+// Swap `root` with its larger child until both children are smaller
+fn sift_down<T: Ord + Clone, R: Trace<T>>(xs: &mut Tracked<T, R>, mut root: usize, end: usize) {
+  loop {
+    let mut child = 2 * root + 1;
+    if child >= end {
+      return;
+    }
+    if child + 1 < end && xs.less(child, child + 1) {
+      child += 1;
+    }
+    if !xs.less(root, child) {
+      return;
+    }
+    xs.swap(root, child);
+    root = child;
+  }
+}
+
+// Like `slice::binary_search`: `Ok` with the index of an item equal to
+// `target`, or `Err` with the index where it could be inserted
+pub fn binary_search<T: Ord, R: Trace<T>>(
+  items: &[T],
+  target: &T,
+  trace: &mut R,
+) -> Result<usize, usize> {
+  let (mut low, mut high) = (0, items.len());
+  while low < high {
+    let mid = low + (high - low) / 2;
+    trace.record(Event::Probe { low, mid, high });
+    match items[mid].cmp(target) {
+      std::cmp::Ordering::Less => low = mid + 1,
+      std::cmp::Ordering::Greater => high = mid,
+      std::cmp::Ordering::Equal => return Ok(mid),
+    }
+  }
+  Err(low)
+}
+
+// How many events of each kind
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Counts {
+  pub compares: usize,
+  pub swaps: usize,
+  pub sets: usize,
+  pub probes: usize,
+}
+
+impl Counts {
+  pub fn of<T>(events: &[Event<T>]) -> Counts {
+    let mut counts = Counts::default();
+    for event in events {
+      match event {
+        Event::Compare(..) => counts.compares += 1,
+        Event::Swap(..) => counts.swaps += 1,
+        Event::Set(..) => counts.sets += 1,
+        Event::Probe { .. } => counts.probes += 1,
+      }
+    }
+    counts
+  }
+}
+
+// Only the kinds of events that happened, `3 comparisons, 1 swap`
+impl Display for Counts {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let kinds = [
+      (self.compares, "comparison"),
+      (self.swaps, "swap"),
+      (self.sets, "write"),
+      (self.probes, "probe"),
+    ];
+    let parts: Vec<String> = kinds
+      .iter()
+      .filter(|(count, _)| *count > 0)
+      .map(|(count, kind)| format!("{} {}{}", count, kind, if *count == 1 { "" } else { "s" }))
+      .collect();
+    if parts.is_empty() {
+      write!(f, "nothing to do")
+    } else {
+      write!(f, "{}", parts.join(", "))
+    }
+  }
+}
+
+// The items after a step, with the ones it touched marked
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame<T> {
+  pub step: usize,
+  pub description: String,
+  pub items: Vec<T>,
+  // A mark under some of the items: `^` compared, `*` changed, `-` still
+  // searched
+  pub marks: Vec<(usize, char)>,
+}
+
+// A frame for the input, then one for every event
+pub fn replay<T: Clone + Debug>(input: &[T], events: &[Event<T>]) -> Vec<Frame<T>> {
+  let mut items = input.to_vec();
+  let mut frames = vec![Frame {
+    step: 0,
+    description: "start".to_owned(),
+    items: items.clone(),
+    marks: Vec::new(),
+  }];
+
+  for (step, event) in events.iter().enumerate() {
+    let (description, marks) = match event {
+      Event::Compare(i, j) => (
+        format!(
+          "compare xs[{}] = {:?} with xs[{}] = {:?}",
+          i, items[*i], j, items[*j]
+        ),
+        vec![(*i, '^'), (*j, '^')],
+      ),
+      Event::Swap(i, j) => {
+        items.swap(*i, *j);
+        (
+          format!("swap xs[{}] and xs[{}]", i, j),
+          vec![(*i, '*'), (*j, '*')],
+        )
+      }
+      Event::Set(i, value) => {
+        items[*i] = value.clone();
+        (format!("write {:?} to xs[{}]", value, i), vec![(*i, '*')])
+      }
+      Event::Probe { low, mid, high } => {
+        let mut marks: Vec<(usize, char)> = (*low..*high).map(|k| (k, '-')).collect();
+        marks[mid - low].1 = '^';
+        (
+          format!(
+            "look at xs[{}] = {:?} in xs[{}..{}]",
+            mid, items[*mid], low, high
+          ),
+          marks,
+        )
+      }
+    };
+    frames.push(Frame {
+      step: step + 1,
+      description,
+      items: items.clone(),
+      marks,
+    });
+  }
+  frames
+}
+
+impl<T: Debug> Display for Frame<T> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let cells: Vec<String> = self
+      .items
+      .iter()
+      .map(|item| format!("{:?}", item))
+      .collect();
+    let width = cells.iter().map(|cell| cell.len()).max().unwrap_or(0) + 1;
+    let mut marks = vec![' '; cells.len()];
+    for &(i, mark) in &self.marks {
+      marks[i] = mark;
+    }
+
+    writeln!(f, "step {}: {}", self.step, self.description)?;
+    let row: Vec<String> = cells.iter().map(|c| pad(c, width, Align::Right)).collect();
+    write!(f, "{}", row.join(""))?;
+    if self.marks.is_empty() {
+      return Ok(());
+    }
+    let marks: Vec<String> = marks
+      .iter()
+      .map(|m| pad(&m.to_string(), width, Align::Right))
+      .collect();
+    write!(f, "\n{}", marks.join("").trim_end())
+  }
+}
+
+// The output of the events on `input`, without the frames in between
+fn apply<T: Clone>(input: &[T], events: &[Event<T>]) -> Vec<T> {
+  let mut items = input.to_vec();
+  for event in events {
+    match event {
+      Event::Swap(i, j) => items.swap(*i, *j),
+      Event::Set(i, value) => items[*i] = value.clone(),
+      Event::Compare(..) | Event::Probe { .. } => {}
+    }
+  }
+  items
+}
+
+// `{"swap": [0, 1]}`, `{"probe": {"low": 0, "mid": 3, "high": 7}}`
+impl<T: ToJson> ToJson for Event<T> {
+  fn to_json(&self) -> Value {
+    let pair = |i: usize, j: Value| Value::Array(vec![i.to_json(), j]);
+    let (name, content) = match self {
+      Event::Compare(i, j) => ("compare", pair(*i, j.to_json())),
+      Event::Swap(i, j) => ("swap", pair(*i, j.to_json())),
+      Event::Set(i, value) => ("set", pair(*i, value.to_json())),
+      Event::Probe { low, mid, high } => (
+        "probe",
+        Value::Object(vec![
+          ("low".to_owned(), low.to_json()),
+          ("mid".to_owned(), mid.to_json()),
+          ("high".to_owned(), high.to_json()),
+        ]),
+      ),
+    };
+    Value::Object(vec![(name.to_owned(), content)])
+  }
+}
+
+// `{"name": ..., "input": [...], "events": [...], "output": [...]}`
+pub fn to_json<T: Clone + ToJson>(name: &str, input: &[T], events: &[Event<T>]) -> Value {
+  Value::Object(vec![
+    ("name".to_owned(), name.to_json()),
+    ("input".to_owned(), input.to_json()),
+    ("events".to_owned(), events.to_json()),
+    ("output".to_owned(), apply(input, events).to_json()),
+  ])
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::random::XorShift;
+
+  // Sorts `input` with every algorithm, directly and by replaying the events
+  fn check(input: &[u64]) {
+    let mut expected = input.to_vec();
+    expected.sort();
+    for &algorithm in Algorithm::ALL.iter() {
+      let mut items = input.to_vec();
+      algorithm.sort(&mut items, &mut ());
+      assert_eq!(items, expected, "{} sort of {:?}", algorithm, input);
+
+      let mut events = Vec::new();
+      algorithm.sort(&mut input.to_vec(), &mut events);
+      let replayed = replay(input, &events).pop().map(|frame| frame.items);
+      assert_eq!(
+        replayed,
+        Some(expected.clone()),
+        "{} replay of {:?}",
+        algorithm,
+        input
+      );
+    }
+  }
+
+  #[test]
+  fn sorts_edge_cases() {
+    check(&[]);
+    check(&[7]);
+    check(&[4, 4, 4, 4, 4]);
+    check(&[1, 2, 3, 4, 5, 6]);
+    check(&[6, 5, 4, 3, 2, 1]);
+    check(&[2, 1]);
+  }
+
+  #[test]
+  fn sorts_random_slices() {
+    let mut rng = XorShift::new(42);
+    for _ in 0..200 {
+      let len = rng.below(40) as usize;
+      // Few distinct values, so that there are many duplicates
+      let input: Vec<u64> = (0..len).map(|_| rng.below(20)).collect();
+      check(&input);
+    }
+  }
+
+  #[test]
+  fn binary_search_agrees_with_slice() {
+    let mut rng = XorShift::new(42);
+    for _ in 0..200 {
+      let len = rng.below(30) as usize;
+      let mut items: Vec<u64> = (0..len).map(|_| rng.below(20)).collect();
+      items.sort();
+      items.dedup();
+      for target in 0..22 {
+        assert_eq!(
+          binary_search(&items, &target, &mut ()),
+          items.binary_search(&target),
+          "{} in {:?}",
+          target,
+          items
+        );
+      }
+    }
+  }
+
+  #[test]
+  fn binary_search_with_duplicates_finds_an_equal_item() {
+    let items = [1, 2, 2, 2, 3];
+    let index = binary_search(&items, &2, &mut ()).unwrap();
+    assert_eq!(items[index], 2);
+    assert_eq!(binary_search(&items, &0, &mut ()), Err(0));
+    assert_eq!(binary_search(&items, &4, &mut ()), Err(5));
+  }
+
+  #[test]
+  fn json_trace_is_valid_json() {
+    let input = ["b\u{7f}", "a\"z"].map(String::from);
+    let mut events = Vec::new();
+    Algorithm::Merge.sort(&mut input.clone(), &mut events);
+    let text = format!("{:#}", to_json("merge \u{1}sort", &input, &events));
+    let value = crate::format::json::parse(&text).unwrap();
+    assert_eq!(
+      value.get("name").and_then(Value::as_str),
+      Some("merge \u{1}sort")
+    );
+    assert_eq!(
+      value.get("output"),
+      Some(&Value::Array(vec![
+        Value::String("a\"z".to_owned()),
+        Value::String("b\u{7f}".to_owned()),
+      ]))
+    );
+  }
+}
@@ -0,0 +1,8 @@
+pub mod chapters;
+pub mod cli;
+pub mod format;
+pub mod geometry;
+pub mod inspect;
+pub mod math;
+pub mod records;
+pub mod tuple;
@@ -1,11 +1,13 @@
-mod chapters;
-
-use chapters::{c01, c02, c03};
+use rust_by_example::chapters::{c01, c02, c03};
+use rust_by_example::cli;
+use std::env;
+use std::process;
 
 fn separator() {
     println!("\n \n ---------------------------------------- \n \n")
 }
-fn main() {
+
+fn chapters() {
     c01::format_string();
     separator();
     c01::display();
@@ -34,3 +36,16 @@ fn main() {
     separator();
     c03::constants();
 }
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.is_empty() {
+        chapters();
+        return;
+    }
+
+    if let Err(error) = cli::run(&args) {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
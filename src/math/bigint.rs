@@ -0,0 +1,833 @@
+/*
+ * Arbitrary-precision integers.
+ *
+ * The primitives stop at `i128` and `u128`. A `BigInt` is a sign and a
+ * magnitude of any length, stored as base 2^32 digits ("limbs"), least
+ * significant first, without leading zero limbs. Zero is never negative.
+ *
+ * Multiplication is the schoolbook method for short numbers and Karatsuba's,
+ * which needs three half-size products instead of four, above
+ * `KARATSUBA_THRESHOLD` limbs. Division is Knuth's algorithm D, and rounds
+ * toward zero like the primitives: `-7 / 2 == -3` and `-7 % 2 == -1`.
+ *
+ * `{:b}`, `{:o}`, `{:x}` and `{:X}` print the sign and the magnitude, since
+ * there is no fixed width for a two's complement of a negative value.
+ */
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Binary, Display, Formatter, LowerHex, Octal, UpperHex};
+use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
+use std::str::FromStr;
+
+// Below this many limbs, Karatsuba's bookkeeping costs more than it saves
+pub const KARATSUBA_THRESHOLD: usize = 32;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct BigInt {
+  negative: bool,
+  limbs: Vec<u32>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  UnsupportedRadix(u32),
+  InvalidDigit { text: String, radix: u32 },
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::UnsupportedRadix(radix) => write!(f, "radix {} is not between 2 and 36", radix),
+      Error::InvalidDigit { text, radix } => {
+        write!(f, "`{}` is not a base {} integer", text, radix)
+      }
+    }
+  }
+}
+
+// Operations on magnitudes, as limb slices without leading zeros
+
+fn trim(mut limbs: Vec<u32>) -> Vec<u32> {
+  while limbs.last() == Some(&0) {
+    limbs.pop();
+  }
+  limbs
+}
+
+fn cmp_mag(a: &[u32], b: &[u32]) -> Ordering {
+  a.len()
+    .cmp(&b.len())
+    .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+  let mut sum = Vec::with_capacity(long.len() + 1);
+  let mut carry = 0u64;
+  for (i, &x) in long.iter().enumerate() {
+    let total = u64::from(x) + u64::from(*short.get(i).unwrap_or(&0)) + carry;
+    sum.push(total as u32);
+    carry = total >> 32;
+  }
+  if carry > 0 {
+    sum.push(carry as u32);
+  }
+  sum
+}
+
+// `a - b`, where `a >= b`
+fn sub_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let mut difference = Vec::with_capacity(a.len());
+  let mut borrow = 0i64;
+  for (i, &x) in a.iter().enumerate() {
+    let total = i64::from(x) - i64::from(*b.get(i).unwrap_or(&0)) - borrow;
+    difference.push(total as u32);
+    borrow = (total < 0) as i64;
+  }
+  trim(difference)
+}
+
+// Add `x`, shifted left by `shift` limbs, into `acc`
+fn add_shifted(acc: &mut Vec<u32>, x: &[u32], shift: usize) {
+  if acc.len() < x.len() + shift {
+    acc.resize(x.len() + shift, 0);
+  }
+  let mut carry = 0u64;
+  let mut i = shift;
+  for &limb in x {
+    let total = u64::from(acc[i]) + u64::from(limb) + carry;
+    acc[i] = total as u32;
+    carry = total >> 32;
+    i += 1;
+  }
+  while carry > 0 {
+    if i == acc.len() {
+      acc.push(0);
+    }
+    let total = u64::from(acc[i]) + carry;
+    acc[i] = total as u32;
+    carry = total >> 32;
+    i += 1;
+  }
+}
+
+fn mul_schoolbook(a: &[u32], b: &[u32]) -> Vec<u32> {
+  let mut product = vec![0u32; a.len() + b.len()];
+  for (i, &x) in a.iter().enumerate() {
+    let mut carry = 0u64;
+    for (j, &y) in b.iter().enumerate() {
+      let total = u64::from(x) * u64::from(y) + u64::from(product[i + j]) + carry;
+      product[i + j] = total as u32;
+      carry = total >> 32;
+    }
+    product[i + b.len()] = carry as u32;
+  }
+  trim(product)
+}
+
+// The low `half` limbs and the rest
+fn split(limbs: &[u32], half: usize) -> (Vec<u32>, &[u32]) {
+  if limbs.len() <= half {
+    (limbs.to_vec(), &[])
+  } else {
+    (trim(limbs[..half].to_vec()), &limbs[half..])
+  }
+}
+
+// With a = a1 * B + a0 and b = b1 * B + b0:
+// a * b = a1 * b1 * B² + ((a0 + a1) * (b0 + b1) - a0 * b0 - a1 * b1) * B + a0 * b0
+fn mul_mag(a: &[u32], b: &[u32]) -> Vec<u32> {
+  if a.is_empty() || b.is_empty() {
+    return Vec::new();
+  }
+  if a.len() < KARATSUBA_THRESHOLD || b.len() < KARATSUBA_THRESHOLD {
+    return mul_schoolbook(a, b);
+  }
+
+  let half = a.len().max(b.len()) / 2;
+  let (a0, a1) = split(a, half);
+  let (b0, b1) = split(b, half);
+  let low = mul_mag(&a0, &b0);
+  let high = mul_mag(a1, b1);
+  let cross = mul_mag(&add_mag(&a0, a1), &add_mag(&b0, b1));
+  let middle = sub_mag(&sub_mag(&cross, &low), &high);
+
+  let mut product = low;
+  add_shifted(&mut product, &middle, half);
+  add_shifted(&mut product, &high, 2 * half);
+  trim(product)
+}
+
+// Divide by a single limb, for printing and for one-limb divisors
+fn divrem_small(a: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+  let mut quotient = vec![0u32; a.len()];
+  let mut rest = 0u64;
+  for (i, &limb) in a.iter().enumerate().rev() {
+    let current = rest << 32 | u64::from(limb);
+    quotient[i] = (current / u64::from(divisor)) as u32;
+    rest = current % u64::from(divisor);
+  }
+  (trim(quotient), rest as u32)
+}
+
+// Shift left by `bits` < 32, keeping the carried-out bits in a new limb
+fn shl_bits(a: &[u32], bits: u32) -> Vec<u32> {
+  let mut shifted = Vec::with_capacity(a.len() + 1);
+  let mut carry = 0u32;
+  for &limb in a {
+    shifted.push(limb << bits | carry);
+    carry = if bits == 0 { 0 } else { limb >> (32 - bits) };
+  }
+  shifted.push(carry);
+  shifted
+}
+
+// Knuth, The Art of Computer Programming, 4.3.1, algorithm D
+fn divrem_mag(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+  if cmp_mag(a, b) == Ordering::Less {
+    return (Vec::new(), a.to_vec());
+  }
+  if b.len() == 1 {
+    let (quotient, rest) = divrem_small(a, b[0]);
+    return (quotient, trim(vec![rest]));
+  }
+
+  // Scale both so the top limb of the divisor has its high bit set, which
+  // keeps each estimated quotient limb at most two above the real one
+  let shift = b[b.len() - 1].leading_zeros();
+  let v = shl_bits(b, shift);
+  let v = &v[..b.len()];
+  let mut u = shl_bits(a, shift);
+  let n = v.len();
+  let base = 1u64 << 32;
+  let mut quotient = vec![0u32; u.len() - n];
+
+  for j in (0..u.len() - n).rev() {
+    let top = u64::from(u[j + n]) << 32 | u64::from(u[j + n - 1]);
+    let mut estimate = top / u64::from(v[n - 1]);
+    let mut rest = top % u64::from(v[n - 1]);
+    while estimate >= base
+      || estimate * u64::from(v[n - 2]) > (rest << 32 | u64::from(u[j + n - 2]))
+    {
+      estimate -= 1;
+      rest += u64::from(v[n - 1]);
+      if rest >= base {
+        break;
+      }
+    }
+
+    // u -= estimate * v, at position j
+    let mut borrow = 0i64;
+    let mut carry = 0u64;
+    for i in 0..n {
+      let product = estimate * u64::from(v[i]) + carry;
+      carry = product >> 32;
+      let total = i64::from(u[i + j]) - borrow - (product & 0xFFFF_FFFF) as i64;
+      u[i + j] = total as u32;
+      borrow = (total < 0) as i64;
+    }
+    let total = i64::from(u[j + n]) - borrow - carry as i64;
+    u[j + n] = total as u32;
+
+    // The estimate was one too big: add v back
+    if total < 0 {
+      estimate -= 1;
+      let mut carry = 0u64;
+      for i in 0..n {
+        let sum = u64::from(u[i + j]) + u64::from(v[i]) + carry;
+        u[i + j] = sum as u32;
+        carry = sum >> 32;
+      }
+      u[j + n] = u[j + n].wrapping_add(carry as u32);
+    }
+    quotient[j] = estimate as u32;
+  }
+
+  // The remainder is left in the low limbs, still scaled
+  let rest = (0..n)
+    .map(|i| {
+      let high = if shift == 0 || i + 1 == n {
+        0
+      } else {
+        u[i + 1] << (32 - shift)
+      };
+      u[i] >> shift | high
+    })
+    .collect();
+  (trim(quotient), trim(rest))
+}
+
+impl BigInt {
+  pub fn zero() -> BigInt {
+    BigInt::default()
+  }
+
+  fn from_parts(negative: bool, limbs: Vec<u32>) -> BigInt {
+    let limbs = trim(limbs);
+    BigInt {
+      negative: negative && !limbs.is_empty(),
+      limbs,
+    }
+  }
+
+  pub fn is_zero(&self) -> bool {
+    self.limbs.is_empty()
+  }
+
+  pub fn is_negative(&self) -> bool {
+    self.negative
+  }
+
+  pub fn abs(&self) -> BigInt {
+    BigInt::from_parts(false, self.limbs.clone())
+  }
+
+  // The number of bits of the magnitude
+  pub fn bits(&self) -> u64 {
+    match self.limbs.last() {
+      Some(top) => 32 * self.limbs.len() as u64 - u64::from(top.leading_zeros()),
+      None => 0,
+    }
+  }
+
+  // Quotient rounded toward zero and remainder with the sign of `self`, or
+  // `None` when dividing by zero
+  pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+    if other.is_zero() {
+      return None;
+    }
+    let (quotient, rest) = divrem_mag(&self.limbs, &other.limbs);
+    Some((
+      BigInt::from_parts(self.negative != other.negative, quotient),
+      BigInt::from_parts(self.negative, rest),
+    ))
+  }
+
+  // Square and multiply
+  pub fn pow(&self, mut exponent: u32) -> BigInt {
+    let mut base = self.clone();
+    let mut result = BigInt::from(1u8);
+    while exponent > 0 {
+      if exponent & 1 == 1 {
+        result = &result * &base;
+      }
+      exponent >>= 1;
+      if exponent > 0 {
+        base = &base * &base;
+      }
+    }
+    result
+  }
+
+  // The greatest common divisor, never negative
+  pub fn gcd(&self, other: &BigInt) -> BigInt {
+    let (mut a, mut b) = (self.abs(), other.abs());
+    while let Some((_, rest)) = a.div_rem(&b) {
+      a = b;
+      b = rest;
+    }
+    a
+  }
+
+  pub fn from_str_radix(text: &str, radix: u32) -> Result<BigInt, Error> {
+    if !(2..=36).contains(&radix) {
+      return Err(Error::UnsupportedRadix(radix));
+    }
+    let invalid = || Error::InvalidDigit {
+      text: text.to_owned(),
+      radix,
+    };
+    let (negative, digits) = match text.strip_prefix('-') {
+      Some(rest) => (true, rest),
+      None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    if digits.is_empty() || digits.starts_with('_') {
+      return Err(invalid());
+    }
+
+    let mut limbs = Vec::new();
+    for c in digits.chars().filter(|&c| c != '_') {
+      let digit = c.to_digit(radix).ok_or_else(invalid)?;
+      // limbs = limbs * radix + digit
+      let mut carry = u64::from(digit);
+      for limb in limbs.iter_mut() {
+        let total = u64::from(*limb) * u64::from(radix) + carry;
+        *limb = total as u32;
+        carry = total >> 32;
+      }
+      if carry > 0 {
+        limbs.push(carry as u32);
+      }
+    }
+    Ok(BigInt::from_parts(negative, limbs))
+  }
+
+  // The digits of the magnitude, without sign or prefix
+  fn magnitude_digits(&self, radix: u32, upper: bool) -> String {
+    if self.is_zero() {
+      return "0".to_owned();
+    }
+    // Peel off as many digits as fit in a limb at a time
+    let mut width = 1;
+    let mut chunk = radix;
+    while let Some(bigger) = chunk.checked_mul(radix) {
+      chunk = bigger;
+      width += 1;
+    }
+
+    let mut groups = Vec::new();
+    let mut rest = self.limbs.clone();
+    while !rest.is_empty() {
+      let (quotient, group) = divrem_small(&rest, chunk);
+      groups.push(group);
+      rest = quotient;
+    }
+
+    let mut digits = String::new();
+    for (i, &group) in groups.iter().rev().enumerate() {
+      let mut text = Vec::new();
+      let mut group = group;
+      while group > 0 {
+        let digit = std::char::from_digit(group % radix, radix).unwrap_or('?');
+        text.push(if upper {
+          digit.to_ascii_uppercase()
+        } else {
+          digit
+        });
+        group /= radix;
+      }
+      // Every group but the first is zero-padded
+      if i > 0 {
+        text.resize(width, '0');
+      }
+      digits.extend(text.iter().rev());
+    }
+    digits
+  }
+
+  pub fn to_str_radix(&self, radix: u32) -> String {
+    let sign = if self.negative { "-" } else { "" };
+    format!("{}{}", sign, self.magnitude_digits(radix, false))
+  }
+
+  pub fn to_u128(&self) -> Option<u128> {
+    if self.negative || self.limbs.len() > 4 {
+      return None;
+    }
+    Some(
+      self
+        .limbs
+        .iter()
+        .rev()
+        .fold(0u128, |value, &limb| value << 32 | u128::from(limb)),
+    )
+  }
+
+  pub fn to_i128(&self) -> Option<i128> {
+    let magnitude = self.abs().to_u128()?;
+    if self.negative {
+      0i128.checked_sub_unsigned(magnitude)
+    } else {
+      i128::try_from(magnitude).ok()
+    }
+  }
+}
+
+macro_rules! bigint_from {
+  ($($t:ty)*) => ($(
+    impl From<$t> for BigInt {
+      #[allow(unused_comparisons)]
+      fn from(n: $t) -> BigInt {
+        let mut magnitude = (n as i128).unsigned_abs();
+        let mut limbs = Vec::new();
+        while magnitude > 0 {
+          limbs.push(magnitude as u32);
+          magnitude >>= 32;
+        }
+        BigInt::from_parts(n < 0, limbs)
+      }
+    }
+  )*)
+}
+
+bigint_from! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 usize }
+
+// `u128` doesn't fit in an `i128`
+impl From<u128> for BigInt {
+  fn from(mut n: u128) -> BigInt {
+    let mut limbs = Vec::new();
+    while n > 0 {
+      limbs.push(n as u32);
+      n >>= 32;
+    }
+    BigInt::from_parts(false, limbs)
+  }
+}
+
+impl Ord for BigInt {
+  fn cmp(&self, other: &BigInt) -> Ordering {
+    match (self.negative, other.negative) {
+      (false, true) => Ordering::Greater,
+      (true, false) => Ordering::Less,
+      (false, false) => cmp_mag(&self.limbs, &other.limbs),
+      (true, true) => cmp_mag(&other.limbs, &self.limbs),
+    }
+  }
+}
+
+impl PartialOrd for BigInt {
+  fn partial_cmp(&self, other: &BigInt) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl PartialEq<u128> for BigInt {
+  fn eq(&self, other: &u128) -> bool {
+    self.to_u128() == Some(*other)
+  }
+}
+
+impl PartialOrd<u128> for BigInt {
+  fn partial_cmp(&self, other: &u128) -> Option<Ordering> {
+    Some(self.cmp(&BigInt::from(*other)))
+  }
+}
+
+impl Neg for &BigInt {
+  type Output = BigInt;
+
+  fn neg(self) -> BigInt {
+    BigInt::from_parts(!self.negative, self.limbs.clone())
+  }
+}
+
+impl Add for &BigInt {
+  type Output = BigInt;
+
+  fn add(self, other: &BigInt) -> BigInt {
+    if self.negative == other.negative {
+      return BigInt::from_parts(self.negative, add_mag(&self.limbs, &other.limbs));
+    }
+    // Opposite signs: the bigger magnitude wins
+    match cmp_mag(&self.limbs, &other.limbs) {
+      Ordering::Less => BigInt::from_parts(other.negative, sub_mag(&other.limbs, &self.limbs)),
+      _ => BigInt::from_parts(self.negative, sub_mag(&self.limbs, &other.limbs)),
+    }
+  }
+}
+
+impl Sub for &BigInt {
+  type Output = BigInt;
+
+  fn sub(self, other: &BigInt) -> BigInt {
+    self + &-other
+  }
+}
+
+impl Mul for &BigInt {
+  type Output = BigInt;
+
+  fn mul(self, other: &BigInt) -> BigInt {
+    BigInt::from_parts(
+      self.negative != other.negative,
+      mul_mag(&self.limbs, &other.limbs),
+    )
+  }
+}
+
+impl Div for &BigInt {
+  type Output = BigInt;
+
+  fn div(self, other: &BigInt) -> BigInt {
+    self.div_rem(other).expect("attempt to divide by zero").0
+  }
+}
+
+impl Rem for &BigInt {
+  type Output = BigInt;
+
+  fn rem(self, other: &BigInt) -> BigInt {
+    self
+      .div_rem(other)
+      .expect("attempt to calculate the remainder with a divisor of zero")
+      .1
+  }
+}
+
+// The owned operators borrow both sides
+macro_rules! bigint_owned_op {
+  ($($trait:ident $method:ident)*) => ($(
+    impl $trait for BigInt {
+      type Output = BigInt;
+
+      fn $method(self, other: BigInt) -> BigInt {
+        (&self).$method(&other)
+      }
+    }
+  )*)
+}
+
+bigint_owned_op! { Add add Sub sub Mul mul Div div Rem rem }
+
+impl Neg for BigInt {
+  type Output = BigInt;
+
+  fn neg(self) -> BigInt {
+    -&self
+  }
+}
+
+impl FromStr for BigInt {
+  type Err = Error;
+
+  // Decimal, or with a `0x`, `0o` or `0b` prefix after the sign. The sign
+  // comes first only: `0x-5` and `--5` are errors.
+  fn from_str(text: &str) -> Result<BigInt, Error> {
+    let (sign, rest) = match text.strip_prefix('-') {
+      Some(rest) => ("-", rest),
+      None => ("", text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (radix, digits) = match rest.get(..2) {
+      Some("0x") | Some("0X") => (16, &rest[2..]),
+      Some("0o") | Some("0O") => (8, &rest[2..]),
+      Some("0b") | Some("0B") => (2, &rest[2..]),
+      _ => (10, rest),
+    };
+    let invalid = || Error::InvalidDigit {
+      text: text.to_owned(),
+      radix,
+    };
+    if digits.starts_with(['-', '+']) {
+      return Err(invalid());
+    }
+    BigInt::from_str_radix(&format!("{}{}", sign, digits), radix).map_err(|_| invalid())
+  }
+}
+
+macro_rules! bigint_fmt {
+  ($($trait:ident $radix:literal $prefix:literal $upper:literal)*) => ($(
+    impl $trait for BigInt {
+      // Width, fill, `+` and `#` behave like they do for the primitives
+      fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.pad_integral(!self.negative, $prefix, &self.magnitude_digits($radix, $upper))
+      }
+    }
+  )*)
+}
+
+bigint_fmt! {
+  Display 10 "" false
+  Binary 2 "0b" false
+  Octal 8 "0o" false
+  LowerHex 16 "0x" false
+  UpperHex 16 "0x" true
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::random::XorShift;
+
+  // Random lengths, so that sums and products don't always overflow
+  fn random_u128(rng: &mut XorShift) -> u128 {
+    rng.next_u128() >> rng.below(128)
+  }
+
+  fn random_big(rng: &mut XorShift, len: usize) -> BigInt {
+    let limbs = (0..len).map(|_| rng.next_u64() as u32).collect();
+    BigInt::from_parts(rng.chance(1, 2), limbs)
+  }
+
+  // `q * b + r == a`, with `r` smaller than `b` and signed like `a`
+  fn check_division(a: &BigInt, b: &BigInt) {
+    let (q, r) = a.div_rem(b).unwrap();
+    assert_eq!(&(&q * b) + &r, *a, "{} / {}", a, b);
+    assert!(r.abs() < b.abs(), "{} % {} = {}", a, b, r);
+    assert!(r.is_zero() || r.is_negative() == a.is_negative());
+  }
+
+  #[test]
+  fn agrees_with_u128() {
+    let mut rng = XorShift::new(37);
+    for _ in 0..2000 {
+      let (x, y) = (random_u128(&mut rng), random_u128(&mut rng));
+      let (big_x, big_y) = (BigInt::from(x), BigInt::from(y));
+      assert_eq!((&big_x + &big_y).to_u128(), x.checked_add(y));
+      assert_eq!((&big_x - &big_y).to_u128(), x.checked_sub(y));
+      assert_eq!((&big_x * &big_y).to_u128(), x.checked_mul(y));
+      assert_eq!(
+        big_x
+          .div_rem(&big_y)
+          .map(|(q, r)| (q.to_u128(), r.to_u128())),
+        x.checked_div(y).map(|q| (Some(q), Some(x % y)))
+      );
+      assert_eq!(big_x.cmp(&big_y), x.cmp(&y));
+      assert_eq!(big_x.partial_cmp(&y), x.partial_cmp(&y));
+    }
+  }
+
+  #[test]
+  fn agrees_with_i128() {
+    let mut rng = XorShift::new(37);
+    for _ in 0..2000 {
+      let x = rng.next_u64() as i64 >> rng.below(64);
+      let y = rng.next_u64() as i64 >> rng.below(64);
+      let (x, y) = (i128::from(x), i128::from(y));
+      let (big_x, big_y) = (BigInt::from(x), BigInt::from(y));
+      assert_eq!((&big_x + &big_y).to_i128(), Some(x + y));
+      assert_eq!((&big_x - &big_y).to_i128(), Some(x - y));
+      assert_eq!((&big_x * &big_y).to_i128(), Some(x * y));
+      if y != 0 {
+        assert_eq!((&big_x / &big_y).to_i128(), Some(x / y));
+        assert_eq!((&big_x % &big_y).to_i128(), Some(x % y));
+      }
+    }
+  }
+
+  #[test]
+  fn karatsuba_agrees_with_schoolbook() {
+    let mut rng = XorShift::new(37);
+    for &(m, n) in &[
+      (KARATSUBA_THRESHOLD, KARATSUBA_THRESHOLD),
+      (KARATSUBA_THRESHOLD + 1, 2 * KARATSUBA_THRESHOLD),
+      (3 * KARATSUBA_THRESHOLD, KARATSUBA_THRESHOLD + 5),
+      (5 * KARATSUBA_THRESHOLD + 3, 4 * KARATSUBA_THRESHOLD),
+    ] {
+      let (a, b) = (random_big(&mut rng, m), random_big(&mut rng, n));
+      assert_eq!(
+        mul_mag(&a.limbs, &b.limbs),
+        mul_schoolbook(&a.limbs, &b.limbs)
+      );
+
+      let product = &a * &b;
+      assert_eq!(product.is_negative(), a.is_negative() != b.is_negative());
+      assert_eq!(product.div_rem(&b), Some((a.clone(), BigInt::zero())));
+      assert_eq!(&(&a + &b) - &b, a);
+    }
+  }
+
+  #[test]
+  fn division_normalizes_every_divisor() {
+    let mut rng = XorShift::new(37);
+    // Top limbs that need every shift from 31 down to none
+    let tops = [1, 2, 0xFFFF, 0x7FFF_FFFF, 0x8000_0000, 0xFFFF_FFFF];
+    for &top in &tops {
+      for len in 2..6 {
+        for _ in 0..50 {
+          let mut b = random_big(&mut rng, len - 1);
+          b.limbs.push(top);
+          let extra = rng.below(6) as usize;
+          let a = random_big(&mut rng, len + extra);
+          check_division(&a, &b);
+          // Exact multiples, and one less, are where the estimate is off
+          let multiple = &a * &b;
+          check_division(&multiple, &b);
+          check_division(&(&multiple - &BigInt::from(1u8)), &b);
+        }
+      }
+    }
+    // Divisors of the same magnitude as the dividend, or bigger
+    for _ in 0..200 {
+      let len = 1 + rng.below(8) as usize;
+      let (a, b) = (random_big(&mut rng, len), random_big(&mut rng, len));
+      if !b.is_zero() {
+        check_division(&a, &b);
+      }
+    }
+    assert_eq!(BigInt::from(7u8).div_rem(&BigInt::zero()), None);
+  }
+
+  #[test]
+  fn pow_and_gcd() {
+    let mut rng = XorShift::new(37);
+    for _ in 0..500 {
+      let base = rng.next_u64() >> rng.below(64);
+      let exponent = rng.below(12) as u32;
+      assert_eq!(
+        BigInt::from(base).pow(exponent).to_u128(),
+        u128::from(base).checked_pow(exponent)
+      );
+
+      let (x, y) = (random_u128(&mut rng), random_u128(&mut rng));
+      let (mut a, mut b) = (x, y);
+      while b != 0 {
+        let rest = a % b;
+        a = b;
+        b = rest;
+      }
+      assert_eq!(BigInt::from(x).gcd(&BigInt::from(y)).to_u128(), Some(a));
+      assert_eq!(BigInt::from(x).gcd(&-BigInt::from(y)).to_u128(), Some(a));
+    }
+    // gcd(a * c, b * c) is a multiple of c
+    let c = BigInt::from(3u8).pow(300);
+    let g = (&c * &BigInt::from(10u8)).gcd(&(&c * &BigInt::from(4u8)));
+    assert_eq!(g, &c * &BigInt::from(2u8));
+    assert_eq!(BigInt::from(-5i8).pow(3), BigInt::from(-125i16));
+    assert_eq!(BigInt::zero().pow(0), BigInt::from(1u8));
+  }
+
+  #[test]
+  fn parses_a_sign_then_a_prefix() {
+    let parse = |text: &str| text.parse::<BigInt>();
+    assert_eq!(parse("-0x1F"), Ok(BigInt::from(-31i8)));
+    assert_eq!(parse("+0b101"), Ok(BigInt::from(5u8)));
+    assert_eq!(parse("0o17"), Ok(BigInt::from(15u8)));
+    assert_eq!(parse("+42"), Ok(BigInt::from(42u8)));
+    assert_eq!(parse("-1_000"), Ok(BigInt::from(-1000i16)));
+    for &text in &[
+      "0x-5", "0x+5", "-0x-5", "--5", "-+5", "+-5", "++5", "-", "0x", "",
+    ] {
+      assert!(parse(text).is_err(), "{:?}", text);
+    }
+    assert_eq!(
+      parse("0x-5"),
+      Err(Error::InvalidDigit {
+        text: "0x-5".to_owned(),
+        radix: 16
+      })
+    );
+  }
+
+  #[test]
+  fn round_trips_through_every_radix() {
+    let mut rng = XorShift::new(37);
+    for radix in 2..=36 {
+      for len in 0..12 {
+        let n = random_big(&mut rng, len);
+        let text = n.to_str_radix(radix);
+        assert_eq!(
+          BigInt::from_str_radix(&text, radix),
+          Ok(n.clone()),
+          "{}",
+          text
+        );
+      }
+      // Against the digits of a u128, worked out one at a time
+      let x = random_u128(&mut rng);
+      let (mut rest, mut digits) = (x, Vec::new());
+      while rest > 0 {
+        digits.push(std::char::from_digit((rest % u128::from(radix)) as u32, radix).unwrap());
+        rest /= u128::from(radix);
+      }
+      let expected: String = digits.iter().rev().collect();
+      let expected = if expected.is_empty() {
+        "0".to_owned()
+      } else {
+        expected
+      };
+      assert_eq!(BigInt::from(x).to_str_radix(radix), expected);
+    }
+    assert_eq!(
+      BigInt::from_str_radix("1", 37),
+      Err(Error::UnsupportedRadix(37))
+    );
+    assert!(BigInt::from_str_radix("12", 2).is_err());
+    let x = random_u128(&mut rng);
+    let big = BigInt::from(x);
+    assert_eq!(
+      format!("{} {:b} {:o} {:x} {:X}", big, big, big, big, big),
+      format!("{} {:b} {:o} {:x} {:X}", x, x, x, x, x)
+    );
+  }
+}
@@ -0,0 +1,249 @@
+/*
+ * Complex numbers.
+ *
+ * `Complex<T>` is `re + im·i`. The arithmetic operators only need the ones of
+ * `T`, so they also work on integers, while the functions that need square
+ * roots and trigonometry are implemented for `f32` and `f64` by a macro:
+ *
+ *  - `modulus` and `arg`, the distance to zero and the angle with the real
+ *    axis, which are also the polar form `r∠θ`
+ *  - `exp`, `ln`, `sqrt` and `pow`, on their principal branch
+ *
+ * `{}` prints `3+4i` and `{:#}` prints the polar form, with the angle in
+ * degrees. The precision applies to both parts, like the rounded coordinates
+ * of a `City`: `{:#.2}` prints `5.00∠53.13°`.
+ */
+
+use crate::format::number::pad_number;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq, Default)]
+pub struct Complex<T> {
+  pub re: T,
+  pub im: T,
+}
+
+impl<T> Complex<T> {
+  pub fn new(re: T, im: T) -> Complex<T> {
+    Complex { re, im }
+  }
+}
+
+impl<T: Copy + Neg<Output = T>> Complex<T> {
+  // Mirror across the real axis
+  pub fn conj(&self) -> Complex<T> {
+    Complex::new(self.re, -self.im)
+  }
+}
+
+impl<T: Copy + Add<Output = T> + Mul<Output = T>> Complex<T> {
+  // The square of the modulus, which needs no square root
+  pub fn norm_sqr(&self) -> T {
+    self.re * self.re + self.im * self.im
+  }
+}
+
+impl<T: Add<Output = T>> Add for Complex<T> {
+  type Output = Complex<T>;
+
+  fn add(self, other: Complex<T>) -> Complex<T> {
+    Complex::new(self.re + other.re, self.im + other.im)
+  }
+}
+
+impl<T: Sub<Output = T>> Sub for Complex<T> {
+  type Output = Complex<T>;
+
+  fn sub(self, other: Complex<T>) -> Complex<T> {
+    Complex::new(self.re - other.re, self.im - other.im)
+  }
+}
+
+// (a + bi)(c + di) = (ac - bd) + (ad + bc)i, since i² = -1
+impl<T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>> Mul for Complex<T> {
+  type Output = Complex<T>;
+
+  fn mul(self, other: Complex<T>) -> Complex<T> {
+    Complex::new(
+      self.re * other.re - self.im * other.im,
+      self.re * other.im + self.im * other.re,
+    )
+  }
+}
+
+// Multiply both by the conjugate of the divisor, which makes it real
+impl<T> Div for Complex<T>
+where
+  T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+  type Output = Complex<T>;
+
+  fn div(self, other: Complex<T>) -> Complex<T> {
+    let denom = other.norm_sqr();
+    Complex::new(
+      (self.re * other.re + self.im * other.im) / denom,
+      (self.im * other.re - self.re * other.im) / denom,
+    )
+  }
+}
+
+impl<T: Neg<Output = T>> Neg for Complex<T> {
+  type Output = Complex<T>;
+
+  fn neg(self) -> Complex<T> {
+    Complex::new(-self.re, -self.im)
+  }
+}
+
+macro_rules! complex_float {
+  ($($t:ident)*) => ($(
+    impl Complex<$t> {
+      pub const I: Complex<$t> = Complex { re: 0.0, im: 1.0 };
+
+      pub fn from_polar(r: $t, theta: $t) -> Complex<$t> {
+        Complex::new(r * theta.cos(), r * theta.sin())
+      }
+
+      // `(r, θ)`, with θ in `-π..=π`
+      pub fn to_polar(&self) -> ($t, $t) {
+        (self.modulus(), self.arg())
+      }
+
+      pub fn modulus(&self) -> $t {
+        self.re.hypot(self.im)
+      }
+
+      pub fn arg(&self) -> $t {
+        self.im.atan2(self.re)
+      }
+
+      // e^(a + bi) = e^a (cos b + i sin b)
+      pub fn exp(&self) -> Complex<$t> {
+        Complex::<$t>::from_polar(self.re.exp(), self.im)
+      }
+
+      // The principal logarithm, whose imaginary part is in `-π..=π`
+      pub fn ln(&self) -> Complex<$t> {
+        Complex::new(self.modulus().ln(), self.arg())
+      }
+
+      // The principal root, with a non-negative real part
+      pub fn sqrt(&self) -> Complex<$t> {
+        // Half-angle formulas, which don't lose precision near the axes like
+        // going through the polar form does
+        let r = self.modulus();
+        let re = ((r + self.re) / 2.0).sqrt();
+        let im = ((r - self.re) / 2.0).sqrt().copysign(self.im);
+        Complex::new(re, im)
+      }
+
+      // z^w = e^(w ln z). `ln 0` is infinite, so a zero base is on its own:
+      // like `0.0.powf(w)`, `0^w` is 0 for `Re(w) > 0` and infinite for
+      // `Re(w) < 0`. For a purely imaginary `w` it spins around forever
+      // without settling, which is NaN.
+      pub fn pow(&self, exponent: Complex<$t>) -> Complex<$t> {
+        if self.re == 0.0 && self.im == 0.0 {
+          return if exponent.re == 0.0 && exponent.im == 0.0 {
+            Complex::new(1.0, 0.0)
+          } else if exponent.re > 0.0 {
+            Complex::new(0.0, 0.0)
+          } else if exponent.re < 0.0 {
+            Complex::new(<$t>::INFINITY, 0.0)
+          } else {
+            Complex::new(<$t>::NAN, <$t>::NAN)
+          };
+        }
+        (exponent * self.ln()).exp()
+      }
+
+      pub fn powf(&self, exponent: $t) -> Complex<$t> {
+        // Without an angle, which would give `inf · sin 0`, NaN, for `0^-1`
+        if self.re == 0.0 && self.im == 0.0 {
+          return Complex::from((0.0 as $t).powf(exponent));
+        }
+        let (r, theta) = self.to_polar();
+        Complex::<$t>::from_polar(r.powf(exponent), theta * exponent)
+      }
+    }
+
+    impl From<$t> for Complex<$t> {
+      fn from(re: $t) -> Complex<$t> {
+        Complex::new(re, 0.0)
+      }
+    }
+
+    impl Display for Complex<$t> {
+      fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let number = |x: $t| match f.precision() {
+          Some(precision) => format!("{:.*}", precision, x),
+          None => x.to_string(),
+        };
+        let text = if f.alternate() {
+          let (r, theta) = self.to_polar();
+          format!("{}∠{}°", number(r), number(theta.to_degrees()))
+        } else {
+          // The sign of the imaginary part becomes the operator
+          let sign = if self.im.is_sign_negative() { '-' } else { '+' };
+          format!("{}{}{}i", number(self.re), sign, number(self.im.abs()))
+        };
+        pad_number(f, &text)
+      }
+    }
+  )*)
+}
+
+complex_float! { f32 f64 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  type C = Complex<f64>;
+
+  fn close(a: C, b: C) -> bool {
+    (a - b).modulus() <= 1e-12 * b.modulus().max(1.0)
+  }
+
+  #[test]
+  fn arithmetic() {
+    let (a, b) = (C::new(3.0, 4.0), C::new(1.0, -2.0));
+    assert_eq!(a + b, C::new(4.0, 2.0));
+    assert_eq!(a * b, C::new(11.0, -2.0));
+    assert!(close(a / b * b, a));
+    assert_eq!(a.modulus(), 5.0);
+    assert!(close(C::new(-4.0, 0.0).sqrt(), C::new(0.0, 2.0)));
+    assert!(close(
+      C::new(0.0, std::f64::consts::PI).exp(),
+      C::new(-1.0, 0.0)
+    ));
+    assert!(close(a.pow(C::new(2.0, 0.0)), a * a));
+    assert!(close(a.powf(-1.0), C::new(1.0, 0.0) / a));
+  }
+
+  #[test]
+  fn zero_base() {
+    let zero = C::new(0.0, 0.0);
+    assert_eq!(zero.pow(zero), C::new(1.0, 0.0));
+    assert_eq!(zero.pow(C::new(2.0, 5.0)), zero);
+    assert_eq!(zero.pow(C::new(-1.0, 0.0)), C::new(f64::INFINITY, 0.0));
+    assert_eq!(zero.pow(C::new(-0.5, 3.0)), C::new(f64::INFINITY, 0.0));
+    let spinning = zero.pow(C::new(0.0, 1.0));
+    assert!(spinning.re.is_nan() && spinning.im.is_nan());
+
+    // The same as the real powers
+    for &exponent in &[-1.0, -0.5, 0.0, 0.5, 2.0] {
+      assert_eq!(zero.powf(exponent), C::from(0f64.powf(exponent)));
+      assert_eq!(
+        zero.pow(C::from(exponent)).re,
+        0f64.powf(exponent),
+        "{}",
+        exponent
+      );
+    }
+    assert_eq!(
+      Complex::<f32>::new(0.0, 0.0).pow(Complex::from(-2.0)),
+      Complex::new(f32::INFINITY, 0.0)
+    );
+  }
+}
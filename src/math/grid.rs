@@ -0,0 +1,362 @@
+/*
+ * Two-dimensional grids.
+ *
+ * `Grid<T, W, H>` is `W` columns by `H` rows, stored row after row in one
+ * array: `[[T; W]; H]` has no gaps between the rows, so the whole grid is
+ * also a flat `[T]` of `W * H` cells. A row is a slice of it, and a column
+ * is every `W`-th cell starting at its index.
+ *
+ * Cells are addressed as `(x, y)`, with `x` the column and `y` the row
+ * counted from the top, like the pixels of a screen. Unlike `Matrix`, which
+ * is indexed `(row, column)` like in mathematics.
+ */
+
+use crate::format::table::{pad, Align};
+use crate::format::width::str_width;
+use std::array;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+// Which cells count as neighbours
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Connectivity {
+  // Up, down, left and right
+  Four,
+  // The diagonals too
+  Eight,
+}
+
+impl Connectivity {
+  fn offsets(self) -> &'static [(isize, isize)] {
+    match self {
+      Connectivity::Four => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+      Connectivity::Eight => &[
+        (-1, -1),
+        (0, -1),
+        (1, -1),
+        (-1, 0),
+        (1, 0),
+        (-1, 1),
+        (0, 1),
+        (1, 1),
+      ],
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Grid<T, const W: usize, const H: usize> {
+  rows: [[T; W]; H],
+}
+
+impl<T, const W: usize, const H: usize> Grid<T, W, H> {
+  pub fn new(rows: [[T; W]; H]) -> Self {
+    Grid { rows }
+  }
+
+  // The cell at `(x, y)` is `f(x, y)`, filled row by row
+  pub fn from_fn<F: FnMut(usize, usize) -> T>(mut f: F) -> Self {
+    Grid {
+      rows: array::from_fn(|y| array::from_fn(|x| f(x, y))),
+    }
+  }
+
+  pub fn width(&self) -> usize {
+    W
+  }
+
+  pub fn height(&self) -> usize {
+    H
+  }
+
+  pub fn get(&self, x: usize, y: usize) -> Option<&T> {
+    self.rows.get(y)?.get(x)
+  }
+
+  pub fn get_mut(&mut self, x: usize, y: usize) -> Option<&mut T> {
+    self.rows.get_mut(y)?.get_mut(x)
+  }
+
+  // Every cell, row after row
+  pub fn as_slice(&self) -> &[T] {
+    self.rows.as_flattened()
+  }
+
+  pub fn as_mut_slice(&mut self) -> &mut [T] {
+    self.rows.as_flattened_mut()
+  }
+
+  pub fn row(&self, y: usize) -> &[T] {
+    &self.rows[y]
+  }
+
+  pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+    &mut self.rows[y]
+  }
+
+  pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+    self.rows.iter().map(|row| &row[..])
+  }
+
+  // The cells of column `x`, from the top. Panics if `x` is out of bounds.
+  pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+    assert!(x < W, "column {} out of bounds for a width of {}", x, W);
+    self.as_slice().iter().skip(x).step_by(W)
+  }
+
+  // The coordinates of the neighbours of `(x, y)` inside the grid
+  pub fn neighbors(
+    &self,
+    x: usize,
+    y: usize,
+    connectivity: Connectivity,
+  ) -> impl Iterator<Item = (usize, usize)> {
+    connectivity.offsets().iter().filter_map(move |&(dx, dy)| {
+      let nx = x.checked_add_signed(dx).filter(|&nx| nx < W)?;
+      let ny = y.checked_add_signed(dy).filter(|&ny| ny < H)?;
+      Some((nx, ny))
+    })
+  }
+
+  pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Grid<U, W, H> {
+    Grid {
+      rows: self.rows.map(|row| row.map(&mut f)),
+    }
+  }
+}
+
+impl<T: Copy, const W: usize, const H: usize> Grid<T, W, H> {
+  // Swap rows and columns, the cell at `(x, y)` moves to `(y, x)`
+  pub fn transpose(&self) -> Grid<T, H, W> {
+    Grid::from_fn(|x, y| self.rows[x][y])
+  }
+
+  // A quarter turn clockwise: the left column becomes the top row
+  pub fn rotate_right(&self) -> Grid<T, H, W> {
+    Grid::from_fn(|x, y| self.rows[H - 1 - x][y])
+  }
+
+  // A quarter turn counterclockwise: the top row becomes the left column
+  pub fn rotate_left(&self) -> Grid<T, H, W> {
+    Grid::from_fn(|x, y| self.rows[x][W - 1 - y])
+  }
+
+  pub fn rotate_half(&self) -> Self {
+    Grid::from_fn(|x, y| self.rows[H - 1 - y][W - 1 - x])
+  }
+}
+
+impl<T: Copy + PartialEq, const W: usize, const H: usize> Grid<T, W, H> {
+  // Replace the cell at `(x, y)` and every cell connected to it through
+  // cells of the same value by `value`, and return how many were replaced
+  pub fn flood_fill(&mut self, x: usize, y: usize, value: T, connectivity: Connectivity) -> usize {
+    let target = match self.get(x, y) {
+      Some(&target) if target != value => target,
+      _ => return 0,
+    };
+    let mut filled = 0;
+    // A stack instead of recursion, which could overflow on a big grid
+    let mut pending = vec![(x, y)];
+    while let Some((x, y)) = pending.pop() {
+      if self.rows[y][x] != target {
+        continue;
+      }
+      self.rows[y][x] = value;
+      filled += 1;
+      pending.extend(self.neighbors(x, y, connectivity));
+    }
+    filled
+  }
+}
+
+impl<const W: usize, const H: usize> Grid<bool, W, H> {
+  // The next generation of Conway's Game of Life, with dead cells all around
+  // the grid: a live cell survives with 2 or 3 live neighbours, and a dead
+  // one comes to life with exactly 3
+  pub fn life_step(&self) -> Self {
+    Grid::from_fn(|x, y| {
+      let alive = self
+        .neighbors(x, y, Connectivity::Eight)
+        .filter(|&(nx, ny)| self.rows[ny][nx])
+        .count();
+      matches!((self.rows[y][x], alive), (true, 2) | (_, 3))
+    })
+  }
+
+  pub fn population(&self) -> usize {
+    self.as_slice().iter().filter(|&&alive| alive).count()
+  }
+}
+
+impl<T, const W: usize, const H: usize> Index<(usize, usize)> for Grid<T, W, H> {
+  type Output = T;
+
+  fn index(&self, (x, y): (usize, usize)) -> &T {
+    &self.rows[y][x]
+  }
+}
+
+impl<T, const W: usize, const H: usize> IndexMut<(usize, usize)> for Grid<T, W, H> {
+  fn index_mut(&mut self, (x, y): (usize, usize)) -> &mut T {
+    &mut self.rows[y][x]
+  }
+}
+
+// One line per row, with the columns right-aligned and the precision applied
+// to every cell. `{:#}` leaves out the spaces between the cells, for grids of
+// single characters.
+impl<T: Display, const W: usize, const H: usize> Display for Grid<T, W, H> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let cells: Vec<Vec<String>> = self
+      .rows
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|cell| match f.precision() {
+            Some(precision) => format!("{:.*}", precision, cell),
+            None => cell.to_string(),
+          })
+          .collect()
+      })
+      .collect();
+    let widths: Vec<usize> = (0..W)
+      .map(|x| {
+        cells
+          .iter()
+          .map(|row| str_width(&row[x]))
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+    let separator = if f.alternate() { "" } else { " " };
+
+    for (y, row) in cells.iter().enumerate() {
+      if y > 0 {
+        writeln!(f)?;
+      }
+      let row: Vec<String> = row
+        .iter()
+        .zip(&widths)
+        .map(|(cell, &width)| pad(cell, width, Align::Right))
+        .collect();
+      write!(f, "{}", row.join(separator))?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // `#` is alive and `.` is dead, one row per line
+  fn life<const W: usize, const H: usize>(text: &str) -> Grid<bool, W, H> {
+    let rows: Vec<&str> = text.split_whitespace().collect();
+    assert_eq!(rows.len(), H);
+    Grid::from_fn(|x, y| rows[y].as_bytes()[x] == b'#')
+  }
+
+  fn cells<const W: usize, const H: usize>(grid: &Grid<bool, W, H>) -> String {
+    format!("{:#}", grid.map(|alive| if alive { '#' } else { '.' }))
+  }
+
+  #[test]
+  fn glider_generations() {
+    let generations = [
+      ".#.... ..#... ###... ...... ...... ......",
+      "...... #.#... .##... .#.... ...... ......",
+      "...... ..#... #.#... .##... ...... ......",
+      "...... .#.... ..##.. .##... ...... ......",
+      "...... ..#... ...#.. .###.. ...... ......",
+    ];
+    let mut grid: Grid<bool, 6, 6> = life(generations[0]);
+    for expected in &generations[1..] {
+      grid = grid.life_step();
+      assert_eq!(cells(&grid), cells(&life::<6, 6>(expected)));
+      assert_eq!(grid.population(), 5);
+    }
+  }
+
+  #[test]
+  fn blinker_and_block() {
+    let horizontal: Grid<bool, 5, 5> = life("..... ..... .###. ..... .....");
+    let vertical: Grid<bool, 5, 5> = life("..... ..#.. ..#.. ..#.. .....");
+    assert_eq!(horizontal.life_step(), vertical);
+    assert_eq!(vertical.life_step(), horizontal);
+
+    let block: Grid<bool, 4, 4> = life(".... .##. .##. ....");
+    assert_eq!(block.life_step(), block);
+    // Cells outside the grid are dead, so a block in the corner stays too
+    let corner: Grid<bool, 3, 3> = life("##. ##. ...");
+    assert_eq!(corner.life_step(), corner);
+    let lonely: Grid<bool, 3, 3> = life("... .#. ...");
+    assert_eq!(lonely.life_step().population(), 0);
+  }
+
+  #[test]
+  fn rotations() {
+    let grid = Grid::new([[1, 2, 3], [4, 5, 6]]);
+    assert_eq!(grid.transpose(), Grid::new([[1, 4], [2, 5], [3, 6]]));
+    assert_eq!(grid.rotate_right(), Grid::new([[4, 1], [5, 2], [6, 3]]));
+    assert_eq!(grid.rotate_left(), Grid::new([[3, 6], [2, 5], [1, 4]]));
+    assert_eq!(grid.rotate_half(), Grid::new([[6, 5, 4], [3, 2, 1]]));
+
+    assert_eq!(grid.rotate_right().rotate_left(), grid);
+    assert_eq!(grid.rotate_right().rotate_right(), grid.rotate_half());
+    assert_eq!(
+      grid.rotate_left().rotate_left().rotate_left(),
+      grid.rotate_right()
+    );
+    assert_eq!(grid.transpose().transpose(), grid);
+  }
+
+  #[test]
+  fn flood_fill() {
+    let walls = [
+      [0, 0, 1, 0, 0],
+      [0, 1, 0, 0, 0],
+      [1, 0, 0, 1, 1],
+      [0, 0, 1, 0, 0],
+    ];
+
+    let mut four = Grid::new(walls);
+    assert_eq!(four.flood_fill(3, 0, 2, Connectivity::Four), 9);
+    assert_eq!(
+      four,
+      Grid::new([
+        [0, 0, 1, 2, 2],
+        [0, 1, 2, 2, 2],
+        [1, 2, 2, 1, 1],
+        [2, 2, 1, 0, 0],
+      ])
+    );
+
+    // The diagonals let the fill through the gaps between the walls
+    let mut eight = Grid::new(walls);
+    assert_eq!(eight.flood_fill(3, 0, 2, Connectivity::Eight), 14);
+    assert_eq!(
+      eight,
+      Grid::new([
+        [2, 2, 1, 2, 2],
+        [2, 1, 2, 2, 2],
+        [1, 2, 2, 1, 1],
+        [2, 2, 1, 2, 2],
+      ])
+    );
+
+    // Nothing to do with the same value, or outside the grid
+    let before = eight;
+    assert_eq!(eight.flood_fill(0, 0, 2, Connectivity::Four), 0);
+    assert_eq!(eight.flood_fill(5, 0, 3, Connectivity::Four), 0);
+    assert_eq!(eight, before);
+  }
+
+  #[test]
+  fn display() {
+    let grid = Grid::new([[1.0, 22.5], [-3.25, 4.0]]);
+    assert_eq!(format!("{:.1}", grid), " 1.0 22.5\n-3.2  4.0");
+    let glider: Grid<bool, 3, 3> = life(".#. ..# ###");
+    assert_eq!(cells(&glider), ".#.\n..#\n###");
+  }
+}
@@ -0,0 +1,359 @@
+/*
+ * Linear algebra on square matrices.
+ *
+ * Gaussian elimination with partial pivoting factors a matrix `A` into
+ * `P * A = L * U`: `P` reorders the rows, `L` is lower triangular with ones
+ * on its diagonal and `U` is upper triangular. With the factors, the
+ * determinant is the product of the diagonal of `U`, and `A * x = b` is
+ * solved by substituting forward through `L`, then backward through `U`.
+ *
+ * The entries can be any `Field`, a `Scalar` that can also be divided. Floats
+ * treat a pivot within rounding error of zero as zero, while exact types only
+ * treat zero as zero, so that `A * A⁻¹ == I` holds exactly for them.
+ */
+
+use super::matrix::{Matrix, Scalar};
+use std::array;
+use std::cmp::Ordering;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Div, Neg};
+
+pub trait Field: Scalar + Div<Output = Self> + Neg<Output = Self> + PartialOrd {
+  // The absolute value, to pick the biggest pivot
+  fn magnitude(self) -> Self;
+
+  // Whether a pivot counts as zero, in a matrix of size `n` whose biggest
+  // entry is `scale`
+  fn is_negligible(self, scale: Self, n: usize) -> bool;
+}
+
+macro_rules! field_float {
+  ($($t:ty)*) => ($(
+    impl Field for $t {
+      fn magnitude(self) -> Self {
+        self.abs()
+      }
+
+      fn is_negligible(self, scale: Self, n: usize) -> bool {
+        self.abs() <= scale * n as $t * <$t>::EPSILON
+      }
+    }
+  )*)
+}
+
+field_float! { f32 f64 }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Error {
+  Singular,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::Singular => write!(f, "the matrix is singular"),
+    }
+  }
+}
+
+// The biggest magnitude among the entries
+fn scale<T: Field, const R: usize, const C: usize>(rows: &[[T; C]; R]) -> T {
+  rows
+    .iter()
+    .flatten()
+    .map(|x| x.magnitude())
+    .fold(T::zero(), |max, x| if x > max { x } else { max })
+}
+
+// The row at or below `from` whose entry in `column` is the biggest
+fn pivot<T: Field, const C: usize>(rows: &[[T; C]], from: usize, column: usize) -> usize {
+  (from..rows.len())
+    .max_by(|&i, &j| {
+      let (a, b) = (rows[i][column].magnitude(), rows[j][column].magnitude());
+      a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+    })
+    .unwrap_or(from)
+}
+
+// Subtract `factor` times row `k` from row `i`, from column `from` on
+fn subtract_row<T: Field, const C: usize>(
+  rows: &mut [[T; C]],
+  k: usize,
+  i: usize,
+  factor: T,
+  from: usize,
+) {
+  let source = rows[k];
+  for (x, &y) in rows[i][from..].iter_mut().zip(&source[from..]) {
+    *x = *x - factor * y;
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Lu<T, const N: usize> {
+  // `L` below the diagonal and `U` on and above it
+  factors: Matrix<T, N, N>,
+  // Row `i` of `P * A` is row `permutation[i]` of `A`
+  permutation: [usize; N],
+  swaps: usize,
+  singular: bool,
+}
+
+impl<T: Field, const N: usize> Lu<T, N> {
+  pub fn new(a: &Matrix<T, N, N>) -> Self {
+    let mut rows = *a.rows();
+    let scale = scale(&rows);
+    let mut permutation = array::from_fn(|i| i);
+    let mut swaps = 0;
+    let mut singular = false;
+
+    for k in 0..N {
+      let p = pivot(&rows, k, k);
+      if rows[p][k].is_negligible(scale, N) {
+        // Nothing to eliminate with, `U` gets a zero on its diagonal
+        singular = true;
+        continue;
+      }
+      if p != k {
+        rows.swap(p, k);
+        permutation.swap(p, k);
+        swaps += 1;
+      }
+      for i in k + 1..N {
+        let factor = rows[i][k] / rows[k][k];
+        subtract_row(&mut rows, k, i, factor, k + 1);
+        rows[i][k] = factor;
+      }
+    }
+
+    Lu {
+      factors: Matrix::new(rows),
+      permutation,
+      swaps,
+      singular,
+    }
+  }
+
+  pub fn is_singular(&self) -> bool {
+    self.singular
+  }
+
+  pub fn lower(&self) -> Matrix<T, N, N> {
+    Matrix::from_fn(|i, j| match i.cmp(&j) {
+      Ordering::Greater => self.factors[(i, j)],
+      Ordering::Equal => T::one(),
+      Ordering::Less => T::zero(),
+    })
+  }
+
+  pub fn upper(&self) -> Matrix<T, N, N> {
+    Matrix::from_fn(|i, j| {
+      if i <= j {
+        self.factors[(i, j)]
+      } else {
+        T::zero()
+      }
+    })
+  }
+
+  pub fn permutation(&self) -> Matrix<T, N, N> {
+    Matrix::from_fn(|i, j| {
+      if self.permutation[i] == j {
+        T::one()
+      } else {
+        T::zero()
+      }
+    })
+  }
+
+  pub fn determinant(&self) -> T {
+    if self.singular {
+      return T::zero();
+    }
+    let product = (0..N).fold(T::one(), |product, i| product * self.factors[(i, i)]);
+    // Every row swap flips the sign
+    if self.swaps.is_multiple_of(2) {
+      product
+    } else {
+      -product
+    }
+  }
+
+  pub fn solve(&self, b: [T; N]) -> Result<[T; N], Error> {
+    if self.singular {
+      return Err(Error::Singular);
+    }
+    // L * y = P * b
+    let mut x: [T; N] = array::from_fn(|i| b[self.permutation[i]]);
+    for i in 0..N {
+      for j in 0..i {
+        x[i] = x[i] - self.factors[(i, j)] * x[j];
+      }
+    }
+    // U * x = y
+    for i in (0..N).rev() {
+      for j in i + 1..N {
+        x[i] = x[i] - self.factors[(i, j)] * x[j];
+      }
+      x[i] = x[i] / self.factors[(i, i)];
+    }
+    Ok(x)
+  }
+
+  pub fn inverse(&self) -> Result<Matrix<T, N, N>, Error> {
+    // Column `j` of the inverse solves `A * x = e_j`
+    let mut columns = [[T::zero(); N]; N];
+    for (j, column) in columns.iter_mut().enumerate() {
+      let e = array::from_fn(|i| if i == j { T::one() } else { T::zero() });
+      *column = self.solve(e)?;
+    }
+    Ok(Matrix::new(columns).transpose())
+  }
+}
+
+pub fn determinant<T: Field, const N: usize>(a: &Matrix<T, N, N>) -> T {
+  Lu::new(a).determinant()
+}
+
+pub fn inverse<T: Field, const N: usize>(a: &Matrix<T, N, N>) -> Result<Matrix<T, N, N>, Error> {
+  Lu::new(a).inverse()
+}
+
+// The `x` for which `a * x == b`
+pub fn solve<T: Field, const N: usize>(a: &Matrix<T, N, N>, b: [T; N]) -> Result<[T; N], Error> {
+  Lu::new(a).solve(b)
+}
+
+// The number of independent rows, from the same elimination brought to row
+// echelon form. Unlike the others it works for any shape.
+pub fn rank<T: Field, const R: usize, const C: usize>(a: &Matrix<T, R, C>) -> usize {
+  let mut rows = *a.rows();
+  let scale = scale(&rows);
+  let mut rank = 0;
+
+  for k in 0..C {
+    if rank == R {
+      break;
+    }
+    let p = pivot(&rows, rank, k);
+    if rows[p][k].is_negligible(scale, R.max(C)) {
+      continue;
+    }
+    rows.swap(p, rank);
+    for i in rank + 1..R {
+      let factor = rows[i][k] / rows[rank][k];
+      subtract_row(&mut rows, rank, i, factor, k);
+    }
+    rank += 1;
+  }
+  rank
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::math::ratio::Ratio;
+
+  type Q = Ratio<i64>;
+
+  fn exact<const R: usize, const C: usize>(rows: [[i64; C]; R]) -> Matrix<Q, R, C> {
+    Matrix::new(rows).map(Q::from)
+  }
+
+  #[test]
+  fn exact_inverse() {
+    let a = exact([[2, 1, 1], [1, 3, 2], [1, 0, 0]]);
+    let a_inverse = inverse(&a).unwrap();
+    assert_eq!(a * a_inverse, Matrix::identity());
+    assert_eq!(a_inverse * a, Matrix::identity());
+    assert_eq!(
+      a_inverse,
+      Matrix::new([[0, 0, 1], [-2, 1, 3], [3, -1, -5]]).map(Q::from)
+    );
+    assert_eq!(determinant(&a), Q::from(-1));
+
+    // Entries that are not integers
+    let hilbert: Matrix<Q, 4, 4> = Matrix::from_fn(|i, j| Q::new(1, (i + j + 1) as i64).unwrap());
+    let hilbert_inverse = inverse(&hilbert).unwrap();
+    assert_eq!(hilbert * hilbert_inverse, Matrix::identity());
+    assert_eq!(hilbert_inverse[(0, 0)], Q::from(16));
+    assert_eq!(determinant(&hilbert), Q::new(1, 6_048_000).unwrap());
+  }
+
+  #[test]
+  fn determinant_sign_follows_the_swaps() {
+    // The first pivot is in the last row, and every swap flips the sign
+    let a = exact([[0, 1, 0], [0, 0, 1], [1, 0, 0]]);
+    assert_eq!(determinant(&a), Q::from(1));
+    let b = exact([[0, 1, 0], [1, 0, 0], [0, 0, 1]]);
+    assert_eq!(determinant(&b), Q::from(-1));
+    assert_eq!(determinant(&exact([[3, 8], [4, 6]])), Q::from(-14));
+  }
+
+  #[test]
+  fn factors_rebuild_the_matrix() {
+    let a = exact([[1, 2, 3], [4, 5, 6], [7, 8, 10]]);
+    let lu = Lu::new(&a);
+    assert_eq!(lu.permutation() * a, lu.lower() * lu.upper());
+    for i in 0..3 {
+      assert_eq!(lu.lower()[(i, i)], Q::from(1));
+    }
+  }
+
+  #[test]
+  fn solve_exactly() {
+    let a = exact([[2, 1, -1], [-3, -1, 2], [-2, 1, 2]]);
+    let b = [8, -11, -3].map(Q::from);
+    assert_eq!(solve(&a, b), Ok([2, 3, -1].map(Q::from)));
+  }
+
+  #[test]
+  fn solve_floats() {
+    let a: Matrix<f64, 3, 3> = Matrix::new([[4.0, -2.0, 1.0], [-2.0, 4.0, -2.0], [1.0, -2.0, 4.0]]);
+    let x: [f64; 3] = [1.5, -0.25, 2.0];
+    let solved = solve(&a, a * x).unwrap();
+    for (found, expected) in solved.iter().zip(&x) {
+      assert!((found - expected).abs() < 1e-12, "{:?}", solved);
+    }
+    let identity = a * inverse(&a).unwrap();
+    for i in 0..3 {
+      for j in 0..3 {
+        let expected: f64 = if i == j { 1.0 } else { 0.0 };
+        assert!((identity[(i, j)] - expected).abs() < 1e-12);
+      }
+    }
+  }
+
+  #[test]
+  fn singular_matrices() {
+    // The last row is the sum of the other two
+    let a = exact([[1, 2, 3], [4, 5, 6], [5, 7, 9]]);
+    assert_eq!(determinant(&a), Q::from(0));
+    assert_eq!(inverse(&a), Err(Error::Singular));
+    assert_eq!(solve(&a, [1, 2, 3].map(Q::from)), Err(Error::Singular));
+    assert!(Lu::new(&a).is_singular());
+
+    // Singular, but only within rounding error for floats
+    let b = Matrix::new([[0.1, 0.2, 0.3], [0.4, 0.5, 0.6], [0.5, 0.7, 0.9]]);
+    assert_eq!(inverse(&b), Err(Error::Singular));
+    assert_eq!(determinant(&b), 0.0);
+    assert_eq!(inverse(&Matrix::<f64, 2, 2>::zero()), Err(Error::Singular));
+  }
+
+  #[test]
+  fn rank_of_any_shape() {
+    assert_eq!(rank(&exact([[1, 2, 3], [4, 5, 6], [5, 7, 9]])), 2);
+    assert_eq!(rank(&exact([[1, 2, 3], [4, 5, 6], [7, 8, 10]])), 3);
+    assert_eq!(rank(&exact([[1, 2, 3, 4], [2, 4, 6, 8]])), 1);
+    assert_eq!(rank(&exact([[1, 0], [0, 1], [1, 1]])), 2);
+    assert_eq!(rank(&Matrix::<Q, 2, 3>::zero()), 0);
+    assert_eq!(
+      rank(&Matrix::new([
+        [0.1, 0.2, 0.3],
+        [0.4, 0.5, 0.6],
+        [0.5, 0.7, 0.9]
+      ])),
+      2
+    );
+  }
+}
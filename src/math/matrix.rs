@@ -0,0 +1,241 @@
+/*
+ * Matrices of any size.
+ *
+ * The `tuples` activity uses `Matrix(f32, f32, f32, f32)` for a 2×2 matrix.
+ * `Matrix<T, R, C>` keeps the size in the type with const generics, so that
+ * adding a 2×3 matrix to a 3×2 one, or multiplying two matrices whose inner
+ * sizes differ, does not compile.
+ *
+ * The entries can be any `Scalar`: a number with a zero, a one, and the
+ * arithmetic operators.
+ */
+
+use crate::format::json::{DecodeError, FromJson, ToJson, Value};
+use crate::format::table::{pad, Align};
+use crate::format::width::str_width;
+use std::array;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Index, IndexMut, Mul, Neg, Sub};
+
+pub trait Scalar:
+  Copy + PartialEq + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+  fn zero() -> Self;
+  fn one() -> Self;
+}
+
+macro_rules! scalar {
+  ($zero:expr, $one:expr; $($t:ty)*) => ($(
+    impl Scalar for $t {
+      fn zero() -> Self {
+        $zero
+      }
+
+      fn one() -> Self {
+        $one
+      }
+    }
+  )*)
+}
+
+scalar! { 0, 1; i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+scalar! { 0.0, 1.0; f32 f64 }
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Matrix<T, const R: usize, const C: usize> {
+  rows: [[T; C]; R],
+}
+
+impl<T, const R: usize, const C: usize> Matrix<T, R, C> {
+  pub fn new(rows: [[T; C]; R]) -> Self {
+    Matrix { rows }
+  }
+
+  // The entry at `(i, j)` is `f(i, j)`
+  pub fn from_fn<F: FnMut(usize, usize) -> T>(mut f: F) -> Self {
+    Matrix {
+      rows: array::from_fn(|i| array::from_fn(|j| f(i, j))),
+    }
+  }
+
+  pub fn rows(&self) -> &[[T; C]; R] {
+    &self.rows
+  }
+
+  pub fn into_rows(self) -> [[T; C]; R] {
+    self.rows
+  }
+
+  pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Matrix<U, R, C> {
+    Matrix {
+      rows: self.rows.map(|row| row.map(&mut f)),
+    }
+  }
+}
+
+impl<T: Copy, const R: usize, const C: usize> Matrix<T, R, C> {
+  pub fn row(&self, i: usize) -> [T; C] {
+    self.rows[i]
+  }
+
+  pub fn column(&self, j: usize) -> [T; R] {
+    array::from_fn(|i| self.rows[i][j])
+  }
+
+  pub fn transpose(&self) -> Matrix<T, C, R> {
+    Matrix::from_fn(|i, j| self.rows[j][i])
+  }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Matrix<T, R, C> {
+  pub fn zero() -> Self {
+    Matrix::from_fn(|_, _| T::zero())
+  }
+
+  // Multiply every entry by `k`
+  pub fn scale(&self, k: T) -> Self {
+    self.map(|x| x * k)
+  }
+}
+
+impl<T: Scalar, const N: usize> Matrix<T, N, N> {
+  pub fn identity() -> Self {
+    Matrix::from_fn(|i, j| if i == j { T::one() } else { T::zero() })
+  }
+
+  // The sum of the diagonal
+  pub fn trace(&self) -> T {
+    (0..N).fold(T::zero(), |sum, i| sum + self.rows[i][i])
+  }
+}
+
+impl<T, const R: usize, const C: usize> Index<(usize, usize)> for Matrix<T, R, C> {
+  type Output = T;
+
+  fn index(&self, (i, j): (usize, usize)) -> &T {
+    &self.rows[i][j]
+  }
+}
+
+impl<T, const R: usize, const C: usize> IndexMut<(usize, usize)> for Matrix<T, R, C> {
+  fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut T {
+    &mut self.rows[i][j]
+  }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Add for Matrix<T, R, C> {
+  type Output = Self;
+
+  fn add(self, other: Self) -> Self {
+    Matrix::from_fn(|i, j| self.rows[i][j] + other.rows[i][j])
+  }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Sub for Matrix<T, R, C> {
+  type Output = Self;
+
+  fn sub(self, other: Self) -> Self {
+    Matrix::from_fn(|i, j| self.rows[i][j] - other.rows[i][j])
+  }
+}
+
+impl<T: Scalar + Neg<Output = T>, const R: usize, const C: usize> Neg for Matrix<T, R, C> {
+  type Output = Self;
+
+  fn neg(self) -> Self {
+    self.map(|x| -x)
+  }
+}
+
+// R×C times C×K is R×K
+impl<T: Scalar, const R: usize, const C: usize, const K: usize> Mul<Matrix<T, C, K>>
+  for Matrix<T, R, C>
+{
+  type Output = Matrix<T, R, K>;
+
+  fn mul(self, other: Matrix<T, C, K>) -> Matrix<T, R, K> {
+    Matrix::from_fn(|i, j| {
+      (0..C).fold(T::zero(), |sum, k| sum + self.rows[i][k] * other.rows[k][j])
+    })
+  }
+}
+
+// A vector is multiplied as a column
+impl<T: Scalar, const R: usize, const C: usize> Mul<[T; C]> for Matrix<T, R, C> {
+  type Output = [T; R];
+
+  fn mul(self, vector: [T; C]) -> [T; R] {
+    array::from_fn(|i| (0..C).fold(T::zero(), |sum, k| sum + self.rows[i][k] * vector[k]))
+  }
+}
+
+impl<T: Scalar, const R: usize, const C: usize> Mul<T> for Matrix<T, R, C> {
+  type Output = Self;
+
+  fn mul(self, k: T) -> Self {
+    self.scale(k)
+  }
+}
+
+impl<T: Scalar + Div<Output = T>, const R: usize, const C: usize> Div<T> for Matrix<T, R, C> {
+  type Output = Self;
+
+  fn div(self, k: T) -> Self {
+    self.map(|x| x / k)
+  }
+}
+
+// One row per line, each column right-aligned to its widest entry. The
+// precision, as in `{:.2}`, is applied to every entry.
+impl<T: Display, const R: usize, const C: usize> Display for Matrix<T, R, C> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let cells: Vec<Vec<String>> = self
+      .rows
+      .iter()
+      .map(|row| {
+        row
+          .iter()
+          .map(|x| match f.precision() {
+            Some(precision) => format!("{:.*}", precision, x),
+            None => x.to_string(),
+          })
+          .collect()
+      })
+      .collect();
+    let widths: Vec<usize> = (0..C)
+      .map(|j| {
+        cells
+          .iter()
+          .map(|row| str_width(&row[j]))
+          .max()
+          .unwrap_or(0)
+      })
+      .collect();
+
+    for (i, row) in cells.iter().enumerate() {
+      if i > 0 {
+        writeln!(f)?;
+      }
+      write!(f, "(")?;
+      for (cell, &width) in row.iter().zip(&widths) {
+        write!(f, " {}", pad(cell, width, Align::Right))?;
+      }
+      write!(f, " )")?;
+    }
+    Ok(())
+  }
+}
+
+// An array of `R` rows of `C` entries: `[[1, 2], [3, 4]]`
+impl<T: ToJson, const R: usize, const C: usize> ToJson for Matrix<T, R, C> {
+  fn to_json(&self) -> Value {
+    self.rows.to_json()
+  }
+}
+
+// The number of rows and columns must match the type
+impl<T: FromJson, const R: usize, const C: usize> FromJson for Matrix<T, R, C> {
+  fn from_json(value: &Value) -> Result<Self, DecodeError> {
+    Ok(Matrix::new(FromJson::from_json(value)?))
+  }
+}
@@ -0,0 +1,8 @@
+pub mod bigint;
+pub mod complex;
+pub mod grid;
+pub mod linalg;
+pub mod matrix;
+pub mod random;
+pub mod ratio;
+pub mod stats;
@@ -0,0 +1,61 @@
+/*
+ * A small pseudo-random number generator.
+ *
+ * The examples that check themselves on random inputs need the same inputs
+ * on every run, so this is xorshift64*, seeded by the caller: fast, tiny and
+ * good enough for shuffling, but not for anything secret.
+ */
+
+#[derive(Clone, Debug)]
+pub struct XorShift {
+  state: u64,
+}
+
+impl XorShift {
+  pub fn new(seed: u64) -> XorShift {
+    // The state must never be zero, or it stays zero
+    let mut rng = XorShift {
+      state: (seed ^ 0x9E37_79B9_7F4A_7C15).max(1),
+    };
+    // Mix the seed, so that close seeds give unrelated sequences
+    for _ in 0..4 {
+      rng.next_u64();
+    }
+    rng
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    let mut x = self.state;
+    x ^= x >> 12;
+    x ^= x << 25;
+    x ^= x >> 27;
+    self.state = x;
+    x.wrapping_mul(0x2545_F491_4F6C_DD1D)
+  }
+
+  pub fn next_u128(&mut self) -> u128 {
+    u128::from(self.next_u64()) << 64 | u128::from(self.next_u64())
+  }
+
+  // A float in `0.0..1.0`, from the 53 bits an `f64` can hold exactly
+  pub fn next_f64(&mut self) -> f64 {
+    (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+  }
+
+  // A number in `0..bound`, `bound` must not be zero
+  pub fn below(&mut self, bound: u64) -> u64 {
+    self.next_u64() % bound
+  }
+
+  pub fn chance(&mut self, numerator: u64, denominator: u64) -> bool {
+    self.below(denominator) < numerator
+  }
+
+  // Put the items in a random order (Fisher-Yates)
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      let j = self.below(i as u64 + 1) as usize;
+      items.swap(i, j);
+    }
+  }
+}
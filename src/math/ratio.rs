@@ -0,0 +1,362 @@
+/*
+ * Exact rational numbers.
+ *
+ * `1.1f32` is stored as the nearest binary fraction, so the matrix in the
+ * `tuples` activity prints float noise as soon as it is multiplied. A
+ * `Ratio` keeps a numerator and a denominator instead:
+ *
+ *  - it is always reduced, with the sign on the numerator: `2/-4` is `-1/2`
+ *  - `checked_add` and friends return `None` on overflow, and the operators
+ *    panic like the integer ones do in debug builds
+ *  - floats convert through continued fractions, `0.1` is `1/10`
+ *  - `{}` prints `3/2`, `{:#}` prints the mixed number `1 1/2`
+ *  - `"3/2"`, `"1 1/2"`, `"-4"` and `"1.5"` all parse to the same value
+ *
+ * Like the integer primitives, it is implemented for each signed integer
+ * type by a macro.
+ */
+
+use super::linalg::Field;
+use super::matrix::Scalar;
+use crate::format::number::pad_number;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Ratio<T> {
+  numer: T,
+  // Always positive
+  denom: T,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  ZeroDenominator,
+  Overflow,
+  Invalid(String),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::ZeroDenominator => write!(f, "the denominator is zero"),
+      Error::Overflow => write!(f, "the value does not fit"),
+      Error::Invalid(text) => write!(f, "`{}` is not a fraction", text),
+    }
+  }
+}
+
+// Euclid's algorithm, on unsigned values
+macro_rules! gcd_unsigned {
+  ($a:expr, $b:expr) => {{
+    let (mut a, mut b) = ($a, $b);
+    while b != 0 {
+      let r = a % b;
+      a = b;
+      b = r;
+    }
+    a
+  }};
+}
+
+macro_rules! ratio {
+  ($($t:ident $u:ident)*) => ($(
+    impl Ratio<$t> {
+      // Reduce `numer / denom`, moving the sign to the numerator
+      pub fn new(numer: $t, denom: $t) -> Result<Self, Error> {
+        if denom == 0 {
+          return Err(Error::ZeroDenominator);
+        }
+        let negative = (numer < 0) != (denom < 0);
+        // In the unsigned type, so that `MIN` has a magnitude
+        let (n, d) = (numer.unsigned_abs(), denom.unsigned_abs());
+        let g = gcd_unsigned!(n, d);
+        let (n, d) = (n / g, d / g);
+
+        let denom = $t::try_from(d).map_err(|_| Error::Overflow)?;
+        let numer = if negative {
+          // `n` may be the magnitude of `MIN`, which wraps to itself
+          if n > $t::MIN.unsigned_abs() {
+            return Err(Error::Overflow);
+          }
+          (n as $t).wrapping_neg()
+        } else {
+          $t::try_from(n).map_err(|_| Error::Overflow)?
+        };
+        Ok(Ratio { numer, denom })
+      }
+
+      pub fn numer(&self) -> $t {
+        self.numer
+      }
+
+      pub fn denom(&self) -> $t {
+        self.denom
+      }
+
+      pub fn is_integer(&self) -> bool {
+        self.denom == 1
+      }
+
+      pub fn to_f64(&self) -> f64 {
+        self.numer as f64 / self.denom as f64
+      }
+
+      // Swap numerator and denominator
+      pub fn recip(&self) -> Option<Self> {
+        Ratio::<$t>::new(self.denom, self.numer).ok()
+      }
+
+      pub fn checked_neg(self) -> Option<Self> {
+        Some(Ratio {
+          numer: self.numer.checked_neg()?,
+          denom: self.denom,
+        })
+      }
+
+      pub fn checked_abs(self) -> Option<Self> {
+        if self.numer < 0 {
+          self.checked_neg()
+        } else {
+          Some(self)
+        }
+      }
+
+      pub fn checked_add(self, other: Self) -> Option<Self> {
+        // Only multiply by what the denominators don't share
+        let g = gcd_unsigned!(self.denom as $u, other.denom as $u) as $t;
+        let (left, right) = (self.denom / g, other.denom / g);
+        let numer = self
+          .numer
+          .checked_mul(right)?
+          .checked_add(other.numer.checked_mul(left)?)?;
+        Ratio::<$t>::new(numer, self.denom.checked_mul(right)?).ok()
+      }
+
+      pub fn checked_sub(self, other: Self) -> Option<Self> {
+        self.checked_add(other.checked_neg()?)
+      }
+
+      pub fn checked_mul(self, other: Self) -> Option<Self> {
+        // Reduce across before multiplying: (a/b) * (c/d) = (a/d) * (c/b)
+        let g1 = gcd_unsigned!(self.numer.unsigned_abs(), other.denom as $u) as $t;
+        let g2 = gcd_unsigned!(other.numer.unsigned_abs(), self.denom as $u) as $t;
+        let numer = (self.numer / g1).checked_mul(other.numer / g2)?;
+        let denom = (self.denom / g2).checked_mul(other.denom / g1)?;
+        Ratio::<$t>::new(numer, denom).ok()
+      }
+
+      // `None` on overflow and when dividing by zero
+      pub fn checked_div(self, other: Self) -> Option<Self> {
+        self.checked_mul(other.recip()?)
+      }
+
+      // The closest fraction to `x` whose denominator is at most `max_denom`,
+      // among the convergents of its continued fraction. `None` for NaN,
+      // infinities and values that don't fit.
+      pub fn from_f64(x: f64, max_denom: $t) -> Option<Self> {
+        if !x.is_finite() {
+          return None;
+        }
+        let max_denom = max_denom.max(1) as i128;
+        // The last two convergents h/k
+        let (mut h0, mut h1) = (0i128, 1i128);
+        let (mut k0, mut k1) = (1i128, 0i128);
+        let mut rest = x;
+
+        for _ in 0..64 {
+          let whole = rest.floor();
+          if whole.abs() >= 1e38 {
+            return None;
+          }
+          let a = whole as i128;
+          let h = a.checked_mul(h1)?.checked_add(h0)?;
+          let k = a.checked_mul(k1)?.checked_add(k0)?;
+          if k > max_denom {
+            break;
+          }
+          h0 = h1;
+          h1 = h;
+          k0 = k1;
+          k1 = k;
+
+          let fraction = rest - whole;
+          if fraction == 0.0 || h as f64 / k as f64 == x {
+            break;
+          }
+          rest = 1.0 / fraction;
+        }
+        Ratio::<$t>::new($t::try_from(h1).ok()?, $t::try_from(k1).ok()?).ok()
+      }
+    }
+
+    impl From<$t> for Ratio<$t> {
+      fn from(n: $t) -> Self {
+        Ratio { numer: n, denom: 1 }
+      }
+    }
+
+    impl Ord for Ratio<$t> {
+      // Compare whole parts, then the reciprocals of what is left, which is
+      // how continued fractions compare and never overflows
+      fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (self.numer, self.denom);
+        let (c, d) = (other.numer, other.denom);
+        let (q1, r1) = (a.div_euclid(b), a.rem_euclid(b));
+        let (q2, r2) = (c.div_euclid(d), c.rem_euclid(d));
+        match (q1.cmp(&q2), r1, r2) {
+          (Ordering::Equal, 0, 0) => Ordering::Equal,
+          (Ordering::Equal, 0, _) => Ordering::Less,
+          (Ordering::Equal, _, 0) => Ordering::Greater,
+          // r1/b < r2/d when b/r1 > d/r2
+          (Ordering::Equal, _, _) => {
+            let left = Ratio { numer: b, denom: r1 };
+            let right = Ratio { numer: d, denom: r2 };
+            right.cmp(&left)
+          }
+          (ordering, _, _) => ordering,
+        }
+      }
+    }
+
+    impl PartialOrd for Ratio<$t> {
+      fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+      }
+    }
+
+    impl Add for Ratio<$t> {
+      type Output = Self;
+
+      fn add(self, other: Self) -> Self {
+        self.checked_add(other).expect("attempt to add with overflow")
+      }
+    }
+
+    impl Sub for Ratio<$t> {
+      type Output = Self;
+
+      fn sub(self, other: Self) -> Self {
+        self.checked_sub(other).expect("attempt to subtract with overflow")
+      }
+    }
+
+    impl Mul for Ratio<$t> {
+      type Output = Self;
+
+      fn mul(self, other: Self) -> Self {
+        self.checked_mul(other).expect("attempt to multiply with overflow")
+      }
+    }
+
+    impl Div for Ratio<$t> {
+      type Output = Self;
+
+      fn div(self, other: Self) -> Self {
+        if other.numer == 0 {
+          panic!("attempt to divide by zero");
+        }
+        self.checked_div(other).expect("attempt to divide with overflow")
+      }
+    }
+
+    impl Neg for Ratio<$t> {
+      type Output = Self;
+
+      fn neg(self) -> Self {
+        self.checked_neg().expect("attempt to negate with overflow")
+      }
+    }
+
+    impl Scalar for Ratio<$t> {
+      fn zero() -> Self {
+        Ratio::from(0)
+      }
+
+      fn one() -> Self {
+        Ratio::from(1)
+      }
+    }
+
+    impl Field for Ratio<$t> {
+      fn magnitude(self) -> Self {
+        self.checked_abs().expect("attempt to negate with overflow")
+      }
+
+      // Exact, only zero is zero
+      fn is_negligible(self, _scale: Self, _n: usize) -> bool {
+        self.numer == 0
+      }
+    }
+
+    // `3/2`, or the mixed number `1 1/2` with `{:#}`
+    impl Display for Ratio<$t> {
+      fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let whole = self.numer / self.denom;
+        let rest = (self.numer % self.denom).unsigned_abs();
+        let text = if self.denom == 1 {
+          self.numer.to_string()
+        } else if f.alternate() && whole != 0 {
+          format!("{} {}/{}", whole, rest, self.denom)
+        } else {
+          format!("{}/{}", self.numer, self.denom)
+        };
+        pad_number(f, &text)
+      }
+    }
+
+    impl FromStr for Ratio<$t> {
+      type Err = Error;
+
+      fn from_str(text: &str) -> Result<Self, Error> {
+        let invalid = || Error::Invalid(text.to_owned());
+        let int = |s: &str| {
+          s.parse::<$t>().map_err(|e| match e.kind() {
+            std::num::IntErrorKind::PosOverflow | std::num::IntErrorKind::NegOverflow => {
+              Error::Overflow
+            }
+            _ => invalid(),
+          })
+        };
+        let text = text.trim();
+
+        let parts: Vec<&str> = text.split_whitespace().collect();
+        if let [whole, fraction] = parts[..] {
+          // A mixed number, the sign of the whole part applies to both
+          let whole = Ratio::from(int(whole)?);
+          let fraction: Ratio<$t> = fraction.parse()?;
+          if !fraction.is_integer() && fraction.numer > 0 {
+            let sum = if text.starts_with('-') {
+              whole.checked_sub(fraction)
+            } else {
+              whole.checked_add(fraction)
+            };
+            return sum.ok_or(Error::Overflow);
+          }
+          return Err(invalid());
+        }
+
+        if let Some((numer, denom)) = text.split_once('/') {
+          return Ratio::<$t>::new(int(numer)?, int(denom)?);
+        }
+        if let Some((whole, digits)) = text.split_once('.') {
+          // `12.34` is `1234/100`
+          if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+          }
+          let denom = (10 as $t)
+            .checked_pow(digits.len() as u32)
+            .ok_or(Error::Overflow)?;
+          let numer = int(&format!("{}{}", whole, digits))?;
+          return Ratio::<$t>::new(numer, denom);
+        }
+        Ok(Ratio::from(int(text)?))
+      }
+    }
+  )*)
+}
+
+ratio! { i8 u8 i16 u16 i32 u32 i64 u64 i128 u128 isize usize }
@@ -0,0 +1,378 @@
+/*
+ * Descriptive statistics of a slice.
+ *
+ * `analyze_slice` in `arrays_and_slices` reads `slice[0]`, which panics on an
+ * empty slice. `analyze` checks instead, and works out in one call:
+ *
+ *  - the count, the minimum and the maximum
+ *  - the sum, in the type of the items, or `None` if it overflows
+ *  - the mean, variance and standard deviation, computed in `f64` one item at
+ *    a time (Welford's method) on items scaled down by a power of two, so
+ *    that they only overflow when the result itself is out of range. With
+ *    infinite items the mean is their infinity, or NaN when they have both
+ *    signs, and the variance is NaN.
+ *  - the median and any percentile, interpolated between the closest items
+ *  - the modes, every value that appears the most
+ *
+ * It is implemented for every integer and float type by a macro. A float
+ * slice with a NaN has no order, so it is an error too.
+ */
+
+use crate::format::table::{Align, Column, Table};
+use std::fmt::{self, Display, Formatter};
+
+// A primitive that can be analyzed
+pub trait Number: Copy + PartialOrd + Display {
+  // `None` when the sum doesn't fit in the type
+  fn checked_add(self, other: Self) -> Option<Self>;
+
+  fn to_f64(self) -> f64;
+
+  fn is_nan(self) -> bool;
+}
+
+macro_rules! number_int {
+  ($($t:ident)*) => ($(
+    impl Number for $t {
+      fn checked_add(self, other: $t) -> Option<$t> {
+        $t::checked_add(self, other)
+      }
+
+      fn to_f64(self) -> f64 {
+        self as f64
+      }
+
+      fn is_nan(self) -> bool {
+        false
+      }
+    }
+  )*)
+}
+
+macro_rules! number_float {
+  ($($t:ident)*) => ($(
+    impl Number for $t {
+      // Floats don't wrap: finite values overflow to infinity
+      fn checked_add(self, other: $t) -> Option<$t> {
+        let sum = self + other;
+        if sum.is_infinite() && self.is_finite() && other.is_finite() {
+          None
+        } else {
+          Some(sum)
+        }
+      }
+
+      fn to_f64(self) -> f64 {
+        f64::from(self)
+      }
+
+      fn is_nan(self) -> bool {
+        $t::is_nan(self)
+      }
+    }
+  )*)
+}
+
+number_int! { i8 i16 i32 i64 i128 isize u8 u16 u32 u64 u128 usize }
+number_float! { f32 f64 }
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  Empty,
+  // The position of the first NaN
+  NotANumber(usize),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::Empty => write!(f, "the slice is empty"),
+      Error::NotANumber(index) => write!(f, "item {} is NaN, which can't be ordered", index),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stats<T> {
+  pub count: usize,
+  pub min: T,
+  pub max: T,
+  pub sum: Option<T>,
+  pub mean: f64,
+  // Of the population, divided by `count`
+  pub variance: f64,
+  pub median: f64,
+  // Every value that appears `mode_count` times, in increasing order
+  pub modes: Vec<T>,
+  pub mode_count: usize,
+  // The items in increasing order, for the percentiles
+  sorted: Vec<T>,
+}
+
+pub fn analyze<T: Number>(slice: &[T]) -> Result<Stats<T>, Error> {
+  if slice.is_empty() {
+    return Err(Error::Empty);
+  }
+  if let Some(index) = slice.iter().position(|x| x.is_nan()) {
+    return Err(Error::NotANumber(index));
+  }
+
+  let mut sorted = slice.to_vec();
+  // Without NaN, every pair of items is ordered
+  sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+
+  let sum = slice[1..]
+    .iter()
+    .try_fold(slice[0], |sum, &x| sum.checked_add(x));
+
+  let (mean, variance) = mean_and_variance(slice);
+
+  let (modes, mode_count) = modes(&sorted);
+  let mut stats = Stats {
+    count: slice.len(),
+    min: sorted[0],
+    max: sorted[sorted.len() - 1],
+    sum,
+    mean,
+    variance,
+    median: 0.0,
+    modes,
+    mode_count,
+    sorted,
+  };
+  stats.median = stats.percentile(50.0).unwrap_or_default();
+  Ok(stats)
+}
+
+fn mean_and_variance<T: Number>(slice: &[T]) -> (f64, f64) {
+  let items: Vec<f64> = slice.iter().map(|x| x.to_f64()).collect();
+  // `inf - inf` is NaN, so infinities can't go through the differences below
+  if items.iter().any(|x| x.is_infinite()) {
+    let positive = items.contains(&f64::INFINITY);
+    let negative = items.contains(&f64::NEG_INFINITY);
+    let mean = match (positive, negative) {
+      (true, false) => f64::INFINITY,
+      (false, true) => f64::NEG_INFINITY,
+      _ => f64::NAN,
+    };
+    return (mean, f64::NAN);
+  }
+
+  // The power of two of the largest item, which scales every item to less
+  // than 2 without rounding: the difference of `f64::MAX` and `-f64::MAX`
+  // doesn't fit in an `f64`, but that of 1.99.. and -1.99.. does
+  let largest = items
+    .iter()
+    .fold(0.0, |largest: f64, x| largest.max(x.abs()));
+  let exponent = largest.to_bits() >> 52;
+  let scale = if exponent == 0 {
+    1.0
+  } else {
+    f64::from_bits(exponent << 52)
+  };
+
+  // Welford's method, `m2` is the sum of the squared differences to the mean
+  let (mut mean, mut m2) = (0.0, 0.0);
+  for (i, x) in items.iter().enumerate() {
+    let x = x / scale;
+    let delta = x - mean;
+    mean += delta / (i + 1) as f64;
+    m2 += delta * (x - mean);
+  }
+  (mean * scale, m2 / items.len() as f64 * scale * scale)
+}
+
+// The values of the longest runs of equal items, and their length
+fn modes<T: Number>(sorted: &[T]) -> (Vec<T>, usize) {
+  let mut modes = Vec::new();
+  let mut best = 0;
+  for run in sorted.chunk_by(|a, b| a == b) {
+    if run.len() > best {
+      best = run.len();
+      modes.clear();
+    }
+    if run.len() == best {
+      modes.push(run[0]);
+    }
+  }
+  (modes, best)
+}
+
+impl<T: Number> Stats<T> {
+  pub fn std_dev(&self) -> f64 {
+    self.variance.sqrt()
+  }
+
+  // Of a sample, divided by `count - 1`. `None` for a single item.
+  pub fn sample_variance(&self) -> Option<f64> {
+    if self.count < 2 {
+      return None;
+    }
+    Some(self.variance * self.count as f64 / (self.count - 1) as f64)
+  }
+
+  // The value below which `p` percent of the items fall, interpolated
+  // linearly between the two closest items. `None` outside of `0..=100`.
+  pub fn percentile(&self, p: f64) -> Option<f64> {
+    if !(0.0..=100.0).contains(&p) {
+      return None;
+    }
+    let rank = p / 100.0 * (self.count - 1) as f64;
+    let (below, above) = (rank.floor() as usize, rank.ceil() as usize);
+    let (low, high) = (self.sorted[below].to_f64(), self.sorted[above].to_f64());
+    // Between two equal infinities, `high - low` would be NaN
+    if low == high {
+      return Some(low);
+    }
+    let t = rank - below as f64;
+    let width = high - low;
+    if width.is_infinite() && low.is_finite() && high.is_finite() {
+      // Too far apart for their difference, weigh each one instead
+      return Some(low * (1.0 - t) + high * t);
+    }
+    Some(low + width * t)
+  }
+
+  // Every statistic, the precision applies to the ones computed in `f64`
+  pub fn table(&self, precision: Option<usize>) -> Table {
+    let mut table = Table::new(vec![
+      Column::new("Statistic", Align::Left),
+      Column::new("Value", Align::Right),
+    ]);
+    let real = |x: f64| match precision {
+      Some(precision) => format!("{:.*}", precision, x),
+      None => x.to_string(),
+    };
+    let mut row = |name: &str, value: String| table.push(vec![name.to_owned(), value]);
+
+    row("count", self.count.to_string());
+    row("min", self.min.to_string());
+    row("max", self.max.to_string());
+    row(
+      "sum",
+      match self.sum {
+        Some(sum) => sum.to_string(),
+        None => format!("overflows {}", std::any::type_name::<T>()),
+      },
+    );
+    row("mean", real(self.mean));
+    row("median", real(self.median));
+    row(
+      "mode",
+      if self.mode_count == 1 && self.count > 1 {
+        "none, no value repeats".to_owned()
+      } else {
+        let modes: Vec<String> = self.modes.iter().map(|m| m.to_string()).collect();
+        format!("{} ({}×)", modes.join(", "), self.mode_count)
+      },
+    );
+    row("variance", real(self.variance));
+    row("std dev", real(self.std_dev()));
+    for &p in [25.0, 75.0, 90.0, 99.0].iter() {
+      if let Some(value) = self.percentile(p) {
+        row(&format!("p{}", p), real(value));
+      }
+    }
+    table
+  }
+}
+
+// A table of every statistic, `{:.2}` rounds the ones computed in `f64`
+impl<T: Number> Display for Stats<T> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{}", self.table(f.precision()))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn empty_and_single() {
+    assert_eq!(analyze::<i32>(&[]), Err(Error::Empty));
+    assert_eq!(analyze::<f64>(&[]), Err(Error::Empty));
+
+    let stats = analyze(&[7u8]).unwrap();
+    assert_eq!(
+      (stats.count, stats.min, stats.max, stats.sum),
+      (1, 7, 7, Some(7))
+    );
+    assert_eq!((stats.mean, stats.variance, stats.median), (7.0, 0.0, 7.0));
+    assert_eq!((stats.modes.clone(), stats.mode_count), (vec![7], 1));
+    assert_eq!(stats.sample_variance(), None);
+    assert_eq!(stats.percentile(0.0), Some(7.0));
+    assert_eq!(stats.percentile(100.0), Some(7.0));
+    assert_eq!(stats.percentile(101.0), None);
+  }
+
+  #[test]
+  fn small_slice() {
+    let stats = analyze(&[2, 4, 4, 4, 5, 5, 7, 9]).unwrap();
+    assert_eq!(stats.sum, Some(40));
+    assert_eq!(stats.mean, 5.0);
+    assert_eq!(stats.variance, 4.0);
+    assert_eq!(stats.std_dev(), 2.0);
+    assert_eq!(stats.median, 4.5);
+    assert_eq!((stats.modes.clone(), stats.mode_count), (vec![4], 3));
+    assert_eq!(stats.percentile(25.0), Some(4.0));
+    assert_eq!(stats.sample_variance(), Some(32.0 / 7.0));
+  }
+
+  #[test]
+  fn nan_is_an_error() {
+    assert_eq!(
+      analyze(&[1.0, f64::NAN, f64::NAN]),
+      Err(Error::NotANumber(1))
+    );
+    assert_eq!(analyze(&[f32::NAN]), Err(Error::NotANumber(0)));
+  }
+
+  #[test]
+  fn infinities() {
+    let stats = analyze(&[f64::INFINITY, 1.0]).unwrap();
+    assert_eq!(stats.mean, f64::INFINITY);
+    assert!(stats.variance.is_nan());
+    assert_eq!(stats.median, f64::INFINITY);
+    assert_eq!(stats.sum, Some(f64::INFINITY));
+
+    let stats = analyze(&[1.0, f64::NEG_INFINITY, f64::NEG_INFINITY]).unwrap();
+    assert_eq!(stats.mean, f64::NEG_INFINITY);
+    assert_eq!(stats.median, f64::NEG_INFINITY);
+    assert_eq!(stats.min, f64::NEG_INFINITY);
+
+    let stats = analyze(&[f32::INFINITY, f32::NEG_INFINITY]).unwrap();
+    assert!(stats.mean.is_nan());
+    assert_eq!(stats.percentile(0.0), Some(f64::NEG_INFINITY));
+    assert_eq!(stats.percentile(100.0), Some(f64::INFINITY));
+  }
+
+  #[test]
+  fn overflow() {
+    let stats = analyze(&[i64::MAX, i64::MAX, i64::MAX]).unwrap();
+    assert_eq!(stats.sum, None);
+    assert_eq!(stats.mean, i64::MAX as f64);
+    assert_eq!(stats.variance, 0.0);
+
+    let stats = analyze(&[u128::MAX, 0]).unwrap();
+    assert_eq!(stats.sum, Some(u128::MAX));
+    assert_eq!(stats.mean, u128::MAX as f64 / 2.0);
+
+    // The sum of finite floats overflows to infinity, the mean doesn't
+    let stats = analyze(&[f64::MAX, f64::MAX]).unwrap();
+    assert_eq!(stats.sum, None);
+    assert_eq!(stats.mean, f64::MAX);
+    assert_eq!(stats.variance, 0.0);
+    let stats = analyze(&[f64::MAX, -f64::MAX]).unwrap();
+    assert_eq!(stats.sum, Some(0.0));
+    assert_eq!(stats.mean, 0.0);
+    assert_eq!(stats.median, 0.0);
+    // `f64::MAX` squared is out of range
+    assert_eq!(stats.variance, f64::INFINITY);
+
+    // Tiny items are scaled up just the same
+    let tiny = f64::MIN_POSITIVE;
+    let stats = analyze(&[tiny, 3.0 * tiny]).unwrap();
+    assert_eq!(stats.mean, 2.0 * tiny);
+  }
+}
@@ -0,0 +1,476 @@
+/*
+ * Logs of web events.
+ *
+ * A log has one `WebEvent` per line:
+ *
+ *    load
+ *    key x
+ *    paste "my text"
+ *    click 20 80
+ *    unload
+ *
+ * `key` takes a single character, which is quoted when it is a space or a
+ * quote: `key " "`. The text of `paste` is always quoted, with `\"`, `\\`,
+ * `\n` and `\t` escapes. Blank lines and lines starting with `#` are
+ * skipped. An error tells the line and the column, counted from 1, where the
+ * problem is.
+ *
+ * `replay` feeds the events to a `Handler`. Any `FnMut(WebEvent)` is one,
+ * like `c03::inspect`, and `Session` keeps track of what the events did to
+ * the page, to check a trace.
+ */
+
+use crate::chapters::c03::WebEvent;
+use std::fmt::{self, Display, Formatter};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ErrorKind {
+  UnknownCommand(String),
+  MissingArgument(&'static str),
+  ExtraArgument(String),
+  // Not exactly one character
+  InvalidKey(String),
+  InvalidCoordinate(String),
+  ExpectedQuote,
+  UnterminatedString,
+  InvalidEscape(char),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Error {
+  pub line: usize,
+  pub column: usize,
+  pub kind: ErrorKind,
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "line {}, column {}: ", self.line, self.column)?;
+    match &self.kind {
+      ErrorKind::UnknownCommand(command) => write!(
+        f,
+        "unknown event `{}`, expected load, key, paste, click or unload",
+        command
+      ),
+      ErrorKind::MissingArgument(argument) => write!(f, "missing {}", argument),
+      ErrorKind::ExtraArgument(argument) => write!(f, "unexpected `{}`", argument),
+      ErrorKind::InvalidKey(key) => write!(f, "`{}` is not a single character", key),
+      ErrorKind::InvalidCoordinate(text) => write!(f, "`{}` is not a valid coordinate", text),
+      ErrorKind::ExpectedQuote => write!(f, "the text must be between quotes"),
+      ErrorKind::UnterminatedString => write!(f, "the quoted text is never closed"),
+      ErrorKind::InvalidEscape(c) => write!(f, "invalid escape `\\{}`", c),
+    }
+  }
+}
+
+// Reads the words of one line. Positions are byte offsets in the line.
+struct Cursor<'a> {
+  line: &'a str,
+  position: usize,
+}
+
+impl<'a> Cursor<'a> {
+  fn skip_spaces(&mut self) {
+    let rest = &self.line[self.position..];
+    self.position += rest.len() - rest.trim_start().len();
+  }
+
+  // The next word, and where it starts
+  fn word(&mut self) -> Option<(usize, &'a str)> {
+    self.skip_spaces();
+    let start = self.position;
+    let rest = &self.line[start..];
+    let len = rest.find(char::is_whitespace).unwrap_or(rest.len());
+    self.position += len;
+    if len == 0 {
+      None
+    } else {
+      Some((start, &rest[..len]))
+    }
+  }
+
+  fn peek(&self) -> Option<char> {
+    self.line[self.position..].chars().next()
+  }
+
+  // Text between quotes, with its escapes replaced
+  fn quoted(&mut self) -> Result<String, (usize, ErrorKind)> {
+    let start = self.position;
+    let mut chars = self.line[start + 1..].char_indices();
+    let mut text = String::new();
+    while let Some((i, c)) = chars.next() {
+      match c {
+        '"' => {
+          self.position = start + 1 + i + 1;
+          return Ok(text);
+        }
+        '\\' => match chars.next() {
+          Some((_, '"')) => text.push('"'),
+          Some((_, '\\')) => text.push('\\'),
+          Some((_, 'n')) => text.push('\n'),
+          Some((_, 't')) => text.push('\t'),
+          Some((_, other)) => return Err((start + 1 + i, ErrorKind::InvalidEscape(other))),
+          None => break,
+        },
+        c => text.push(c),
+      }
+    }
+    Err((start, ErrorKind::UnterminatedString))
+  }
+
+  // `missing` names the argument in the error when there is none
+  fn argument(&mut self, missing: &'static str) -> Result<(usize, &'a str), (usize, ErrorKind)> {
+    let end = self.line.trim_end().len();
+    self
+      .word()
+      .ok_or((end, ErrorKind::MissingArgument(missing)))
+  }
+
+  fn coordinate(&mut self, missing: &'static str) -> Result<i64, (usize, ErrorKind)> {
+    let (start, word) = self.argument(missing)?;
+    word
+      .parse()
+      .map_err(|_| (start, ErrorKind::InvalidCoordinate(word.to_owned())))
+  }
+
+  fn end(&mut self) -> Result<(), (usize, ErrorKind)> {
+    match self.word() {
+      Some((start, _)) => {
+        let extra = self.line[start..].trim_end().to_owned();
+        Err((start, ErrorKind::ExtraArgument(extra)))
+      }
+      None => Ok(()),
+    }
+  }
+}
+
+// The event on a line that is neither blank nor a comment
+fn parse_line(line: &str) -> Result<WebEvent, (usize, ErrorKind)> {
+  let mut cursor = Cursor { line, position: 0 };
+  let (start, command) = cursor
+    .word()
+    .ok_or((0, ErrorKind::MissingArgument("an event")))?;
+  let event = match command {
+    "load" => WebEvent::PageLoad,
+    "unload" => WebEvent::PageUnload,
+    "key" => {
+      cursor.skip_spaces();
+      let (start, key) = if cursor.peek() == Some('"') {
+        let start = cursor.position;
+        (start, cursor.quoted()?)
+      } else {
+        let (start, word) = cursor.argument("the key")?;
+        (start, word.to_owned())
+      };
+      let mut chars = key.chars();
+      match (chars.next(), chars.next()) {
+        (Some(c), None) => WebEvent::KeyPress(c),
+        _ => return Err((start, ErrorKind::InvalidKey(key))),
+      }
+    }
+    "paste" => {
+      cursor.skip_spaces();
+      match cursor.peek() {
+        Some('"') => WebEvent::Paste(cursor.quoted()?),
+        Some(_) => return Err((cursor.position, ErrorKind::ExpectedQuote)),
+        None => return Err((cursor.position, ErrorKind::MissingArgument("the text"))),
+      }
+    }
+    "click" => WebEvent::Click {
+      x: cursor.coordinate("the x coordinate")?,
+      y: cursor.coordinate("the y coordinate")?,
+    },
+    _ => return Err((start, ErrorKind::UnknownCommand(command.to_owned()))),
+  };
+  cursor.end()?;
+  Ok(event)
+}
+
+// Every event of the log, or the first error
+pub fn parse(log: &str) -> Result<Vec<WebEvent>, Error> {
+  log
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+    .map(|(i, line)| {
+      parse_line(line).map_err(|(position, kind)| Error {
+        line: i + 1,
+        column: line[..position].chars().count() + 1,
+        kind,
+      })
+    })
+    .collect()
+}
+
+fn quote(text: &str) -> String {
+  let escaped = text
+    .replace('\\', "\\\\")
+    .replace('"', "\\\"")
+    .replace('\n', "\\n")
+    .replace('\t', "\\t");
+  format!("\"{}\"", escaped)
+}
+
+// The line of the log for `event`
+pub fn line(event: &WebEvent) -> String {
+  match event {
+    WebEvent::PageLoad => "load".to_owned(),
+    WebEvent::PageUnload => "unload".to_owned(),
+    WebEvent::KeyPress(c) if c.is_whitespace() || *c == '"' || *c == '\\' => {
+      format!("key {}", quote(&c.to_string()))
+    }
+    WebEvent::KeyPress(c) => format!("key {}", c),
+    WebEvent::Paste(text) => format!("paste {}", quote(text)),
+    WebEvent::Click { x, y } => format!("click {} {}", x, y),
+  }
+}
+
+// A log that `parse` reads back as the same events
+pub fn write(events: &[WebEvent]) -> String {
+  events.iter().map(|event| line(event) + "\n").collect()
+}
+
+// What gets the events of a replay
+pub trait Handler {
+  fn handle(&mut self, event: WebEvent);
+}
+
+impl<F: FnMut(WebEvent)> Handler for F {
+  fn handle(&mut self, event: WebEvent) {
+    self(event)
+  }
+}
+
+// Give every event to `handler`, in order
+pub fn replay<H: Handler + ?Sized>(events: &[WebEvent], handler: &mut H) {
+  for event in events {
+    handler.handle(event.clone());
+  }
+}
+
+// The state of a page after a trace: what was typed or pasted into it, and
+// where it was clicked. An event that can't happen, like a key press before
+// the page is loaded, is a problem.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Session {
+  pub loaded: bool,
+  pub text: String,
+  pub clicks: Vec<(i64, i64)>,
+  pub events: usize,
+  pub problems: Vec<String>,
+}
+
+impl Session {
+  pub fn new() -> Session {
+    Session::default()
+  }
+}
+
+impl Handler for Session {
+  fn handle(&mut self, event: WebEvent) {
+    self.events += 1;
+    let problem = match (&event, self.loaded) {
+      (WebEvent::PageLoad, true) => Some("the page is loaded twice"),
+      (WebEvent::PageLoad, false) => None,
+      (_, false) => Some("the page is not loaded"),
+      _ => None,
+    };
+    if let Some(problem) = problem {
+      self.problems.push(format!(
+        "event {}, `{}`: {}",
+        self.events,
+        line(&event),
+        problem
+      ));
+    }
+    match event {
+      WebEvent::PageLoad => self.loaded = true,
+      WebEvent::PageUnload => self.loaded = false,
+      WebEvent::KeyPress(c) => self.text.push(c),
+      WebEvent::Paste(text) => self.text.push_str(&text),
+      WebEvent::Click { x, y } => self.clicks.push((x, y)),
+    }
+  }
+}
+
+impl Display for Session {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    let clicks: Vec<String> = self
+      .clicks
+      .iter()
+      .map(|(x, y)| format!("({}, {})", x, y))
+      .collect();
+    writeln!(f, "{} events", self.events)?;
+    writeln!(f, "text: {}", quote(&self.text))?;
+    match clicks.len() {
+      0 => writeln!(f, "no clicks")?,
+      _ => writeln!(f, "clicks: {}", clicks.join(", "))?,
+    }
+    writeln!(
+      f,
+      "the page is {}",
+      if self.loaded {
+        "still loaded"
+      } else {
+        "unloaded"
+      }
+    )?;
+    if self.problems.is_empty() {
+      write!(f, "no problems")
+    } else {
+      write!(f, "problems:\n  {}", self.problems.join("\n  "))
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn error(log: &str) -> (usize, usize, ErrorKind) {
+    let error = parse(log).unwrap_err();
+    (error.line, error.column, error.kind)
+  }
+
+  #[test]
+  fn round_trip() {
+    let events = vec![
+      WebEvent::PageLoad,
+      WebEvent::KeyPress('x'),
+      WebEvent::KeyPress(' '),
+      WebEvent::KeyPress('"'),
+      WebEvent::KeyPress('\\'),
+      WebEvent::KeyPress('\t'),
+      WebEvent::KeyPress('#'),
+      WebEvent::KeyPress('é'),
+      WebEvent::Paste("say \"hi\"\n\tthen C:\\go".to_owned()),
+      WebEvent::Paste(String::new()),
+      WebEvent::Click { x: -20, y: 80 },
+      WebEvent::PageUnload,
+    ];
+    let log = write(&events);
+    assert_eq!(parse(&log), Ok(events));
+    assert_eq!(log.lines().count(), 12);
+  }
+
+  #[test]
+  fn lines() {
+    assert_eq!(line(&WebEvent::KeyPress(' ')), "key \" \"");
+    assert_eq!(line(&WebEvent::KeyPress('"')), "key \"\\\"\"");
+    assert_eq!(line(&WebEvent::KeyPress('\\')), "key \"\\\\\"");
+    assert_eq!(
+      line(&WebEvent::Paste("a\"b\\c\nd".to_owned())),
+      "paste \"a\\\"b\\\\c\\nd\""
+    );
+    assert_eq!(line(&WebEvent::Click { x: 1, y: -2 }), "click 1 -2");
+  }
+
+  #[test]
+  fn skips_blank_lines_and_comments() {
+    let log = "# a trace\n\n  load  \n   # indented\nkey a\r\nunload\n";
+    assert_eq!(
+      parse(log),
+      Ok(vec![
+        WebEvent::PageLoad,
+        WebEvent::KeyPress('a'),
+        WebEvent::PageUnload
+      ])
+    );
+  }
+
+  #[test]
+  fn error_positions() {
+    assert_eq!(
+      error("load\n  scroll 3"),
+      (2, 3, ErrorKind::UnknownCommand("scroll".to_owned()))
+    );
+    assert_eq!(error("key"), (1, 4, ErrorKind::MissingArgument("the key")));
+    assert_eq!(
+      error("click 20  "),
+      (1, 9, ErrorKind::MissingArgument("the y coordinate"))
+    );
+    assert_eq!(
+      error("paste"),
+      (1, 6, ErrorKind::MissingArgument("the text"))
+    );
+    assert_eq!(
+      error("load now"),
+      (1, 6, ErrorKind::ExtraArgument("now".to_owned()))
+    );
+    assert_eq!(
+      error("click 1 2 3 4"),
+      (1, 11, ErrorKind::ExtraArgument("3 4".to_owned()))
+    );
+    assert_eq!(
+      error("key ab"),
+      (1, 5, ErrorKind::InvalidKey("ab".to_owned()))
+    );
+    assert_eq!(
+      error("key \"\""),
+      (1, 5, ErrorKind::InvalidKey(String::new()))
+    );
+    assert_eq!(
+      error("click 2O 80"),
+      (1, 7, ErrorKind::InvalidCoordinate("2O".to_owned()))
+    );
+    assert_eq!(error("paste hello"), (1, 7, ErrorKind::ExpectedQuote));
+    assert_eq!(
+      error("load\n\npaste \"never closed"),
+      (3, 7, ErrorKind::UnterminatedString)
+    );
+    assert_eq!(
+      error("paste \"ends with \\\""),
+      (1, 7, ErrorKind::UnterminatedString)
+    );
+    assert_eq!(
+      error("paste \"a\\qb\""),
+      (1, 9, ErrorKind::InvalidEscape('q'))
+    );
+    // Columns count characters, not bytes
+    assert_eq!(
+      error("paste \"é\\x\""),
+      (1, 9, ErrorKind::InvalidEscape('x'))
+    );
+  }
+
+  #[test]
+  fn error_messages() {
+    let error = parse("key").unwrap_err();
+    assert_eq!(error.to_string(), "line 1, column 4: missing the key");
+  }
+
+  #[test]
+  fn session_finds_problems() {
+    let mut session = Session::new();
+    let events = parse("key a\nload\nload\nkey b\nclick 1 2\nunload\npaste \"c\"").unwrap();
+    replay(&events, &mut session);
+    assert_eq!(
+      session.problems,
+      [
+        "event 1, `key a`: the page is not loaded",
+        "event 3, `load`: the page is loaded twice",
+        "event 7, `paste \"c\"`: the page is not loaded",
+      ]
+    );
+    assert_eq!(session.text, "abc");
+    assert_eq!(session.clicks, [(1, 2)]);
+    assert_eq!(session.events, 7);
+    assert!(!session.loaded);
+  }
+
+  #[test]
+  fn clean_session() {
+    let mut session = Session::new();
+    replay(&parse("load\nkey a\nunload\nload").unwrap(), &mut session);
+    assert!(session.problems.is_empty());
+    assert!(session.loaded);
+  }
+
+  #[test]
+  fn closures_are_handlers() {
+    let mut seen = Vec::new();
+    let events = [WebEvent::PageLoad, WebEvent::KeyPress('k')];
+    replay(&events, &mut |event| seen.push(event));
+    assert_eq!(seen, events);
+  }
+}
@@ -0,0 +1,371 @@
+/*
+ * People, read from text.
+ *
+ * `c03::Person` borrows its name, which is fine for literals but not for a
+ * name read from a file. This `Person` owns it, and can only be built with a
+ * name that is not blank and an age from 0 to 150.
+ *
+ * A person is written in one of two forms:
+ *
+ *    Peter,27
+ *    name=Peter age=27
+ *
+ * The first one is a CSV record, so a name with a comma can be quoted. In
+ * the second one the fields can come in any order, but values can't contain
+ * spaces.
+ *
+ * A list of people is a CSV file with a `Name,Age` header, a JSON array of
+ * `{"name": ..., "age": ...}` objects, or one person per line in either form.
+ */
+
+use crate::format::csv;
+use crate::format::json::{self, DecodeError, FromJson, ToJson, Value};
+use crate::format::table::{Align, Column, Record, Style, Table};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+pub const MAX_AGE: u32 = 150;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Error {
+  EmptyName,
+  InvalidAge(String),
+  TooOld(u32),
+  // Neither of the two forms
+  Malformed(String),
+  MissingField(&'static str),
+  UnknownField(String),
+  Csv(csv::Error),
+  Json(json::Error),
+  Decode(DecodeError),
+  // Where the error is in a list of people
+  Line(usize, Box<Error>),
+  Item(usize, Box<Error>),
+}
+
+impl Display for Error {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    match self {
+      Error::EmptyName => write!(f, "the name is empty"),
+      Error::InvalidAge(age) => write!(f, "`{}` is not a valid age", age),
+      Error::TooOld(age) => write!(f, "an age of {} is over {}", age, MAX_AGE),
+      Error::Malformed(text) => write!(
+        f,
+        "`{}` is neither `name,age` nor `name=<name> age=<age>`",
+        text
+      ),
+      Error::MissingField(field) => write!(f, "the {} is missing", field),
+      Error::UnknownField(field) => write!(f, "unknown field `{}`", field),
+      Error::Csv(error) => write!(f, "invalid CSV: {}", error),
+      Error::Json(error) => write!(f, "invalid JSON: {}", error),
+      Error::Decode(error) => write!(f, "invalid people: {}", error),
+      Error::Line(line, error) => write!(f, "line {}: {}", line, error),
+      Error::Item(index, error) => write!(f, "person {}: {}", index, error),
+    }
+  }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Person {
+  name: String,
+  age: u8,
+}
+
+fn parse_age(text: &str) -> Result<u32, Error> {
+  text
+    .trim()
+    .parse()
+    .map_err(|_| Error::InvalidAge(text.trim().to_owned()))
+}
+
+impl Person {
+  // The name is trimmed
+  pub fn new(name: &str, age: u32) -> Result<Person, Error> {
+    let name = name.trim();
+    if name.is_empty() {
+      return Err(Error::EmptyName);
+    }
+    if age > MAX_AGE {
+      return Err(Error::TooOld(age));
+    }
+    Ok(Person {
+      name: name.to_owned(),
+      age: age as u8,
+    })
+  }
+
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn age(&self) -> u8 {
+    self.age
+  }
+
+  // `name=Peter age=27`
+  fn from_fields(text: &str) -> Result<Person, Error> {
+    let (mut name, mut age) = (None, None);
+    for field in text.split_whitespace() {
+      match field.split_once('=') {
+        Some(("name", value)) if name.is_none() => name = Some(value),
+        Some(("age", value)) if age.is_none() => age = Some(parse_age(value)?),
+        Some(("name", _)) | Some(("age", _)) => return Err(Error::Malformed(text.to_owned())),
+        Some((key, _)) => return Err(Error::UnknownField(key.to_owned())),
+        None => return Err(Error::Malformed(text.to_owned())),
+      }
+    }
+    Person::new(
+      name.ok_or(Error::MissingField("name"))?,
+      age.ok_or(Error::MissingField("age"))?,
+    )
+  }
+
+  // `Peter,27`
+  fn from_record(text: &str) -> Result<Person, Error> {
+    let records = csv::parse(text).map_err(Error::Csv)?;
+    match records.as_slice() {
+      [record] => match record.as_slice() {
+        [name, age] => Person::new(name, parse_age(age)?),
+        _ => Err(Error::Malformed(text.to_owned())),
+      },
+      _ => Err(Error::Malformed(text.to_owned())),
+    }
+  }
+}
+
+impl FromStr for Person {
+  type Err = Error;
+
+  fn from_str(text: &str) -> Result<Person, Error> {
+    let text = text.trim();
+    let first = text.split_whitespace().next().unwrap_or("");
+    if first.starts_with("name=") || first.starts_with("age=") {
+      Person::from_fields(text)
+    } else {
+      Person::from_record(text)
+    }
+  }
+}
+
+// `{"name": "Peter", "age": 27}`
+impl ToJson for Person {
+  fn to_json(&self) -> Value {
+    Value::Object(vec![
+      ("name".to_owned(), self.name.to_json()),
+      ("age".to_owned(), self.age.to_json()),
+    ])
+  }
+}
+
+impl FromJson for Person {
+  fn from_json(value: &Value) -> Result<Person, DecodeError> {
+    json::object(value, &["name", "age"])?;
+    let name: String = json::field(value, "name")?;
+    let age: u32 = json::field(value, "age")?;
+    Person::new(&name, age).map_err(|error| DecodeError::Invalid(error.to_string()))
+  }
+}
+
+// `Peter (27)`
+impl Display for Person {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    write!(f, "{} ({})", self.name, self.age)
+  }
+}
+
+impl Record for Person {
+  fn columns() -> Vec<Column> {
+    vec![
+      Column::new("Name", Align::Left),
+      Column::new("Age", Align::Right),
+    ]
+  }
+
+  fn cells(&self) -> Vec<String> {
+    vec![self.name.clone(), self.age.to_string()]
+  }
+}
+
+// A CSV file with a header
+pub fn to_csv(people: &[Person]) -> String {
+  Table::from_records(people).render(Style::Csv)
+}
+
+// The header names the columns, in any order and any case
+pub fn from_csv(text: &str) -> Result<Vec<Person>, Error> {
+  let records = csv::parse(text).map_err(Error::Csv)?;
+  let (header, rows) = match records.split_first() {
+    Some(split) => split,
+    None => return Ok(Vec::new()),
+  };
+  let column = |field: &'static str| {
+    header
+      .iter()
+      .position(|h| h.trim().eq_ignore_ascii_case(field))
+      .ok_or(Error::MissingField(field))
+  };
+  let (name, age) = (column("name")?, column("age")?);
+  if let Some(unknown) = header
+    .iter()
+    .find(|h| !h.trim().eq_ignore_ascii_case("name") && !h.trim().eq_ignore_ascii_case("age"))
+  {
+    return Err(Error::UnknownField(unknown.clone()));
+  }
+  rows
+    .iter()
+    .enumerate()
+    .map(|(i, row)| {
+      let at = |error| Error::Item(i + 1, Box::new(error));
+      if row.len() != header.len() {
+        return Err(at(Error::Malformed(csv::record(row))));
+      }
+      Person::new(&row[name], parse_age(&row[age]).map_err(at)?).map_err(at)
+    })
+    .collect()
+}
+
+// A list of people in any of the formats: JSON when it starts with `[`, CSV
+// when the first line is a header, and otherwise one person per line.
+// Blank lines and lines starting with `#` are skipped.
+pub fn load(text: &str) -> Result<Vec<Person>, Error> {
+  if text.trim_start().starts_with('[') {
+    let value = json::parse(text).map_err(Error::Json)?;
+    return Vec::<Person>::from_json(&value).map_err(Error::Decode);
+  }
+  let is_header = |line: &str| {
+    let mut fields: Vec<String> = line.split(',').map(|f| f.trim().to_lowercase()).collect();
+    fields.sort();
+    fields == ["age", "name"]
+  };
+  if text
+    .lines()
+    .find(|line| !line.trim().is_empty())
+    .is_some_and(is_header)
+  {
+    return from_csv(text);
+  }
+  text
+    .lines()
+    .enumerate()
+    .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+    .map(|(i, line)| {
+      line
+        .parse()
+        .map_err(|error| Error::Line(i + 1, Box::new(error)))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn person(name: &str, age: u32) -> Person {
+    Person::new(name, age).unwrap()
+  }
+
+  #[test]
+  fn validation() {
+    assert_eq!(Person::new("  ", 30), Err(Error::EmptyName));
+    assert_eq!(Person::new("", 30), Err(Error::EmptyName));
+    assert_eq!(Person::new("Old", 151), Err(Error::TooOld(151)));
+    assert_eq!(Person::new("Old", MAX_AGE).map(|p| p.age()), Ok(150));
+    assert_eq!(
+      Person::new(" Peter ", 0).map(|p| p.name().to_owned()),
+      Ok("Peter".to_owned())
+    );
+  }
+
+  #[test]
+  fn both_forms() {
+    assert_eq!("Peter,27".parse(), Ok(person("Peter", 27)));
+    assert_eq!(" Peter , 27 ".parse(), Ok(person("Peter", 27)));
+    assert_eq!("\"Smith, Anna\",31".parse(), Ok(person("Smith, Anna", 31)));
+    assert_eq!("name=Peter age=27".parse(), Ok(person("Peter", 27)));
+    assert_eq!("age=27  name=Peter".parse(), Ok(person("Peter", 27)));
+  }
+
+  #[test]
+  fn invalid_ages() {
+    assert_eq!(
+      "Peter,old".parse::<Person>(),
+      Err(Error::InvalidAge("old".to_owned()))
+    );
+    assert_eq!(
+      "name=Peter age=-1".parse::<Person>(),
+      Err(Error::InvalidAge("-1".to_owned()))
+    );
+    assert_eq!("Peter,151".parse::<Person>(), Err(Error::TooOld(151)));
+    assert_eq!(
+      "age=151 name=Peter".parse::<Person>(),
+      Err(Error::TooOld(151))
+    );
+    assert_eq!(",27".parse::<Person>(), Err(Error::EmptyName));
+    assert_eq!("name= age=27".parse::<Person>(), Err(Error::EmptyName));
+  }
+
+  #[test]
+  fn malformed() {
+    assert_eq!(
+      "Peter".parse::<Person>(),
+      Err(Error::Malformed("Peter".to_owned()))
+    );
+    assert_eq!(
+      "Peter,27,x".parse::<Person>(),
+      Err(Error::Malformed("Peter,27,x".to_owned()))
+    );
+    assert_eq!(
+      "name=Peter".parse::<Person>(),
+      Err(Error::MissingField("age"))
+    );
+    assert_eq!(
+      "name=Peter age=27 city=Oslo".parse::<Person>(),
+      Err(Error::UnknownField("city".to_owned()))
+    );
+    assert_eq!(
+      "name=Peter name=Paul age=27".parse::<Person>(),
+      Err(Error::Malformed("name=Peter name=Paul age=27".to_owned()))
+    );
+  }
+
+  #[test]
+  fn csv_round_trip() {
+    let people = vec![
+      person("Peter", 27),
+      person("Smith, Anna", 31),
+      person("\"Q\"", 0),
+    ];
+    assert_eq!(from_csv(&to_csv(&people)), Ok(people));
+    assert_eq!(
+      from_csv("age,NAME\n27,Peter"),
+      Ok(vec![person("Peter", 27)])
+    );
+    assert_eq!(
+      from_csv("Name,Age\nPeter,27\nAnna,200"),
+      Err(Error::Item(2, Box::new(Error::TooOld(200))))
+    );
+    assert_eq!(from_csv("Name\nPeter"), Err(Error::MissingField("age")));
+  }
+
+  #[test]
+  fn load_every_format() {
+    let expected = vec![person("Peter", 27), person("Anna", 31)];
+    assert_eq!(load("Name,Age\nPeter,27\nAnna,31\n"), Ok(expected.clone()));
+    assert_eq!(
+      load("[{\"name\": \"Peter\", \"age\": 27}, {\"age\": 31, \"name\": \"Anna\"}]"),
+      Ok(expected.clone())
+    );
+    assert_eq!(
+      load("# people\nPeter,27\n\nname=Anna age=31\n"),
+      Ok(expected)
+    );
+    assert_eq!(
+      load("Peter,27\nAnna,x"),
+      Err(Error::Line(2, Box::new(Error::InvalidAge("x".to_owned()))))
+    );
+    assert!(matches!(
+      load("[{\"name\": \"Peter\", \"age\": 151}]"),
+      Err(Error::Decode(_))
+    ));
+  }
+}
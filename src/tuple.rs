@@ -0,0 +1,284 @@
+/*
+ * Tuple toolkit.
+ *
+ * Tuples of different lengths are unrelated types, so the standard library
+ * implements `Debug` and friends one length at a time, and stops at 12: the
+ * 13 element tuple of the `tuples` chapter can't be printed. Here a macro
+ * implements the traits below for every length from 0 to 16:
+ *
+ *  - `Tuple`: `len` and `is_empty`
+ *  - `Reverse`: `(1, "a", true).reverse() == (true, "a", 1)`
+ *  - `Zip`: `(1, 2).zip(("a", "b")) == ((1, "a"), (2, "b"))`
+ *  - `Homogeneous`, for tuples whose items share a type: `map_each`, `swap`
+ *    and `to_vec`
+ *  - `TupleDebug`, which prints any of them
+ *
+ * `debug_tuple!(a, b, c, ...)` prints its arguments like a tuple of any
+ * length would be printed.
+ */
+
+use std::fmt::{self, Debug, Formatter};
+
+pub trait Tuple {
+  const LEN: usize;
+
+  fn len(&self) -> usize {
+    Self::LEN
+  }
+
+  fn is_empty(&self) -> bool {
+    Self::LEN == 0
+  }
+}
+
+pub trait Reverse {
+  type Output;
+
+  fn reverse(self) -> Self::Output;
+}
+
+// Pair up the items of two tuples of the same length
+pub trait Zip<Other> {
+  type Output;
+
+  fn zip(self, other: Other) -> Self::Output;
+}
+
+// A tuple whose items are all `T`
+pub trait Homogeneous<T>: Tuple {
+  type Mapped<U>;
+
+  fn map_each<U, F: FnMut(T) -> U>(self, f: F) -> Self::Mapped<U>;
+
+  // Swap items `i` and `j`. Panics if either is out of bounds, like
+  // `slice::swap`.
+  fn swap(&mut self, i: usize, j: usize);
+
+  fn to_vec(self) -> Vec<T>;
+}
+
+pub trait TupleDebug {
+  fn debug(&self) -> Debugged<'_, Self> {
+    Debugged(self)
+  }
+
+  fn fmt_items(&self, f: &mut Formatter) -> fmt::Result;
+}
+
+// Shows the tuple it borrows with `{:?}` or `{:#?}`
+pub struct Debugged<'a, T: ?Sized>(&'a T);
+
+impl<'a, T: TupleDebug + ?Sized> Debug for Debugged<'a, T> {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    self.0.fmt_items(f)
+  }
+}
+
+// The items of a tuple of any length, for `debug_tuple!`
+pub struct Items<'a>(pub &'a [&'a dyn Debug]);
+
+impl<'a> Debug for Items<'a> {
+  // `debug_tuple` with an empty name prints `(a, b)`, and `(a,)` for a single
+  // item, exactly like the tuples of the standard library. Without any item
+  // it prints nothing at all, so `()` is written here.
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    if self.0.is_empty() {
+      return f.write_str("()");
+    }
+    let mut tuple = f.debug_tuple("");
+    for item in self.0 {
+      tuple.field(item);
+    }
+    tuple.finish()
+  }
+}
+
+// `debug_tuple!(1, 2.5, 'x')` is `"(1, 2.5, 'x')"`, for any number of items
+#[macro_export]
+macro_rules! debug_tuple {
+  ($($item:expr),* $(,)?) => {
+    format!("{:?}", $crate::tuple::Items(&[$(&$item),*]))
+  };
+}
+
+// The tokens in reverse order, as a tuple: `reversed!([a b c])` is `(c, b, a,)`
+macro_rules! reversed {
+  ([] $($reversed:tt)*) => {
+    ($($reversed,)*)
+  };
+  ([$first:tt $($rest:tt)*] $($reversed:tt)*) => {
+    reversed!([$($rest)*] $first $($reversed)*)
+  };
+}
+
+// Replace a token by another, to repeat `T` or `1` once per item
+macro_rules! same {
+  ($_item:tt $replacement:tt) => {
+    $replacement
+  };
+}
+
+// The traits for one length. Each item brings its type and binding, and
+// those of the item of the other tuple for `Zip`.
+macro_rules! tuple {
+  ($($A:ident $a:ident $B:ident $b:ident),*) => {
+    impl<$($A),*> Tuple for ($($A,)*) {
+      const LEN: usize = 0 $(+ same!($A 1))*;
+    }
+
+    impl<$($A),*> Reverse for ($($A,)*) {
+      type Output = reversed!([$($A)*]);
+
+      fn reverse(self) -> Self::Output {
+        let ($($a,)*) = self;
+        reversed!([$($a)*])
+      }
+    }
+
+    impl<$($A,)* $($B),*> Zip<($($B,)*)> for ($($A,)*) {
+      type Output = ($(($A, $B),)*);
+
+      // `()` for the empty tuple
+      #[allow(clippy::unused_unit)]
+      fn zip(self, other: ($($B,)*)) -> Self::Output {
+        let ($($a,)*) = self;
+        let ($($b,)*) = other;
+        ($(($a, $b),)*)
+      }
+    }
+
+    impl<T> Homogeneous<T> for ($(same!($A T),)*) {
+      type Mapped<U> = ($(same!($A U),)*);
+
+      #[allow(unused_mut, unused_variables, clippy::unused_unit)]
+      fn map_each<U, F: FnMut(T) -> U>(self, mut f: F) -> Self::Mapped<U> {
+        let ($($a,)*) = self;
+        ($(f($a),)*)
+      }
+
+      fn swap(&mut self, i: usize, j: usize) {
+        let ($($a,)*) = self;
+        let mut items: Vec<&mut T> = vec![$($a),*];
+        assert!(
+          i < items.len() && j < items.len(),
+          "swap indices {} and {} out of bounds for a tuple of {} items",
+          i,
+          j,
+          items.len()
+        );
+        if i != j {
+          let (left, right) = items.split_at_mut(i.max(j));
+          std::mem::swap(left[i.min(j)], right[0]);
+        }
+      }
+
+      fn to_vec(self) -> Vec<T> {
+        let ($($a,)*) = self;
+        vec![$($a),*]
+      }
+    }
+
+    impl<$($A: Debug),*> TupleDebug for ($($A,)*) {
+      fn fmt_items(&self, f: &mut Formatter) -> fmt::Result {
+        let ($($a,)*) = self;
+        Items(&[$($a as &dyn Debug),*]).fmt(f)
+      }
+    }
+  };
+}
+
+// Every length, by dropping the first item until none are left
+macro_rules! tuples {
+  () => {
+    tuple!();
+  };
+  ($A:ident $a:ident $B:ident $b:ident $(, $RA:ident $ra:ident $RB:ident $rb:ident)*) => {
+    tuple!($A $a $B $b $(, $RA $ra $RB $rb)*);
+    tuples!($($RA $ra $RB $rb),*);
+  };
+}
+
+tuples! {
+  A0 a0 B0 b0, A1 a1 B1 b1, A2 a2 B2 b2, A3 a3 B3 b3, A4 a4 B4 b4, A5 a5 B5 b5,
+  A6 a6 B6 b6, A7 a7 B7 b7, A8 a8 B8 b8, A9 a9 B9 b9, A10 a10 B10 b10,
+  A11 a11 B11 b11, A12 a12 B12 b12, A13 a13 B13 b13, A14 a14 B14 b14,
+  A15 a15 B15 b15
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  type Sixteen = (
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+    u8,
+  );
+
+  fn sixteen() -> Sixteen {
+    (0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15)
+  }
+
+  #[test]
+  fn empty() {
+    assert_eq!(format!("{:?}", ().debug()), format!("{:?}", ()));
+    assert_eq!(format!("{:#?}", ().debug()), format!("{:#?}", ()));
+    assert_eq!(debug_tuple!(), "()");
+    assert_eq!(().len(), 0);
+    assert!(().is_empty());
+    assert_eq!(().reverse(), ());
+    assert_eq!(<() as Homogeneous<u8>>::to_vec(()), Vec::<u8>::new());
+  }
+
+  #[test]
+  fn single() {
+    assert_eq!(format!("{:?}", ("a",).debug()), "(\"a\",)");
+    assert_eq!(format!("{:#?}", ("a",).debug()), format!("{:#?}", ("a",)));
+    assert_eq!(debug_tuple!(1), "(1,)");
+    assert_eq!((1,).len(), 1);
+    assert_eq!((1,).reverse(), (1,));
+    assert_eq!((1,).zip(('x',)), ((1, 'x'),));
+    assert_eq!((2,).map_each(|n| n * 10), (20,));
+  }
+
+  #[test]
+  fn sixteen_items() {
+    // The standard library stops at 12, so compare with the items of a slice
+    let items: Vec<u8> = (0..16).collect();
+    let expected = format!("{:?}", items).replace('[', "(").replace(']', ")");
+    assert_eq!(format!("{:?}", sixteen().debug()), expected);
+    assert_eq!(
+      debug_tuple!(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15),
+      expected
+    );
+    assert_eq!(sixteen().len(), 16);
+    assert_eq!(sixteen().to_vec(), items);
+    let reversed = sixteen().reverse();
+    assert_eq!(reversed.0, 15);
+    assert_eq!(reversed.15, 0);
+    let mut swapped = sixteen();
+    swapped.swap(0, 15);
+    assert_eq!((swapped.0, swapped.15), (15, 0));
+    let zipped = sixteen().zip(sixteen().map_each(|n| n * 2));
+    assert_eq!(zipped.15, (15, 30));
+  }
+
+  #[test]
+  #[should_panic(expected = "out of bounds")]
+  fn swap_out_of_bounds() {
+    sixteen().swap(3, 16);
+  }
+}